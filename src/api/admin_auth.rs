@@ -0,0 +1,59 @@
+use crate::models::ApiError;
+use axum::{
+    extract::{Request, State},
+    http::{header, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::sync::Arc;
+use tracing::warn;
+
+/// The configured `ADMIN_TOKEN`; admin routes are disabled when `None`
+#[derive(Clone)]
+pub struct AdminToken(Option<Arc<str>>);
+
+impl AdminToken {
+    pub fn new(token: Option<&str>) -> Self {
+        Self(token.map(Arc::from))
+    }
+}
+
+/// Require `Authorization: Bearer <ADMIN_TOKEN>` on the admin route group.
+/// Without a configured token every admin request gets 503, never passes through.
+pub async fn require_admin_token(
+    State(token): State<AdminToken>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(ref expected) = token.0 else {
+        return (
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiError::new("admin_disabled", "Admin endpoints are disabled (ADMIN_TOKEN not set)")),
+        )
+            .into_response();
+    };
+
+    let provided = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default();
+
+    if !constant_time_eq(provided.as_bytes(), expected.as_bytes()) {
+        warn!("Rejected admin request to {}", request.uri().path());
+        return (
+            StatusCode::UNAUTHORIZED,
+            Json(ApiError::new("unauthorized", "Missing or invalid admin token")),
+        )
+            .into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Compare secrets without short-circuiting on the first differing byte
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
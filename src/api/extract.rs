@@ -0,0 +1,143 @@
+use crate::api::handlers::AppState;
+use crate::models::ApiError;
+use axum::{
+    async_trait,
+    extract::{FromRequest, Request},
+    http::StatusCode,
+    Json,
+};
+use serde::de::DeserializeOwned;
+use serde_path_to_error::Segment;
+use tracing::debug;
+
+/// JSON body extractor that honours `Config::strict_json`.
+///
+/// Request types use `#[serde(deny_unknown_fields)]`. In strict mode an unknown
+/// field is rejected with a 400 `unknown_field` naming it; in lenient mode (the
+/// default, for compatibility) unknown fields are dropped and parsing retried.
+pub struct ApiJson<T>(pub T);
+
+#[async_trait]
+impl<T: DeserializeOwned> FromRequest<AppState> for ApiJson<T> {
+    type Rejection = (StatusCode, Json<ApiError>);
+
+    async fn from_request(req: Request, state: &AppState) -> Result<Self, Self::Rejection> {
+        let Json(mut value) = Json::<serde_json::Value>::from_request(req, state)
+            .await
+            .map_err(|rejection| {
+                (
+                    rejection.status(),
+                    Json(ApiError::new("invalid_json", rejection.body_text())),
+                )
+            })?;
+
+        loop {
+            let err = match serde_path_to_error::deserialize::<_, T>(value.clone()) {
+                Ok(parsed) => return Ok(ApiJson(parsed)),
+                Err(err) => err,
+            };
+
+            let is_unknown_field = err.inner().to_string().starts_with("unknown field");
+            if !is_unknown_field || state.config.strict_json {
+                let code = if is_unknown_field { "unknown_field" } else { "invalid_request" };
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiError::new(code, err.inner().to_string()).with_details(err.path().to_string())),
+                ));
+            }
+
+            debug!("Ignoring unknown request field {}", err.path());
+            if !remove_path(&mut value, err.path()) {
+                return Err((
+                    StatusCode::BAD_REQUEST,
+                    Json(ApiError::new("invalid_request", err.inner().to_string())),
+                ));
+            }
+        }
+    }
+}
+
+/// Remove the field at `path` from `value`, returning whether it was found
+fn remove_path(value: &mut serde_json::Value, path: &serde_path_to_error::Path) -> bool {
+    let segments: Vec<&Segment> = path.iter().collect();
+    let Some((last, parents)) = segments.split_last() else {
+        return false;
+    };
+
+    let mut current = value;
+    for segment in parents {
+        let next = match segment {
+            Segment::Map { key } => current.get_mut(key.as_str()),
+            Segment::Seq { index } => current.get_mut(*index),
+            _ => None,
+        };
+        match next {
+            Some(next) => current = next,
+            None => return false,
+        }
+    }
+
+    match (last, current.as_object_mut()) {
+        (Segment::Map { key }, Some(object)) => object.remove(key.as_str()).is_some(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PrepareLockRequest;
+    use crate::test_support::{app_state, listings_db};
+
+    fn request(body: &str) -> Request {
+        Request::builder()
+            .method("POST")
+            .header("content-type", "application/json")
+            .body(axum::body::Body::from(body.to_string()))
+            .unwrap()
+    }
+
+    fn state(strict_json: bool) -> AppState {
+        app_state(
+            crate::config::Config {
+                strict_json,
+                ..Default::default()
+            },
+            listings_db(),
+        )
+    }
+
+    const BODY: &str = r#"{"seller_ord_address": "1abc", "seler_ord_address": "typo"}"#;
+
+    #[tokio::test]
+    async fn strict_mode_reports_the_unknown_field() {
+        let result = ApiJson::<PrepareLockRequest>::from_request(request(BODY), &state(true)).await;
+
+        let Err((status, Json(error))) = result else {
+            panic!("unknown field was accepted in strict mode");
+        };
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.error, "unknown_field");
+        assert!(error.message.contains("seler_ord_address"), "{}", error.message);
+    }
+
+    #[tokio::test]
+    async fn lenient_mode_drops_unknown_fields() {
+        let Ok(ApiJson(parsed)) = ApiJson::<PrepareLockRequest>::from_request(request(BODY), &state(false)).await
+        else {
+            panic!("unknown field was rejected in lenient mode");
+        };
+        assert_eq!(parsed.seller_ord_address, "1abc");
+    }
+
+    #[tokio::test]
+    async fn missing_fields_are_rejected_in_either_mode() {
+        for strict in [true, false] {
+            let result = ApiJson::<PrepareLockRequest>::from_request(request("{}"), &state(strict)).await;
+            let Err((_, Json(error))) = result else {
+                panic!("missing field was accepted");
+            };
+            assert_eq!(error.error, "invalid_request");
+        }
+    }
+}
@@ -1,29 +1,42 @@
+use crate::api::extract::ApiJson;
 use crate::cache::CacheManager;
+use crate::metrics::{Metrics, MetricsSnapshot};
 use crate::models::{
     ApiError, HealthCheck, CreateListingRequest, CreateListingResponse,
     CancelListingRequest, PurchaseListingRequest, ListingsResponse, ListingsQuery,
     ListingFees, ListingStatus, PreparePurchaseRequest, PreparePurchaseResponse,
-    BuyerUtxo,
+    BuyerUtxo, PurchaseTxTemplate, StartupReport, ListingEvent, PrepareLockRequest,
+    BroadcastLockRequest, BatchPreparePurchaseRequest, SaleRecord, WalletPortfolio, WalletHistory, PruneRequest,
+    FiatPrice, Listing, PurchaseEstimate, MAX_ADDITIONAL_PAYMENT_ADDRESSES, RepriceListingRequest,
+    BatchListingResult, BatchCreateListingsResponse, CreateOfferRequest, RespondToOfferRequest,
+    PlaceBidRequest, ReadinessCheck, DependencyStatus,
 };
-use crate::services::OrdinalService;
-use crate::services::ListingsDb;
-use crate::services::tx_builder;
+use crate::services::{attributes_from_metadata, OrdinalService, PriceOracle, PurchaseCallbacks, UpstreamHealth};
+use crate::services::{is_revision_conflict, listing_id_for_key, page_start, IdempotencyClaim, ListingWriteError, ListingsDb};
+use crate::services::{signing, tx_builder};
+use crate::validation::validate_bsv_address;
 use axum::{
-    extract::{Path, Query, State},
-    http::{header, StatusCode},
-    response::{IntoResponse, Response},
+    extract::{MatchedPath, Path, Query, Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
     Json,
 };
+use futures_util::stream::{self, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
 use std::time::Instant;
+use tokio::sync::{broadcast, watch};
 use tracing::{error, info};
 use bitcoin::consensus::deserialize;
-use bitcoin::Transaction;
+use bitcoin::{Address, Network, Transaction};
 use hex;
 use reqwest;
-use chrono::Utc;
 
 /// Application state shared across handlers
 #[derive(Clone)]
@@ -33,6 +46,19 @@ pub struct AppState {
     pub listings_db: ListingsDb,
     pub start_time: Instant,
     pub config: crate::config::Config,
+    pub startup_report: Arc<StartupReport>,
+    /// Listing lifecycle events, shared with `ListingsDb` which publishes them
+    pub listing_events: broadcast::Sender<ListingEvent>,
+    /// Flips to `true` when the server starts shutting down
+    pub shutdown: watch::Receiver<bool>,
+    /// Request, GorillaPool and cache counters exported at `/metrics`
+    pub metrics: Arc<Metrics>,
+    /// BSV/USD rate source for fiat-priced listings
+    pub price_oracle: PriceOracle,
+    /// Background view of GorillaPool reachability (degraded mode)
+    pub upstream_health: UpstreamHealth,
+    /// Client for purchase completion callbacks to checkout pages
+    pub purchase_callbacks: PurchaseCallbacks,
 }
 
 // ============================================================================
@@ -65,6 +91,40 @@ pub struct OrdinalResponse {
 pub struct FeeCalculationResponse {
     pub success: bool,
     pub fees: ListingFees,
+    /// Estimated miner fee for a typical purchase, when requested
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_fee: Option<u64>,
+    /// USD equivalents, when requested and the exchange rate is available
+    #[serde(flatten)]
+    pub usd: Option<FeesUsd>,
+}
+
+/// `ListingFees` converted to USD at the price oracle rate
+#[derive(Serialize)]
+pub struct FeesUsd {
+    /// Price of one BSV in USD used for the conversion
+    pub bsv_usd_rate: f64,
+    pub total_price_usd: f64,
+    pub seller_receives_usd: f64,
+    pub marketplace_fee_usd: f64,
+    pub tip_amount_usd: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_fee_usd: Option<f64>,
+}
+
+impl FeesUsd {
+    fn convert(fees: &ListingFees, network_fee: Option<u64>, rate: f64) -> Self {
+        // Rounded to the cent for display
+        let usd = |sats: u64| (sats as f64 / 100_000_000.0 * rate * 100.0).round() / 100.0;
+        Self {
+            bsv_usd_rate: rate,
+            total_price_usd: usd(fees.total_price),
+            seller_receives_usd: usd(fees.seller_receives),
+            marketplace_fee_usd: usd(fees.marketplace_fee),
+            tip_amount_usd: usd(fees.tip_amount),
+            network_fee_usd: network_fee.map(usd),
+        }
+    }
 }
 
 // ============================================================================
@@ -79,17 +139,52 @@ pub async fn root() -> impl IntoResponse {
         "endpoints": {
             "GET /": "This help message",
             "GET /health": "Health check and cache stats",
+            "GET /health/ready": "Readiness check probing GorillaPool and the database",
+            "GET /stats": "Marketplace listing and sales statistics",
+            "GET /metrics": "Prometheus metrics (text exposition format)",
+            "GET /fee-address": "Marketplace fee address with signed attestation",
+            "GET /events": "Server-Sent Events stream of listing created/cancelled/expired/sold",
+            "GET /admin/startup-report": "Startup validation report",
+            "DELETE /admin/cache/content/:origin": "Evict an ordinal's cached content",
+            "POST /admin/cache/clear": "Empty every cache (admin token)",
+            "POST /admin/cache/invalidate/wallet/:address": "Drop a wallet's cached ordinals (admin token)",
+            "POST /admin/cache/invalidate/ordinal/:origin": "Drop an ordinal's cached details and content (admin token)",
+            "POST /admin/prune": "Delete old sold/cancelled/expired listings (admin token)",
             "GET /wallet/:address": "Get all ordinals for a wallet address",
+            "GET /wallet/:address/history": "Seller listings in every status and purchases, with realized totals",
+            "GET /watchlist/:address": "Watched listings with their current state",
+            "POST /watchlist/:address/:listing_id": "Watch a listing",
+            "DELETE /watchlist/:address/:listing_id": "Stop watching a listing",
+            "GET /wallet/:address/portfolio": "Held ordinals, active listings and realized sales",
             "GET /ordinal/:origin": "Get details for a specific ordinal",
+            "GET /ordinal/:origin/preview?w=256": "Downscaled JPEG preview of an image inscription",
             "GET /ordinal/:origin/content": "Get ordinal content (image/file)",
-            "GET /listings": "Get active marketplace listings",
+            "GET /collections": "Collections among active listings with counts, most listed first",
+            "GET /collections/:collection_id/holders": "Distinct owners of a collection",
+            "GET /listings": "Get active marketplace listings (filter traits with attr_<key>=<value>, or by collection_id)",
             "GET /listings/:id": "Get a specific listing",
+            "GET /listings/seq/:seq": "Get a listing by sequence number",
             "POST /listings": "Create a new listing",
+            "POST /listings/batch": "Create several listings, with a result per item",
             "POST /listings/:id/cancel": "Cancel a listing",
+            "POST /listings/:id/reprice": "Change an active listing's price",
+            "GET /listings/:id/offers": "Offers on a listing",
+            "POST /listings/:id/offers": "Make an offer below the asking price",
+            "POST /offers/:id/accept": "Accept an offer, reserving the listing for its buyer",
+            "POST /offers/:id/reject": "Reject an offer",
+            "GET /listings/:id/bids": "Bids on an auction listing",
+            "POST /listings/:id/bids": "Bid on an auction listing",
+            "POST /listings/:id/prepare-lock-tx": "Prepare unsigned TX locking the ordinal on-chain",
+            "POST /listings/:id/broadcast-lock": "Broadcast the signed lock TX",
+            "GET /listings/:id/tx-template": "Get purchase TX inputs/outputs without buyer funding",
             "POST /listings/:id/prepare-purchase": "Prepare unsigned TX for Yours Wallet purchase",
+            "POST /listings/:id/estimate": "Size, miner fee and change of a prospective purchase",
             "POST /listings/:id/broadcast-purchase": "Broadcast signed purchase TX (Yours Wallet)",
-            "POST /listings/:id/purchase": "Purchase a listing",
-            "GET /fees/calculate": "Calculate listing fees",
+            "POST /listings/:id/purchase": "Build unsigned purchase TX from supplied payment UTXOs",
+            "POST /listings/:id/purchase-handcash": "Purchase a listing via HandCash",
+            "POST /purchases/prepare-batch": "Prepare one unsigned TX buying several listings",
+            "GET /sales/recent?limit=20": "Recently sold listings, newest first",
+            "GET /fees/calculate": "Calculate listing fees (include_network_fee=true adds a miner fee estimate, currency=usd adds USD amounts)",
         },
         "documentation": "https://docs.1satordinals.com/public-apis",
         "powered_by": "GorillaPool 1Sat API"
@@ -103,8 +198,10 @@ pub async fn health(State(state): State<AppState>) -> impl IntoResponse {
     let cache_stats = state.cache.stats();
     let listings_count = state.listings_db.count_active_listings();
     
+    // Still alive while degraded, so this stays 200; `/health/ready` fails instead
+    let status = if state.upstream_health.is_degraded() { "degraded" } else { "healthy" };
     Json(HealthCheck {
-        status: "healthy".to_string(),
+        status: status.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
         uptime_seconds: uptime,
         cache_stats,
@@ -112,6 +209,308 @@ pub async fn health(State(state): State<AppState>) -> impl IntoResponse {
     })
 }
 
+/// How long a readiness probe may wait on one dependency
+const READINESS_PROBE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(5);
+
+/// GET /health/ready
+/// Readiness check for load balancers: pings GorillaPool and reads the database,
+/// answering 503 if either fails. `/health` stays the liveness check.
+pub async fn health_ready(State(state): State<AppState>) -> impl IntoResponse {
+    let gorillapool = probe_dependency(state.ordinal_service.gorillapool().ping()).await;
+    let database = probe_dependency(async { state.listings_db.probe() }).await;
+
+    let ready = gorillapool.ok && database.ok;
+    if !ready {
+        tracing::warn!(
+            "Readiness check failed: gorillapool={:?} database={:?}",
+            gorillapool.error, database.error
+        );
+    }
+
+    let status = if ready { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (
+        status,
+        Json(ReadinessCheck {
+            status: if ready { "ready" } else { "not_ready" }.to_string(),
+            gorillapool,
+            database,
+        }),
+    )
+}
+
+/// Time a dependency probe, failing it if it takes longer than `READINESS_PROBE_TIMEOUT`
+async fn probe_dependency(probe: impl std::future::Future<Output = anyhow::Result<()>>) -> DependencyStatus {
+    let started = Instant::now();
+    let error = match tokio::time::timeout(READINESS_PROBE_TIMEOUT, probe).await {
+        Ok(Ok(())) => None,
+        Ok(Err(e)) => Some(e.to_string()),
+        Err(_) => Some(format!("No response within {}s", READINESS_PROBE_TIMEOUT.as_secs())),
+    };
+
+    DependencyStatus {
+        ok: error.is_none(),
+        latency_ms: started.elapsed().as_millis() as u64,
+        error,
+    }
+}
+
+/// Startup validation report (config redacted)
+pub async fn startup_report(State(state): State<AppState>) -> impl IntoResponse {
+    Json(state.startup_report.as_ref().clone())
+}
+
+/// Drop a single ordinal's cached content
+pub async fn evict_content(
+    Path(origin): Path<String>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let evicted = state.cache.invalidate_content(&origin).await;
+    info!("Admin content eviction for {}: evicted={}", origin, evicted);
+
+    Json(json!({
+        "success": true,
+        "origin": origin,
+        "evicted": evicted
+    }))
+}
+
+/// Empty every cache, reporting how many entries each held
+pub async fn clear_caches(State(state): State<AppState>) -> impl IntoResponse {
+    let cleared = state.cache.clear_all().await;
+    info!("Admin cache clear: {:?}", cleared);
+
+    Json(json!({
+        "success": true,
+        "cleared": cleared
+    }))
+}
+
+/// Drop a wallet's cached ordinals so the next lookup refetches them
+pub async fn invalidate_wallet_cache(
+    Path(address): Path<String>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let invalidated = state.cache.invalidate_wallet(&address).await;
+    info!("Admin wallet cache invalidation for {}: invalidated={}", address, invalidated);
+
+    Json(json!({
+        "success": true,
+        "address": address,
+        "invalidated": invalidated
+    }))
+}
+
+/// Drop an ordinal's cached details and content, e.g. after it was transferred
+pub async fn invalidate_ordinal_cache(
+    Path(origin): Path<String>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    let details = state.cache.invalidate_ordinal(&origin).await;
+    let content = state.cache.invalidate_content(&origin).await;
+    info!("Admin ordinal cache invalidation for {}: details={}, content={}", origin, details, content);
+
+    Json(json!({
+        "success": true,
+        "origin": origin,
+        "invalidated": {
+            "details": details,
+            "content": content
+        }
+    }))
+}
+
+/// Delete old sold/cancelled/expired listings
+pub async fn prune_listings(
+    State(state): State<AppState>,
+    ApiJson(request): ApiJson<PruneRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    let older_than = std::time::Duration::from_secs(request.older_than_secs);
+    match state.listings_db.prune_closed(older_than, request.archive_sold) {
+        Ok(pruned) => {
+            info!("Admin prune: removed {} closed listings", pruned);
+            Ok(Json(json!({
+                "success": true,
+                "pruned": pruned
+            })))
+        }
+        Err(e) => {
+            error!("Failed to prune listings: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("db_error", "Failed to prune listings").with_details(e.to_string())),
+            ))
+        }
+    }
+}
+
+/// Aggregate marketplace statistics (cached for 30 seconds)
+pub async fn get_stats(State(state): State<AppState>) -> impl IntoResponse {
+    let stats = match state.cache.get_marketplace_stats().await {
+        Some(stats) => stats,
+        None => {
+            let stats = state.listings_db.aggregate_stats();
+            state.cache.set_marketplace_stats(&stats).await;
+            stats
+        }
+    };
+
+    Json(json!({
+        "success": true,
+        "stats": stats
+    }))
+}
+
+/// Prometheus scrape endpoint
+pub async fn get_metrics(State(state): State<AppState>) -> impl IntoResponse {
+    let (cache_hits, cache_misses) = state.cache.hit_counts();
+    let snapshot = MetricsSnapshot {
+        cache_hits,
+        cache_misses,
+        active_listings: state.listings_db.count_active_listings(),
+    };
+
+    (
+        [(header::CONTENT_TYPE, "text/plain; version=0.0.4")],
+        state.metrics.render(&snapshot),
+    )
+}
+
+/// Count each request against its matched route pattern (e.g. `/listings/:id`)
+pub async fn track_requests(
+    State(metrics): State<Arc<Metrics>>,
+    matched_path: Option<MatchedPath>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let method = request.method().to_string();
+    let route = matched_path
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| "unmatched".to_string());
+
+    let response = next.run(request).await;
+    metrics.record_request(&method, &route, response.status().as_u16());
+    response
+}
+
+/// How long SSE clients should wait before reconnecting
+const EVENTS_RETRY: std::time::Duration = std::time::Duration::from_secs(3);
+
+/// Stream listing lifecycle events as Server-Sent Events
+pub async fn listing_events(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, axum::Error>>> {
+    let rx = state.listing_events.subscribe();
+
+    let retry = stream::once(async { Ok(Event::default().retry(EVENTS_RETRY).comment("connected")) });
+    let events = stream::unfold(rx, |mut rx| async move {
+        loop {
+            match rx.recv().await {
+                Ok(event) => {
+                    let sse = Event::default().event(event.name()).json_data(&event);
+                    return Some((sse, rx));
+                }
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    tracing::warn!("SSE subscriber lagged, skipped {} listing events", skipped);
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    });
+
+    let mut shutdown = state.shutdown.clone();
+    let shutting_down = async move {
+        let _ = shutdown.wait_for(|stopping| *stopping).await;
+    };
+
+    Sse::new(retry.chain(events).take_until(shutting_down)).keep_alive(KeepAlive::default())
+}
+
+/// Marketplace fee address with a signature over it from the attestation key,
+/// so clients can pin the signer and detect a tampered address
+pub async fn get_fee_address(
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    let address = state.config.fee_address_at(chrono::Utc::now());
+
+    let Some(ref wif) = state.config.attestation_wif else {
+        return Ok(Json(json!({
+            "success": true,
+            "address": address,
+            "signature": null,
+            "signer_address": null
+        })));
+    };
+
+    let signed = signing::sign_message(wif, address, state.config.network).map_err(|e| {
+        error!("Failed to sign fee address attestation: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("attestation_error", "Failed to sign fee address")),
+        )
+    })?;
+
+    Ok(Json(json!({
+        "success": true,
+        "address": address,
+        "signature": signed.signature,
+        "signer_address": signed.signer_address
+    })))
+}
+
+// ============================================================================
+// Validation helpers
+// ============================================================================
+
+/// Validate a BSV address from a request, mapping failures to a 400 naming the field
+fn check_address(field: &str, address: &str, network: Network) -> Result<(), (StatusCode, Json<ApiError>)> {
+    validate_bsv_address(address, network).map_err(|e| {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_address", format!("Invalid BSV address in `{}`", field))
+                .with_details(e.to_string())),
+        )
+    })
+}
+
+/// How old a signed seller request may be before it's rejected as stale
+const SIGNATURE_MAX_AGE_SECS: i64 = 300;
+
+/// Signatures dated this far ahead of server time are tolerated for clock skew
+const SIGNATURE_CLOCK_SKEW_SECS: i64 = 60;
+
+/// Verify a seller's recent Bitcoin Signed Message over `message`, rejecting
+/// stale or mismatched signatures with 401
+fn check_signature(
+    address: &str,
+    message: &str,
+    signature: &str,
+    signed_at: i64,
+    network: Network,
+) -> Result<(), (StatusCode, Json<ApiError>)> {
+    let age = chrono::Utc::now().timestamp() - signed_at;
+    if !(-SIGNATURE_CLOCK_SKEW_SECS..=SIGNATURE_MAX_AGE_SECS).contains(&age) {
+        return Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiError::new("stale_signature", format!(
+                "signed_at must be within the last {} seconds",
+                SIGNATURE_MAX_AGE_SECS
+            ))),
+        ));
+    }
+
+    match signing::verify_message(address, message, signature, network) {
+        Ok(true) => Ok(()),
+        Ok(false) => Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiError::new("invalid_signature", "Signature was not made by the seller's ordinal address")),
+        )),
+        Err(e) => Err((
+            StatusCode::UNAUTHORIZED,
+            Json(ApiError::new("invalid_signature", "Signature could not be verified").with_details(e.to_string())),
+        )),
+    }
+}
+
 // ============================================================================
 // Wallet Handlers
 // ============================================================================
@@ -124,14 +523,11 @@ pub async fn get_wallet_ordinals(
 ) -> Result<Json<WalletResponse>, (StatusCode, Json<ApiError>)> {
     info!("Wallet lookup request: {} (refresh={})", address, params.refresh);
     
-    if address.is_empty() || address.len() < 20 || address.len() > 64 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ApiError::new("invalid_address", "Address format is invalid")),
-        ));
-    }
+    check_address("address", &address, state.config.network)?;
 
-    let result = if params.refresh {
+    // A forced refresh would only fail while GorillaPool is down; serve the cache instead
+    let refresh = params.refresh && !state.upstream_health.is_degraded();
+    let result = if refresh {
         state.ordinal_service.refresh_wallet(&address).await
     } else {
         state.ordinal_service.get_wallet_ordinals(&address).await
@@ -155,6 +551,184 @@ pub async fn get_wallet_ordinals(
     }
 }
 
+/// Held ordinals, active listings and realized sales for a wallet
+pub async fn get_wallet_portfolio(
+    Path(address): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    info!("Portfolio request: {}", address);
+
+    check_address("address", &address, state.config.network)?;
+
+    let wallet = state.ordinal_service.get_wallet_ordinals(&address).await.map_err(|e| {
+        error!("Failed to fetch wallet ordinals: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("fetch_error", "Failed to fetch ordinals").with_details(e.to_string())),
+        )
+    })?;
+
+    let listings = state.listings_db.get_listings_by_seller(&address).map_err(|e| {
+        error!("Failed to get listings by seller: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("db_error", "Failed to fetch listings")),
+        )
+    })?;
+
+    let (active_listings, realized_sales): (Vec<_>, Vec<_>) = listings
+        .into_iter()
+        .filter(|l| matches!(l.status, ListingStatus::Active | ListingStatus::Sold))
+        .partition(|l| l.status == ListingStatus::Active);
+
+    let portfolio = WalletPortfolio {
+        address,
+        held: wallet.ordinals,
+        realized_value_sats: realized_sales.iter().map(|l| l.fees.seller_receives).sum(),
+        unrealized_value_sats: active_listings.iter().map(|l| l.fees.seller_receives).sum(),
+        active_listings,
+        realized_sales,
+        computed_at: chrono::Utc::now(),
+    };
+
+    Ok(Json(json!({
+        "success": true,
+        "data": portfolio
+    })))
+}
+
+/// A wallet's listings in every status and its purchases, with realized totals
+pub async fn get_wallet_history(
+    Path(address): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    info!("Wallet history request: {}", address);
+
+    check_address("address", &address, state.config.network)?;
+
+    let db_error = |e: anyhow::Error| {
+        error!("Failed to load wallet history: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("db_error", "Failed to fetch wallet history")),
+        )
+    };
+    let mut listings = state.listings_db.get_listings_by_seller(&address).map_err(db_error)?;
+    let mut purchases = state.listings_db.get_purchases_by_buyer(&address).map_err(db_error)?;
+    listings.sort_by_key(|l| std::cmp::Reverse(l.updated_at));
+    purchases.sort_by_key(|l| std::cmp::Reverse(l.updated_at));
+
+    let sold: Vec<_> = listings.iter().filter(|l| l.status == ListingStatus::Sold).collect();
+    let history = WalletHistory {
+        address,
+        sales_count: sold.len(),
+        total_sold_sats: sold.iter().map(|l| l.fees.seller_receives).sum(),
+        total_spent_sats: purchases.iter().map(|l| l.fees.total_price).sum(),
+        listings,
+        purchases,
+        computed_at: chrono::Utc::now(),
+    };
+
+    Ok(Json(json!({
+        "success": true,
+        "data": history
+    })))
+}
+
+/// Most listings one address may watch
+const MAX_WATCHLIST_ENTRIES: usize = 500;
+
+/// POST /watchlist/:address/:listing_id
+/// Watch a listing. Unauthenticated for now: anyone may edit any address's watchlist.
+pub async fn watch_listing(
+    Path((address, listing_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    check_address("address", &address, state.config.network)?;
+
+    let db_error = |e: anyhow::Error| {
+        error!("Failed to update watchlist: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("db_error", "Failed to update watchlist")),
+        )
+    };
+
+    if state.listings_db.get_listing(&listing_id).map_err(db_error)?.is_none() {
+        return Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))));
+    }
+
+    if state.listings_db.watchlist_len(&address) >= MAX_WATCHLIST_ENTRIES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "watchlist_full",
+                format!("A watchlist can hold at most {} listings", MAX_WATCHLIST_ENTRIES),
+            )),
+        ));
+    }
+
+    let added = state.listings_db.watch_listing(&address, &listing_id).map_err(db_error)?;
+
+    Ok(Json(json!({
+        "success": true,
+        "listing_id": listing_id,
+        "added": added
+    })))
+}
+
+/// DELETE /watchlist/:address/:listing_id
+pub async fn unwatch_listing(
+    Path((address, listing_id)): Path<(String, String)>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    check_address("address", &address, state.config.network)?;
+
+    let removed = state.listings_db.unwatch_listing(&address, &listing_id).map_err(|e| {
+        error!("Failed to update watchlist: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("db_error", "Failed to update watchlist")),
+        )
+    })?;
+
+    if !removed {
+        return Err((
+            StatusCode::NOT_FOUND,
+            Json(ApiError::new("not_watching", "Listing is not on this watchlist")),
+        ));
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "listing_id": listing_id
+    })))
+}
+
+/// GET /watchlist/:address
+/// Watched listings joined to their current state, most recently watched first
+pub async fn get_watchlist(
+    Path(address): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    check_address("address", &address, state.config.network)?;
+
+    let items = state.listings_db.get_watchlist(&address).map_err(|e| {
+        error!("Failed to load watchlist: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("db_error", "Failed to fetch watchlist")),
+        )
+    })?;
+
+    Ok(Json(json!({
+        "success": true,
+        "address": address,
+        "count": items.len(),
+        "data": items
+    })))
+}
+
 /// Get ordinal details
 pub async fn get_ordinal_details(
     Path(origin): Path<String>,
@@ -182,23 +756,83 @@ pub async fn get_ordinal_details(
     }
 }
 
-/// Get ordinal content
+/// Get ordinal content (supports single `Range: bytes=` requests for media seeking)
 pub async fn get_ordinal_content(
     Path(origin): Path<String>,
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<Response, (StatusCode, Json<ApiError>)> {
     info!("Content request: {}", origin);
 
-    match state.ordinal_service.get_ordinal_content(&origin).await {
-        Ok((content, content_type)) => {
-            Ok((
-                StatusCode::OK,
+    let details = state
+        .ordinal_service
+        .get_ordinal_details(&origin)
+        .await
+        .ok()
+        .flatten();
+    let block_height = details.as_ref().and_then(|d| d.block_height);
+    let chain_tip = match block_height {
+        Some(_) => state.ordinal_service.chain_tip().await,
+        None => None,
+    };
+    let cache_control = content_cache_control(block_height, chain_tip).to_string();
+
+    // Inscription content is immutable, so its hash makes a strong validator
+    let etag = details
+        .and_then(|d| d.content_hash)
+        .map(|hash| format!("\"{}\"", hash));
+
+    if let Some(etag) = &etag {
+        let if_none_match = headers.get(header::IF_NONE_MATCH).and_then(|v| v.to_str().ok());
+        if if_none_match.is_some_and(|inm| etag_matches(inm, etag)) {
+            return Ok((
+                StatusCode::NOT_MODIFIED,
                 [
-                    (header::CONTENT_TYPE, content_type),
-                    (header::CACHE_CONTROL, "public, max-age=86400".to_string()),
+                    (header::ETAG, etag.clone()),
+                    (header::CACHE_CONTROL, cache_control),
                 ],
-                content,
-            ).into_response())
+            ).into_response());
+        }
+    }
+
+    match state.ordinal_service.get_ordinal_content(&origin).await {
+        Ok((content, content_type)) => {
+            let total = content.len() as u64;
+            let range = headers.get(header::RANGE).and_then(|v| v.to_str().ok());
+
+            let mut response = match range.map(|r| parse_byte_range(r, total)) {
+                None => (
+                    StatusCode::OK,
+                    [
+                        (header::CONTENT_TYPE, content_type),
+                        (header::CACHE_CONTROL, cache_control),
+                        (header::ACCEPT_RANGES, "bytes".to_string()),
+                    ],
+                    content,
+                ).into_response(),
+                Some(Some((start, end))) => {
+                    let body = content[start as usize..=end as usize].to_vec();
+                    (
+                        StatusCode::PARTIAL_CONTENT,
+                        [
+                            (header::CONTENT_TYPE, content_type),
+                            (header::CACHE_CONTROL, cache_control),
+                            (header::ACCEPT_RANGES, "bytes".to_string()),
+                            (header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, end, total)),
+                        ],
+                        body,
+                    ).into_response()
+                }
+                Some(None) => return Ok((
+                    StatusCode::RANGE_NOT_SATISFIABLE,
+                    [(header::CONTENT_RANGE, format!("bytes */{}", total))],
+                ).into_response()),
+            };
+
+            if let Some(value) = etag.and_then(|e| HeaderValue::from_str(&e).ok()) {
+                response.headers_mut().insert(header::ETAG, value);
+            }
+            Ok(response)
         }
         Err(e) => {
             error!("Failed to fetch ordinal content: {}", e);
@@ -210,70 +844,363 @@ pub async fn get_ordinal_content(
     }
 }
 
-// ============================================================================
-// Listings Handlers
-// ============================================================================
+/// Whether an `If-None-Match` header value matches `etag` (weak comparison, as
+/// RFC 9110 requires for this header)
+fn etag_matches(if_none_match: &str, etag: &str) -> bool {
+    let etag = etag.trim_start_matches("W/");
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|candidate| candidate == "*" || candidate.trim_start_matches("W/") == etag)
+}
 
-/// Calculate fees for a listing
+/// Query parameters for image previews
 #[derive(Debug, Deserialize)]
-pub struct FeeCalcQuery {
-    pub amount: u64,
-    #[serde(default)]
-    pub tip_percent: f64,
+pub struct PreviewQuery {
+    /// Maximum width in pixels
+    pub w: Option<u32>,
 }
 
-pub async fn calculate_fees(
-    Query(params): Query<FeeCalcQuery>,
-) -> Result<Json<FeeCalculationResponse>, (StatusCode, Json<ApiError>)> {
-    let fees = ListingFees::calculate(params.amount, params.tip_percent);
-    Ok(Json(FeeCalculationResponse { success: true, fees }))
-}
+const DEFAULT_PREVIEW_WIDTH: u32 = 256;
+const MIN_PREVIEW_WIDTH: u32 = 16;
+const MAX_PREVIEW_WIDTH: u32 = 1024;
 
-/// Get active listings
-pub async fn get_listings(
-    Query(params): Query<ListingsQuery>,
+/// Downscaled JPEG preview of an image inscription
+pub async fn get_ordinal_preview(
+    Path(origin): Path<String>,
     State(state): State<AppState>,
-) -> Result<Json<ListingsResponse>, (StatusCode, Json<ApiError>)> {
-    info!("Get listings: page={}, per_page={}", params.page, params.per_page);
+    Query(query): Query<PreviewQuery>,
+) -> Result<Response, (StatusCode, Json<ApiError>)> {
+    let width = query
+        .w
+        .unwrap_or(DEFAULT_PREVIEW_WIDTH)
+        .clamp(MIN_PREVIEW_WIDTH, MAX_PREVIEW_WIDTH);
+    info!("Preview request: {} @{}px", origin, width);
+
+    match state.ordinal_service.get_ordinal_preview(&origin, width).await {
+        Ok(Some(jpeg)) => Ok((
+            StatusCode::OK,
+            [
+                (header::CONTENT_TYPE, "image/jpeg"),
+                (header::CACHE_CONTROL, "public, max-age=86400"),
+            ],
+            jpeg,
+        )
+            .into_response()),
+        Ok(None) => Err((
+            StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            Json(ApiError::new("not_an_image", "Previews are only available for image inscriptions")),
+        )),
+        Err(e) => {
+            error!("Failed to build ordinal preview: {}", e);
+            Err((
+                StatusCode::NOT_FOUND,
+                Json(ApiError::new("content_error", "Failed to fetch content").with_details(e.to_string())),
+            ))
+        }
+    }
+}
 
-    if let Some(ref seller) = params.seller {
-        match state.listings_db.get_listings_by_seller(seller) {
-            Ok(listings) => {
-                let total = listings.len();
-                Ok(Json(ListingsResponse {
-                    success: true,
-                    listings,
-                    total,
-                    page: 1,
-                    per_page: total,
-                }))
-            }
-            Err(e) => {
-                error!("Failed to get listings by seller: {}", e);
-                Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiError::new("db_error", "Failed to fetch listings")),
-                ))
-            }
+/// Confirmations after which inscription content is treated as final
+const CONTENT_FINAL_CONFIRMATIONS: u64 = 6;
+
+/// Cache-Control for inscription content: immutable once buried deep enough to
+/// be safe from a re-org, otherwise short-lived
+fn content_cache_control(block_height: Option<u64>, chain_tip: Option<u64>) -> &'static str {
+    let confirmations = match (block_height, chain_tip) {
+        (Some(height), Some(tip)) if tip >= height => tip - height + 1,
+        _ => 0,
+    };
+
+    if confirmations >= CONTENT_FINAL_CONFIRMATIONS {
+        "public, max-age=31536000, immutable"
+    } else {
+        "public, max-age=60"
+    }
+}
+
+/// Parse a single `bytes=start-end` range against a body of `total` bytes.
+///
+/// Returns the inclusive `(start, end)` byte offsets, or `None` if the range is
+/// malformed, uses multiple ranges, or can't be satisfied.
+fn parse_byte_range(range: &str, total: u64) -> Option<(u64, u64)> {
+    let spec = range.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') || total == 0 {
+        return None;
+    }
+
+    let (start, end) = spec.split_once('-')?;
+    let (start, end) = (start.trim(), end.trim());
+
+    let (start, end) = if start.is_empty() {
+        // Suffix range: the last N bytes
+        let suffix: u64 = end.parse().ok()?;
+        if suffix == 0 {
+            return None;
         }
+        (total.saturating_sub(suffix), total - 1)
     } else {
-        match state.listings_db.get_active_listings(params.page, params.per_page) {
-            Ok((listings, total)) => {
-                Ok(Json(ListingsResponse {
-                    success: true,
-                    listings,
-                    total,
-                    page: params.page,
-                    per_page: params.per_page,
-                }))
-            }
-            Err(e) => {
-                error!("Failed to get listings: {}", e);
-                Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    Json(ApiError::new("db_error", "Failed to fetch listings")),
-                ))
-            }
+        let start: u64 = start.parse().ok()?;
+        let end = if end.is_empty() {
+            total - 1
+        } else {
+            end.parse::<u64>().ok()?.min(total - 1)
+        };
+        (start, end)
+    };
+
+    if start > end || start >= total {
+        return None;
+    }
+
+    Some((start, end))
+}
+
+// ============================================================================
+// Collection Handlers
+// ============================================================================
+
+#[derive(Debug, Deserialize)]
+pub struct HoldersQuery {
+    /// Number of top holders to include (0 for counts only)
+    #[serde(default)]
+    pub top: usize,
+}
+
+/// Get the number of distinct owners of a collection
+pub async fn get_collection_holders(
+    Path(collection_id): Path<String>,
+    Query(params): Query<HoldersQuery>,
+    State(state): State<AppState>,
+) -> impl IntoResponse {
+    info!("Collection holders request: {} (top={})", collection_id, params.top);
+
+    let mut holders = state.ordinal_service.get_collection_holders(&collection_id).await;
+    holders.top_holders.truncate(params.top);
+
+    Json(json!({
+        "success": true,
+        "data": holders
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CollectionsQuery {
+    #[serde(default = "default_page")]
+    pub page: usize,
+    #[serde(default = "default_per_page")]
+    pub per_page: usize,
+}
+
+/// Collections with active listings, most listings first
+pub async fn list_collections(
+    Query(params): Query<CollectionsQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    if params.page == 0 || params.per_page == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_pagination", "page and per_page must be at least 1")),
+        ));
+    }
+
+    let (collections, total) = state
+        .listings_db
+        .list_collections(params.page, params.per_page)
+        .map_err(|e| {
+            error!("Failed to list collections: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("db_error", "Failed to list collections")),
+            )
+        })?;
+
+    Ok(Json(json!({
+        "success": true,
+        "collections": collections,
+        "total": total,
+        "page": params.page,
+        "per_page": params.per_page,
+        "has_next": page_start(params.page, params.per_page) + params.per_page < total,
+    })))
+}
+
+// ============================================================================
+// Listings Handlers
+// ============================================================================
+
+/// Calculate fees for a listing
+#[derive(Debug, Deserialize)]
+pub struct FeeCalcQuery {
+    pub amount: u64,
+    #[serde(default)]
+    pub tip_percent: f64,
+    /// Also estimate the miner fee the buyer pays on top
+    #[serde(default)]
+    pub include_network_fee: bool,
+    /// Also show amounts in this fiat currency (only `usd` is supported)
+    #[serde(default)]
+    pub currency: Option<String>,
+}
+
+pub async fn calculate_fees(
+    Query(params): Query<FeeCalcQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<FeeCalculationResponse>, (StatusCode, Json<ApiError>)> {
+    let tip_percent = ListingFees::validate_tip_percent(params.tip_percent, state.config.max_tip_percent)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ApiError::new("invalid_tip", e))))?;
+    let fees = ListingFees::calculate(params.amount, state.config.marketplace_fee_percent, tip_percent);
+    let network_fee = params.include_network_fee.then(|| {
+        let has_marketplace_output = fees.marketplace_fee + fees.tip_amount > 0;
+        tx_builder::estimate_purchase_network_fee(has_marketplace_output, state.config.fee_rate_sat_per_byte)
+    });
+
+    let usd = match params.currency.as_deref() {
+        None => None,
+        Some(currency) if currency.eq_ignore_ascii_case("usd") => {
+            // Display only, so a missing rate drops the USD fields instead of failing
+            cached_bsv_usd_rate(&state)
+                .await
+                .map(|rate| FeesUsd::convert(&fees, network_fee, rate))
+        }
+        Some(currency) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("unsupported_currency", format!("Unsupported currency '{}'", currency))),
+            ));
+        }
+    };
+
+    Ok(Json(FeeCalculationResponse { success: true, fees, network_fee, usd }))
+}
+
+/// BSV/USD rate from the cache, or the price oracle on a miss (`None` if the oracle fails)
+async fn cached_bsv_usd_rate(state: &AppState) -> Option<f64> {
+    if let Some(rate) = state.cache.get_bsv_usd_rate().await {
+        return Some(rate);
+    }
+
+    match state.price_oracle.bsv_usd_rate().await {
+        Ok(rate) => {
+            state.cache.set_bsv_usd_rate(rate).await;
+            Some(rate)
+        }
+        Err(e) => {
+            tracing::warn!("Price oracle lookup failed: {}", e);
+            None
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RecentSalesQuery {
+    #[serde(default = "default_recent_sales_limit")]
+    pub limit: usize,
+}
+
+fn default_recent_sales_limit() -> usize { 20 }
+
+/// Most sales the recent sales feed returns in one response
+const MAX_RECENT_SALES: usize = 100;
+
+/// Recently sold listings, newest first
+pub async fn get_recent_sales(
+    Query(params): Query<RecentSalesQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    let limit = params.limit.min(MAX_RECENT_SALES);
+
+    let listings = state.listings_db.get_recent_sales(limit).map_err(|e| {
+        error!("Failed to get recent sales: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("db_error", "Failed to fetch recent sales")),
+        )
+    })?;
+
+    let redact = state.config.redact_sale_buyers;
+    let sales: Vec<SaleRecord> = listings
+        .into_iter()
+        .filter_map(|l| {
+            Some(SaleRecord {
+                sold_at: l.sold_at?,
+                listing_id: l.id,
+                seq: l.seq,
+                origin: l.origin,
+                price_sats: l.fees.total_price,
+                seller_address: l.seller_address,
+                buyer_address: l.buyer_address.map(|a| if redact { redact_address(&a) } else { a }),
+                purchase_txid: l.purchase_txid,
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "success": true,
+        "count": sales.len(),
+        "sales": sales
+    })))
+}
+
+/// Keep only the first and last few characters of an address
+fn redact_address(address: &str) -> String {
+    if address.len() <= 10 {
+        return "…".to_string();
+    }
+    format!("{}…{}", &address[..4], &address[address.len() - 4..])
+}
+
+/// Get active listings (filter by MAP traits with `attr_<key>=<value>` params)
+pub async fn get_listings(
+    Query(params): Query<ListingsQuery>,
+    Query(raw_params): Query<HashMap<String, String>>,
+    State(state): State<AppState>,
+) -> Result<Json<ListingsResponse>, (StatusCode, Json<ApiError>)> {
+    info!("Get listings: page={}, per_page={}", params.page, params.per_page);
+
+    if params.page == 0 || params.per_page == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_pagination", "page and per_page must be at least 1")),
+        ));
+    }
+
+    let attr_filters: Vec<(String, String)> = raw_params
+        .into_iter()
+        .filter_map(|(k, v)| k.strip_prefix("attr_").map(|key| (key.to_string(), v)))
+        .collect();
+
+    let result = if !attr_filters.is_empty() {
+        state.listings_db.get_listings_by_attributes(&attr_filters, params.page, params.per_page)
+    } else if let Some(ref collection_id) = params.collection_id {
+        state.listings_db.get_listings_by_collection(collection_id, params.page, params.per_page)
+    } else if let Some(ref seller) = params.seller {
+        state.listings_db.get_listings_by_seller(seller).map(|listings| {
+            let total = listings.len();
+            let page = listings
+                .into_iter()
+                .skip(page_start(params.page, params.per_page))
+                .take(params.per_page)
+                .collect();
+            (page, total)
+        })
+    } else {
+        state.listings_db.get_active_listings(params.page, params.per_page)
+    };
+
+    match result {
+        Ok((listings, total)) => Ok(Json(ListingsResponse::paginated(
+            listings,
+            total,
+            params.page,
+            params.per_page,
+        ))),
+        Err(e) => {
+            error!("Failed to get listings: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("db_error", "Failed to fetch listings")),
+            ))
         }
     }
 }
@@ -303,13 +1230,152 @@ pub async fn get_listing(
     }
 }
 
+/// Get a listing by its sequence number
+pub async fn get_listing_by_seq(
+    Path(seq): Path<u64>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    match state.listings_db.get_listing_by_seq(seq) {
+        Ok(Some(listing)) => {
+            Ok(Json(json!({
+                "success": true,
+                "listing": listing
+            })))
+        }
+        Ok(None) => {
+            Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))))
+        }
+        Err(e) => {
+            error!("Failed to get listing by seq: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("db_error", "Failed to fetch listing")),
+            ))
+        }
+    }
+}
+
 /// Create a new listing
 pub async fn create_listing(
     State(state): State<AppState>,
-    Json(request): Json<CreateListingRequest>,
+    ApiJson(request): ApiJson<CreateListingRequest>,
 ) -> Result<Json<CreateListingResponse>, (StatusCode, Json<ApiError>)> {
     info!("Create listing request for origin: {}", request.origin);
 
+    let listing = create_one_listing(&state, request).await?;
+    Ok(Json(CreateListingResponse {
+        success: true,
+        listing,
+        message: "Listing created successfully".to_string(),
+    }))
+}
+
+/// POST /listings/batch
+/// Create several listings in one call. Each item is validated and created on its
+/// own, exactly as `POST /listings` would, so one bad item doesn't fail the rest.
+pub async fn create_listings_batch(
+    State(state): State<AppState>,
+    ApiJson(requests): ApiJson<Vec<CreateListingRequest>>,
+) -> Result<Json<BatchCreateListingsResponse>, (StatusCode, Json<ApiError>)> {
+    info!("Batch create request for {} listings", requests.len());
+
+    if requests.is_empty() || requests.len() > state.config.max_batch_listings {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "invalid_batch",
+                format!("A batch must contain between 1 and {} listings", state.config.max_batch_listings),
+            )),
+        ));
+    }
+
+    // Sequential, so a batch doesn't monopolize GorillaPool for ownership checks and
+    // a repeated origin is caught as already listed
+    let mut results = Vec::with_capacity(requests.len());
+    for (index, request) in requests.into_iter().enumerate() {
+        let origin = request.origin.clone();
+        let result = match create_one_listing(&state, request).await {
+            Ok(listing) => BatchListingResult {
+                index,
+                origin,
+                success: true,
+                listing: Some(listing),
+                error: None,
+            },
+            Err((_, Json(error))) => BatchListingResult {
+                index,
+                origin,
+                success: false,
+                listing: None,
+                error: Some(error),
+            },
+        };
+        results.push(result);
+    }
+
+    let created = results.iter().filter(|r| r.success).count();
+    info!("Batch created {} of {} listings", created, results.len());
+
+    Ok(Json(BatchCreateListingsResponse {
+        created,
+        failed: results.len() - created,
+        results,
+    }))
+}
+
+/// Validate a listing request and create it
+async fn create_one_listing(
+    state: &AppState,
+    mut request: CreateListingRequest,
+) -> Result<Listing, (StatusCode, Json<ApiError>)> {
+    check_address("seller_address", &request.seller_address, state.config.network)?;
+    check_address("seller_ord_address", &request.seller_ord_address, state.config.network)?;
+    check_signature(
+        &request.seller_ord_address,
+        &request.signing_message(),
+        &request.signature,
+        request.signed_at,
+        state.config.network,
+    )?;
+
+    // A retry of a create that already went through gets its listing back
+    if let Some(ref key) = request.idempotency_key {
+        if key.is_empty() || key.len() > MAX_IDEMPOTENCY_KEY_LEN {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new(
+                    "invalid_idempotency_key",
+                    format!("idempotency_key must be 1 to {} characters", MAX_IDEMPOTENCY_KEY_LEN),
+                )),
+            ));
+        }
+
+        let id = listing_id_for_key(&request.origin, &request.seller_ord_address, key);
+        match state.listings_db.get_listing(&id) {
+            Ok(Some(listing)) if listing.status == ListingStatus::Active => {
+                info!("Returning existing listing {} for idempotency key {}", id, key);
+                return Ok(listing);
+            }
+            Ok(Some(_)) => {
+                return Err((
+                    StatusCode::CONFLICT,
+                    Json(ApiError::new(
+                        "idempotency_key_used",
+                        "This idempotency key already created a listing that is no longer active; use a new key",
+                    )),
+                ));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                error!("Failed to check listing: {}", e);
+                return Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(ApiError::new("db_error", "Database error")),
+                ));
+            }
+        }
+    }
+
     match state.listings_db.is_origin_listed(&request.origin) {
         Ok(true) => {
             return Err((
@@ -327,21 +1393,124 @@ pub async fn create_listing(
         _ => {}
     }
 
-    if request.tip_percent != 0.0 && request.tip_percent != 2.5 && request.tip_percent != 5.0 {
-        return Err((
-            StatusCode::BAD_REQUEST,
-            Json(ApiError::new("invalid_tip", "Tip must be 0%, 2.5%, or 5%")),
-        ));
+    // The origin index alone doesn't stop someone relisting an ordinal they don't hold
+    require_upstream(state, "Verifying ordinal ownership")?;
+    match state.ordinal_service.verify_ownership(&request.origin, &request.seller_ord_address).await {
+        Ok(true) => {}
+        Ok(false) => {
+            return Err((
+                StatusCode::FORBIDDEN,
+                Json(ApiError::new("not_owner", "seller_ord_address does not currently hold this ordinal")),
+            ));
+        }
+        Err(e) => {
+            error!("Ownership check failed for {}: {}", request.origin, e);
+            return Err((
+                StatusCode::BAD_GATEWAY,
+                Json(ApiError::new("ownership_check_failed", "Could not verify ordinal ownership").with_details(e.to_string())),
+            ));
+        }
+    }
+
+    request.tip_percent = ListingFees::validate_tip_percent(request.tip_percent, state.config.max_tip_percent)
+        .map_err(|e| (StatusCode::BAD_REQUEST, Json(ApiError::new("invalid_tip", e))))?;
+
+    if let Some(days) = request.expires_in_days {
+        if !(1..=MAX_LISTING_EXPIRY_DAYS).contains(&days) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new(
+                    "invalid_expiry",
+                    format!("expires_in_days must be between 1 and {}", MAX_LISTING_EXPIRY_DAYS),
+                )),
+            ));
+        }
+        // An auction's end time and settlement window already bound its life
+        if request.auction_duration_secs.is_some() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_expiry", "Auctions can't also set expires_in_days")),
+            ));
+        }
+    }
+
+    if let Some(secs) = request.auction_duration_secs {
+        if !(MIN_AUCTION_SECS..=MAX_AUCTION_SECS).contains(&secs) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new(
+                    "invalid_auction",
+                    format!("auction_duration_secs must be between {} and {}", MIN_AUCTION_SECS, MAX_AUCTION_SECS),
+                )),
+            ));
+        }
+        // Bids are in satoshis; a fiat starting price would drift while the auction runs
+        if request.price_usd.is_some() {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_auction", "Auctions are priced in seller_wants_satoshis, not price_usd")),
+            ));
+        }
     }
 
-    match state.listings_db.create_listing(request) {
+    let fiat_price = match request.price_usd {
+        Some(_) if request.seller_wants_satoshis > 0 => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_price", "Give either seller_wants_satoshis or price_usd, not both")),
+            ));
+        }
+        Some(usd) if !usd.is_finite() || usd <= 0.0 => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_price", "price_usd must be a positive number")),
+            ));
+        }
+        Some(usd) => {
+            let rate = state.price_oracle.bsv_usd_rate().await.map_err(|e| {
+                error!("Price oracle lookup failed: {}", e);
+                (
+                    StatusCode::SERVICE_UNAVAILABLE,
+                    Json(ApiError::new("oracle_unavailable", "Exchange rate unavailable; try again or price in satoshis")
+                        .with_details(e.to_string())),
+                )
+            })?;
+            // Round up so the seller never receives less than the quoted fiat price
+            request.seller_wants_satoshis = (usd / rate * 100_000_000.0).ceil() as u64;
+            Some(FiatPrice {
+                currency: "USD".to_string(),
+                amount: usd,
+                rate,
+                quoted_at: chrono::Utc::now(),
+            })
+        }
+        None if request.seller_wants_satoshis == 0 => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_price", "seller_wants_satoshis or price_usd is required")),
+            ));
+        }
+        None => None,
+    };
+
+    // Index the ordinal's MAP traits and collection for filtering (best effort)
+    let (attributes, collection_id) = match state.ordinal_service.get_ordinal_details(&request.origin).await {
+        Ok(Some(details)) => (
+            details.metadata.as_ref().map(attributes_from_metadata).unwrap_or_default(),
+            details.collection_id,
+        ),
+        Ok(None) => (BTreeMap::new(), None),
+        Err(e) => {
+            tracing::warn!("Could not load metadata for {}: {}", request.origin, e);
+            (BTreeMap::new(), None)
+        }
+    };
+
+    match state.listings_db.create_listing(request, attributes, collection_id, fiat_price) {
         Ok(listing) => {
             info!("Created listing {}", listing.id);
-            Ok(Json(CreateListingResponse {
-                success: true,
-                listing,
-                message: "Listing created successfully".to_string(),
-            }))
+            state.cache.invalidate_wallet(&listing.seller_ord_address).await;
+            Ok(listing)
         }
         Err(e) => {
             error!("Failed to create listing: {}", e);
@@ -357,7 +1526,7 @@ pub async fn create_listing(
 pub async fn cancel_listing(
     Path(id): Path<String>,
     State(state): State<AppState>,
-    Json(request): Json<CancelListingRequest>,
+    ApiJson(request): ApiJson<CancelListingRequest>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
     info!("Cancel listing request: {}", id);
 
@@ -368,8 +1537,19 @@ pub async fn cancel_listing(
         ));
     }
 
+    // cancel_listing still compares seller_ord_address; the signature proves the
+    // caller controls that address
+    check_signature(
+        &request.seller_ord_address,
+        &request.signing_message(),
+        &request.signature,
+        request.signed_at,
+        state.config.network,
+    )?;
+
     match state.listings_db.cancel_listing(&id, &request.seller_ord_address) {
         Ok(Some(listing)) => {
+            state.cache.invalidate_wallet(&listing.seller_ord_address).await;
             Ok(Json(json!({
                 "success": true,
                 "listing": listing,
@@ -379,6 +1559,10 @@ pub async fn cancel_listing(
         Ok(None) => {
             Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))))
         }
+        Err(e) if is_revision_conflict(&e) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiError::new("conflict", e.to_string())),
+        )),
         Err(e) => {
             error!("Failed to cancel listing: {}", e);
             Err((
@@ -389,116 +1573,1126 @@ pub async fn cancel_listing(
     }
 }
 
-/// Prepare unsigned transaction for Yours Wallet purchase
-pub async fn prepare_purchase(
-    Path(listing_id): Path<String>,
+/// POST /listings/:id/reprice
+/// Change an active listing's price in place, signed by the seller like a cancellation
+pub async fn reprice_listing(
+    Path(id): Path<String>,
     State(state): State<AppState>,
-    Json(payload): Json<PreparePurchaseRequest>,
-) -> Result<Json<PreparePurchaseResponse>, (StatusCode, String)> {
-    info!("Prepare purchase request for listing: {}", listing_id);
+    ApiJson(mut request): ApiJson<RepriceListingRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    info!("Reprice listing request: {}", id);
 
-    let listing = state
-        .listings_db
+    check_signature(
+        &request.seller_ord_address,
+        &request.signing_message(&id),
+        &request.signature,
+        request.signed_at,
+        state.config.network,
+    )?;
+
+    if request.seller_wants_satoshis == 0 {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_price", "seller_wants_satoshis must be greater than zero")),
+        ));
+    }
+
+    if let Some(tip) = request.tip_percent {
+        request.tip_percent = Some(
+            ListingFees::validate_tip_percent(tip, state.config.max_tip_percent)
+                .map_err(|e| (StatusCode::BAD_REQUEST, Json(ApiError::new("invalid_tip", e))))?,
+        );
+    }
+
+    match state.listings_db.reprice_listing(
+        &id,
+        &request.seller_ord_address,
+        request.seller_wants_satoshis,
+        request.tip_percent,
+    ) {
+        Ok(Some(listing)) => Ok(Json(json!({
+            "success": true,
+            "listing": listing,
+            "message": "Listing repriced successfully"
+        }))),
+        Ok(None) => {
+            Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))))
+        }
+        Err(e) if is_revision_conflict(&e) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiError::new("conflict", e.to_string())),
+        )),
+        Err(e) => {
+            error!("Failed to reprice listing: {}", e);
+            Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("reprice_error", e.to_string())),
+            ))
+        }
+    }
+}
+
+/// Longest expiry a seller may set on a listing
+const MAX_LISTING_EXPIRY_DAYS: u32 = 365;
+
+/// Shortest and longest auction a listing may run
+const MIN_AUCTION_SECS: u64 = 60;
+const MAX_AUCTION_SECS: u64 = 30 * 24 * 60 * 60;
+
+/// How long an offer lasts when the buyer doesn't say
+const DEFAULT_OFFER_TTL_SECS: u64 = 24 * 60 * 60;
+
+/// Longest an offer (and the reservation once accepted) may last
+const MAX_OFFER_TTL_SECS: u64 = 7 * 24 * 60 * 60;
+
+/// POST /listings/:id/offers
+/// Offer less than the asking price, signed by the buyer's ordinal address
+pub async fn create_offer(
+    Path(listing_id): Path<String>,
+    State(state): State<AppState>,
+    ApiJson(request): ApiJson<CreateOfferRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    info!("Offer request for listing: {}", listing_id);
+
+    check_address("buyer_ord_address", &request.buyer_ord_address, state.config.network)?;
+    check_signature(
+        &request.buyer_ord_address,
+        &request.signing_message(&listing_id),
+        &request.signature,
+        request.signed_at,
+        state.config.network,
+    )?;
+
+    let ttl = request.expires_in_secs.unwrap_or(DEFAULT_OFFER_TTL_SECS);
+    if ttl == 0 || ttl > MAX_OFFER_TTL_SECS {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "invalid_expiry",
+                format!("expires_in_secs must be between 1 and {}", MAX_OFFER_TTL_SECS),
+            )),
+        ));
+    }
+
+    let listing = state
+        .listings_db
         .get_listing(&listing_id)
-        .map_err(|_| (StatusCode::NOT_FOUND, "Listing not found".to_string()))?
-        .ok_or((StatusCode::NOT_FOUND, "Listing not found".to_string()))?;
+        .map_err(|_| (StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))))?
+        .ok_or((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))))?;
 
     if listing.status != ListingStatus::Active {
-        return Err((StatusCode::BAD_REQUEST, "Listing is no longer active".to_string()));
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("listing_inactive", "Listing is no longer active")),
+        ));
     }
 
-    let total_price = listing.fees.total_price;
-    let miner_fee_buffer = 1000u64;
-    let required_sats = total_price + miner_fee_buffer;
+    if listing.auction.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("auction_listing", "Auction listings take bids, not offers")),
+        ));
+    }
 
-    let gorillapool_utxos = state
-        .ordinal_service
-        .gorillapool()
-        .get_address_utxos(&payload.buyer_payment_address)
-        .await
+    // Compare against the real ask, not a reserved offer price
+    let ask_sats = listing
+        .accepted_offer
+        .as_ref()
+        .map_or(listing.fees.seller_receives, |a| a.ask_fees.seller_receives);
+    if request.amount_sats == 0 || request.amount_sats >= ask_sats {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_offer", "An offer must be above zero and below the asking price")
+                .with_details(format!("Asking {} sats", ask_sats))),
+        ));
+    }
+
+    let expires_at = chrono::Utc::now() + chrono::Duration::seconds(ttl as i64);
+    let offer = state
+        .listings_db
+        .create_offer(&listing, &request.buyer_ord_address, request.amount_sats, expires_at)
         .map_err(|e| {
-            tracing::error!("GorillaPool UTXO fetch failed: {}", e);
+            error!("Failed to create offer: {}", e);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                "Failed to fetch buyer UTXOs".to_string(),
+                Json(ApiError::new("db_error", "Failed to create offer")),
             )
         })?;
 
-    let mut selected_utxos: Vec<BuyerUtxo> = Vec::new();
-    let mut collected_sats: u64 = 0;
-
-    for utxo in gorillapool_utxos {
-        if utxo.satoshis >= 546 {
-            selected_utxos.push(BuyerUtxo {
-                txid: utxo.txid,
-                vout: utxo.vout,
-                satoshis: utxo.satoshis,
-                script_hex: utxo.lock.clone(),
-            });
-            collected_sats += utxo.satoshis;
-
-            if collected_sats >= required_sats {
-                break;
+    Ok(Json(json!({
+        "success": true,
+        "offer": offer
+    })))
+}
+
+/// GET /listings/:id/offers
+/// Offers on a listing, newest first, with expired pending offers shown as expired
+pub async fn get_listing_offers(
+    Path(listing_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    let db_error = |e: anyhow::Error| {
+        error!("Failed to load offers: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("db_error", "Failed to fetch offers")),
+        )
+    };
+
+    if state.listings_db.get_listing(&listing_id).map_err(db_error)?.is_none() {
+        return Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))));
+    }
+
+    let now = chrono::Utc::now();
+    let mut offers = state.listings_db.get_offers_for_listing(&listing_id).map_err(db_error)?;
+    for offer in &mut offers {
+        offer.status = offer.status_at(now);
+    }
+
+    Ok(Json(json!({
+        "success": true,
+        "count": offers.len(),
+        "data": offers
+    })))
+}
+
+/// POST /offers/:id/accept
+/// Seller accepts an offer: the listing is repriced to it and reserved for the
+/// offer's buyer until the offer expires, then bought through the usual purchase flow
+pub async fn accept_offer(
+    Path(offer_id): Path<String>,
+    State(state): State<AppState>,
+    ApiJson(request): ApiJson<RespondToOfferRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    info!("Accept offer request: {}", offer_id);
+
+    check_signature(
+        &request.seller_ord_address,
+        &request.signing_message("Accept", &offer_id),
+        &request.signature,
+        request.signed_at,
+        state.config.network,
+    )?;
+
+    match state.listings_db.accept_offer(&offer_id, &request.seller_ord_address) {
+        Ok(Some((offer, listing))) => Ok(Json(json!({
+            "success": true,
+            "offer": offer,
+            "listing": listing,
+            "message": "Offer accepted; the listing is reserved for the buyer"
+        }))),
+        Ok(None) => Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Offer not found")))),
+        Err(e) => Err(offer_write_error(e)),
+    }
+}
+
+/// POST /offers/:id/reject
+pub async fn reject_offer(
+    Path(offer_id): Path<String>,
+    State(state): State<AppState>,
+    ApiJson(request): ApiJson<RespondToOfferRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    info!("Reject offer request: {}", offer_id);
+
+    check_signature(
+        &request.seller_ord_address,
+        &request.signing_message("Reject", &offer_id),
+        &request.signature,
+        request.signed_at,
+        state.config.network,
+    )?;
+
+    match state.listings_db.reject_offer(&offer_id, &request.seller_ord_address) {
+        Ok(Some(offer)) => Ok(Json(json!({
+            "success": true,
+            "offer": offer
+        }))),
+        Ok(None) => Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Offer not found")))),
+        Err(e) => Err(offer_write_error(e)),
+    }
+}
+
+/// Map a failed offer response: a concurrent listing write is a 409, anything
+/// else (wrong seller, offer no longer pending, listing closed) a 400
+fn offer_write_error(e: anyhow::Error) -> (StatusCode, Json<ApiError>) {
+    if is_revision_conflict(&e) {
+        return (StatusCode::CONFLICT, Json(ApiError::new("conflict", e.to_string())));
+    }
+    error!("Failed to respond to offer: {}", e);
+    (StatusCode::BAD_REQUEST, Json(ApiError::new("offer_error", e.to_string())))
+}
+
+/// Prepare unsigned transaction for Yours Wallet purchase
+pub async fn prepare_purchase(
+    Path(listing_id): Path<String>,
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<PreparePurchaseRequest>,
+) -> Result<Json<PreparePurchaseResponse>, (StatusCode, Json<ApiError>)> {
+    info!("Prepare purchase request for listing: {}", listing_id);
+
+    let (listing, selected_utxos) = purchase_inputs(&state, &listing_id, &payload).await?;
+    let collected_sats: u64 = selected_utxos.iter().map(|u| u.satoshis).sum();
+
+    info!(
+        "Prepared purchase for {}: using {} UTXOs totaling {} sats",
+        listing_id, selected_utxos.len(), collected_sats
+    );
+
+    let tx_result = tx_builder::build_purchase_tx(
+        &listing,
+        &payload.buyer_ord_address,
+        &payload.buyer_payment_address,
+        selected_utxos,
+        state.config.fee_address_at(chrono::Utc::now()),
+        tx_builder::TxParams::from_config(&state.config).omit_op_return(payload.omit_op_return),
+        payload.split_change,
+    )
+    .map_err(|e| tx_build_error_response(e, "Failed to construct purchase transaction"))?;
+
+    Ok(Json(tx_result))
+}
+
+/// POST /listings/:id/estimate
+/// Dry run of prepare-purchase: same coin selection and transaction, but only its
+/// size, miner fee, input count and change are returned
+pub async fn estimate_purchase(
+    Path(listing_id): Path<String>,
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<PreparePurchaseRequest>,
+) -> Result<Json<PurchaseEstimate>, (StatusCode, Json<ApiError>)> {
+    let (listing, selected_utxos) = purchase_inputs(&state, &listing_id, &payload).await?;
+
+    let estimate = tx_builder::estimate_purchase_tx(
+        &listing,
+        &payload.buyer_ord_address,
+        &payload.buyer_payment_address,
+        selected_utxos,
+        state.config.fee_address_at(chrono::Utc::now()),
+        tx_builder::TxParams::from_config(&state.config).omit_op_return(payload.omit_op_return),
+        payload.split_change,
+    )
+    .map_err(|e| tx_build_error_response(e, "Failed to construct purchase transaction"))?;
+
+    Ok(Json(estimate))
+}
+
+/// Apply a listing's reservations to a purchase delivering the ordinal to
+/// `buyer_ord_address`. A lapsed accepted offer is released first, restoring the
+/// asking price; a live one only admits the offer's buyer. An expired listing
+/// can't be bought, nor an auction while bidding is open, and once closed only by
+/// its winner.
+fn check_reservation(
+    state: &AppState,
+    listing: &mut Listing,
+    buyer_ord_address: Option<&str>,
+) -> Result<(), (StatusCode, Json<ApiError>)> {
+    state.listings_db.release_lapsed_offer(listing).map_err(|e| {
+        if is_revision_conflict(&e) {
+            (StatusCode::CONFLICT, Json(ApiError::new("conflict", "Listing changed; try again")))
+        } else {
+            error!("Failed to release lapsed offer on listing {}: {}", listing.id, e);
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiError::new("db_error", "Failed to update listing")))
+        }
+    })?;
+
+    if let Some(ref offer) = listing.accepted_offer {
+        if buyer_ord_address != Some(offer.buyer_ord_address.as_str()) {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(ApiError::new("reserved", "Listing is reserved for the buyer of an accepted offer")
+                    .with_details(format!("Reserved until {}", offer.expires_at.to_rfc3339()))),
+            ));
+        }
+    }
+
+    if let Some(ref hold) = listing.cart_hold {
+        if hold.expires_at > chrono::Utc::now() && buyer_ord_address != Some(hold.buyer_ord_address.as_str()) {
+            return Err((
+                StatusCode::CONFLICT,
+                Json(ApiError::new("reserved", "Listing is held for another buyer's cart purchase")
+                    .with_details(format!("Held until {}", hold.expires_at.to_rfc3339()))),
+            ));
+        }
+    }
+
+    // The sweeper may not have got to it yet
+    if let Some(reason) = state.listings_db.expiry_reason(listing) {
+        if let Err(e) = state.listings_db.mark_expired(&listing.id, reason) {
+            error!("Failed to expire listing {}: {}", listing.id, e);
+        }
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("listing_inactive", "Listing has expired").with_details(reason)),
+        ));
+    }
+
+    let Some(ref auction) = listing.auction else {
+        return Ok(());
+    };
+    match (&auction.closed_at, &auction.high_bid) {
+        (None, _) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiError::new("auction_open", "Auction listings can't be bought until bidding closes")
+                .with_details(format!("Bidding ends at {}", auction.ends_at.to_rfc3339()))),
+        )),
+        (Some(_), Some(winner)) if buyer_ord_address == Some(winner.bidder_ord_address.as_str()) => Ok(()),
+        (Some(_), _) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiError::new("reserved", "Listing is reserved for the auction winner")),
+        )),
+    }
+}
+
+/// POST /listings/:id/bids
+/// Bid on an auction listing, signed by the bidder's ordinal address. The winner
+/// buys at their bid through the usual purchase flow once the auction closes.
+pub async fn place_bid(
+    Path(listing_id): Path<String>,
+    State(state): State<AppState>,
+    ApiJson(request): ApiJson<PlaceBidRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    info!("Bid request for listing: {}", listing_id);
+
+    check_address("bidder_ord_address", &request.bidder_ord_address, state.config.network)?;
+    check_signature(
+        &request.bidder_ord_address,
+        &request.signing_message(&listing_id),
+        &request.signature,
+        request.signed_at,
+        state.config.network,
+    )?;
+
+    match state.listings_db.place_bid(&listing_id, &request.bidder_ord_address, request.amount_sats) {
+        Ok(Some(listing)) => Ok(Json(json!({
+            "success": true,
+            "listing": listing
+        }))),
+        Ok(None) => Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found")))),
+        // Another bid landed first; the client should re-read the high bid
+        Err(e) if is_revision_conflict(&e) => Err((
+            StatusCode::CONFLICT,
+            Json(ApiError::new("conflict", e.to_string())),
+        )),
+        Err(e) => Err((StatusCode::BAD_REQUEST, Json(ApiError::new("bid_error", e.to_string())))),
+    }
+}
+
+/// GET /listings/:id/bids
+/// Bids on an auction listing, latest (highest) first
+pub async fn get_listing_bids(
+    Path(listing_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    let db_error = |e: anyhow::Error| {
+        error!("Failed to load bids: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("db_error", "Failed to fetch bids")),
+        )
+    };
+
+    match state.listings_db.get_listing(&listing_id).map_err(db_error)? {
+        Some(listing) if listing.auction.is_some() => {}
+        Some(_) => {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("not_auction", "Listing is not an auction")),
+            ));
+        }
+        None => return Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found")))),
+    }
+
+    let bids = state.listings_db.get_bids(&listing_id).map_err(db_error)?;
+    Ok(Json(json!({
+        "success": true,
+        "count": bids.len(),
+        "data": bids
+    })))
+}
+
+/// Validate a single-listing purchase request and select the buyer's UTXOs for it
+async fn purchase_inputs(
+    state: &AppState,
+    listing_id: &str,
+    payload: &PreparePurchaseRequest,
+) -> Result<(Listing, Vec<BuyerUtxo>), (StatusCode, Json<ApiError>)> {
+    check_address("buyer_ord_address", &payload.buyer_ord_address, state.config.network)?;
+    check_address("buyer_payment_address", &payload.buyer_payment_address, state.config.network)?;
+
+    if payload.additional_payment_addresses.len() > MAX_ADDITIONAL_PAYMENT_ADDRESSES {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "too_many_addresses",
+                format!("At most {} additional payment addresses are allowed", MAX_ADDITIONAL_PAYMENT_ADDRESSES),
+            )),
+        ));
+    }
+    let mut payment_addresses = vec![payload.buyer_payment_address.as_str()];
+    for address in &payload.additional_payment_addresses {
+        check_address("additional_payment_addresses", address, state.config.network)?;
+        if !payment_addresses.contains(&address.as_str()) {
+            payment_addresses.push(address);
+        }
+    }
+
+    let mut listing = state
+        .listings_db
+        .get_listing(listing_id)
+        .map_err(|_| (StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))))?
+        .ok_or((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))))?;
+
+    if listing.status != ListingStatus::Active {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("listing_inactive", "Listing is no longer active")),
+        ));
+    }
+
+    check_reservation(state, &mut listing, Some(&payload.buyer_ord_address))?;
+
+    let params = tx_builder::TxParams::from_config(&state.config).omit_op_return(payload.omit_op_return);
+    let selected_utxos =
+        select_buyer_utxos(state, &payment_addresses, std::slice::from_ref(&listing), params).await?;
+
+    Ok((listing, selected_utxos))
+}
+
+/// Map a transaction builder failure to a response. Problems with what the buyer sent
+/// (unfunded inputs, unkeepable change, a bad address or UTXO) are 400s; broken listing or config data is a 500.
+fn tx_build_error_response(e: tx_builder::TxBuildError, message: &str) -> (StatusCode, Json<ApiError>) {
+    use tx_builder::TxBuildError;
+
+    match e {
+        TxBuildError::InsufficientFunds { .. } => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("insufficient_funds", e.to_string())),
+        ),
+        TxBuildError::DustChange { .. } => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("dust_change", e.to_string())),
+        ),
+        TxBuildError::InvalidAddress { role, .. } if role.is_buyer() => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_address", e.to_string())),
+        ),
+        TxBuildError::InvalidUtxoTxid(_) => (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("invalid_utxo", e.to_string())),
+        ),
+        _ => {
+            error!("{}: {}", message, e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("tx_build_error", message).with_details(e.to_string())),
+            )
+        }
+    }
+}
+
+/// Refuse an operation that needs GorillaPool while the upstream probe says it's
+/// down, rather than failing partway through it
+fn require_upstream(state: &AppState, operation: &str) -> Result<(), (StatusCode, Json<ApiError>)> {
+    if state.upstream_health.is_degraded() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiError::new(
+                "upstream_unavailable",
+                format!("{} needs GorillaPool, which is currently unreachable; try again shortly", operation),
+            )),
+        ));
+    }
+    Ok(())
+}
+
+/// Fetch the buyer's spendable UTXOs and select enough of them to pay for `listings`
+/// plus the miner fee at the configured rate
+async fn select_buyer_utxos(
+    state: &AppState,
+    payment_addresses: &[&str],
+    listings: &[Listing],
+    params: tx_builder::TxParams<'_>,
+) -> Result<Vec<BuyerUtxo>, (StatusCode, Json<ApiError>)> {
+    require_upstream(state, "Fetching wallet UTXOs")?;
+
+    // Fetched concurrently; GorillaPool's own semaphore bounds how many run at once
+    let gorillapool = state.ordinal_service.gorillapool();
+    let fetches = payment_addresses.iter().map(|address| async move {
+        gorillapool
+            .get_address_utxos(address)
+            .await
+            .map(|utxos| (*address, utxos))
+    });
+    let per_address = futures_util::future::try_join_all(fetches).await.map_err(|e| {
+        tracing::error!("GorillaPool UTXO fetch failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(ApiError::new("fetch_error", "Failed to fetch buyer UTXOs").with_details(e.to_string())),
+        )
+    })?;
+
+    let mut candidates: Vec<BuyerUtxo> = Vec::new();
+    let mut dust_utxos: usize = 0;
+
+    for (address, gorillapool_utxos) in per_address {
+        for utxo in gorillapool_utxos {
+            if utxo.satoshis >= state.config.dust_threshold_sats {
+                candidates.push(BuyerUtxo {
+                    txid: utxo.txid,
+                    vout: utxo.vout,
+                    satoshis: utxo.satoshis,
+                    script_hex: utxo.lock.clone(),
+                    address: Some(address.to_string()),
+                });
+            } else {
+                dust_utxos += 1;
             }
         }
     }
 
-    if collected_sats < required_sats {
+    // Selection sorts candidates itself, so the result doesn't depend on
+    // GorillaPool's response order and the same wallet state always produces
+    // the same unsigned transaction
+    let total_price: u64 = listings.iter().map(|l| l.fees.total_price).sum();
+    let base_fee = tx_builder::purchase_base_fee(listings, params);
+    tx_builder::select_funding_utxos(&candidates, total_price, base_fee, params).map_err(|required_sats| {
+        let available_sats: u64 = candidates.iter().map(|u| u.satoshis).sum();
+        let details = insufficient_funds_details(
+            available_sats,
+            candidates.len(),
+            dust_utxos,
+            state.config.consolidation_hint_min_utxos,
+        );
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "insufficient_funds",
+                format!(
+                    "Insufficient funds: need {} sats (incl. miner fee), only have {}",
+                    required_sats, available_sats
+                ),
+            ).with_details(details)),
+        )
+    })
+}
+
+/// Most listings one cart purchase may combine
+const MAX_CART_LISTINGS: usize = 20;
+
+/// How long a prepared cart purchase holds its listings for the buyer
+const CART_HOLD: chrono::Duration = chrono::Duration::minutes(10);
+
+/// POST /purchases/prepare-batch
+/// Prepare one unsigned transaction buying every listing in a cart. Either every
+/// listing is active, included and held for the buyer, or the request fails
+/// without building or holding anything.
+pub async fn prepare_batch_purchase(
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<BatchPreparePurchaseRequest>,
+) -> Result<Json<PreparePurchaseResponse>, (StatusCode, Json<ApiError>)> {
+    info!("Prepare batch purchase request for {} listings", payload.listing_ids.len());
+
+    check_address("buyer_ord_address", &payload.buyer_ord_address, state.config.network)?;
+    check_address("buyer_payment_address", &payload.buyer_payment_address, state.config.network)?;
+
+    if payload.listing_ids.is_empty() || payload.listing_ids.len() > MAX_CART_LISTINGS {
         return Err((
             StatusCode::BAD_REQUEST,
-            format!(
-                "Insufficient funds: need {} sats (incl. fee buffer), only have {}",
-                required_sats, collected_sats
-            ),
+            Json(ApiError::new(
+                "invalid_cart",
+                format!("A cart must contain between 1 and {} listings", MAX_CART_LISTINGS),
+            )),
         ));
     }
 
+    let mut seen = std::collections::HashSet::new();
+    let mut listings = Vec::with_capacity(payload.listing_ids.len());
+    for id in &payload.listing_ids {
+        if !seen.insert(id.as_str()) {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_cart", "Cart contains a listing more than once").with_details(id.clone())),
+            ));
+        }
+
+        let mut listing = state
+            .listings_db
+            .get_listing(id)
+            .ok()
+            .flatten()
+            .ok_or_else(|| {
+                (
+                    StatusCode::NOT_FOUND,
+                    Json(ApiError::new("not_found", "Listing not found").with_details(id.clone())),
+                )
+            })?;
+
+        if listing.status != ListingStatus::Active {
+            return Err((
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("listing_inactive", "Listing is no longer active").with_details(id.clone())),
+            ));
+        }
+
+        check_reservation(&state, &mut listing, Some(&payload.buyer_ord_address))?;
+
+        listings.push(listing);
+    }
+
+    let params = tx_builder::TxParams::from_config(&state.config).omit_op_return(payload.omit_op_return);
+    let selected_utxos =
+        select_buyer_utxos(&state, &[&payload.buyer_payment_address], &listings, params).await?;
+
     info!(
-        "Prepared purchase for {}: using {} UTXOs totaling {} sats",
-        listing_id, selected_utxos.len(), collected_sats
+        "Prepared cart purchase of {} listings: using {} UTXOs",
+        listings.len(), selected_utxos.len()
     );
 
-    let tx_result = tx_builder::build_purchase_tx(
-        &listing,
+    let tx_result = tx_builder::build_cart_purchase_tx(
+        &listings,
         &payload.buyer_ord_address,
         &payload.buyer_payment_address,
         selected_utxos,
-        &state.config.marketplace_fee_address,
+        state.config.fee_address_at(chrono::Utc::now()),
+        params,
+        payload.split_change,
+    )
+    .map_err(|e| tx_build_error_response(e, "Failed to construct purchase transaction"))?;
+
+    let hold_until = chrono::Utc::now() + CART_HOLD;
+    state
+        .listings_db
+        .reserve_for_cart(&mut listings, &payload.buyer_ord_address, hold_until)
+        .map_err(|e| match e.downcast_ref::<ListingWriteError>() {
+            Some(ListingWriteError::NotActive) => (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("listing_inactive", "A listing in the cart is no longer active")),
+            ),
+            Some(ListingWriteError::RevisionConflict) => (
+                StatusCode::CONFLICT,
+                Json(ApiError::new("conflict", "A listing in the cart changed; try again")),
+            ),
+            None => {
+                error!("Failed to hold cart listings: {}", e);
+                (StatusCode::INTERNAL_SERVER_ERROR, Json(ApiError::new("db_error", "Failed to hold cart listings")))
+            }
+        })?;
+
+    Ok(Json(tx_result))
+}
+
+/// POST /listings/:id/prepare-lock-tx
+/// Build the unsigned transaction that moves the seller's ordinal into an
+/// ordinal-lock output, funded from the seller's payment address
+pub async fn prepare_lock_tx(
+    Path(listing_id): Path<String>,
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<PrepareLockRequest>,
+) -> Result<Json<PreparePurchaseResponse>, (StatusCode, Json<ApiError>)> {
+    info!("Prepare lock TX request for listing: {}", listing_id);
+
+    let Some((prefix, suffix)) = state.config.ordlock_template() else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            Json(ApiError::new("ordlock_unconfigured", "On-chain listing locks are not configured on this server")),
+        ));
+    };
+
+    let listing = state
+        .listings_db
+        .get_listing(&listing_id)
+        .map_err(|_| (StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))))?
+        .ok_or((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))))?;
+
+    if listing.seller_ord_address != payload.seller_ord_address {
+        return Err((
+            StatusCode::FORBIDDEN,
+            Json(ApiError::new("forbidden", "Not authorized to lock this listing")),
+        ));
+    }
+
+    if listing.status != ListingStatus::Active {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("listing_inactive", "Listing is no longer active")),
+        ));
+    }
+
+    if listing.listing_utxo.is_some() {
+        return Err((
+            StatusCode::CONFLICT,
+            Json(ApiError::new("already_locked", "Listing is already locked on-chain")),
+        ));
+    }
+
+    // The lock script would pay out the starting price, not the winning bid
+    if listing.auction.is_some() {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("auction_listing", "Auction listings can't be locked on-chain")),
+        ));
+    }
+
+    require_upstream(&state, "Fetching seller UTXOs")?;
+
+    let params = tx_builder::TxParams::from_config(&state.config);
+    let base_fee = tx_builder::lock_base_fee(&listing, &prefix, &suffix, params)
+        .map_err(|e| tx_build_error_response(e, "Failed to construct lock transaction"))?;
+
+    let gorillapool_utxos = state
+        .ordinal_service
+        .gorillapool()
+        .get_address_utxos(&listing.seller_address)
+        .await
+        .map_err(|e| {
+            tracing::error!("GorillaPool UTXO fetch failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("fetch_error", "Failed to fetch seller UTXOs").with_details(e.to_string())),
+            )
+        })?;
+
+    let candidates: Vec<BuyerUtxo> = gorillapool_utxos
+        .into_iter()
+        .filter(|utxo| utxo.satoshis >= state.config.dust_threshold_sats)
+        .map(|utxo| BuyerUtxo {
+            txid: utxo.txid,
+            vout: utxo.vout,
+            satoshis: utxo.satoshis,
+            script_hex: utxo.lock,
+            address: Some(listing.seller_address.clone()),
+        })
+        .collect();
+
+    let selected_utxos = tx_builder::select_funding_utxos(&candidates, 0, base_fee, params).map_err(|required_sats| {
+        let available_sats: u64 = candidates.iter().map(|u| u.satoshis).sum();
+        (
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "insufficient_funds",
+                format!(
+                    "Insufficient funds: need {} sats for the lock miner fee, only have {}",
+                    required_sats, available_sats
+                ),
+            )),
+        )
+    })?;
+
+    let tx_result = tx_builder::build_lock_tx(&listing, selected_utxos, &prefix, &suffix, params)
+    .map_err(|e| tx_build_error_response(e, "Failed to construct lock transaction"))?;
+
+    Ok(Json(tx_result))
+}
+
+/// POST /listings/:id/broadcast-lock
+/// Broadcast the seller-signed lock transaction and record the lock output on the listing
+pub async fn broadcast_lock(
+    Path(listing_id): Path<String>,
+    State(state): State<AppState>,
+    ApiJson(payload): ApiJson<BroadcastLockRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
+    info!("Broadcast lock request for listing: {}", listing_id);
+
+    let Some((prefix, suffix)) = state.config.ordlock_template() else {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "On-chain listing locks are not configured on this server".to_string(),
+        ));
+    };
+
+    let mut listing = state
+        .listings_db
+        .get_listing(&listing_id)
+        .map_err(|_| (StatusCode::NOT_FOUND, "Listing not found".to_string()))?
+        .ok_or((StatusCode::NOT_FOUND, "Listing not found".to_string()))?;
+
+    if listing.seller_ord_address != payload.seller_ord_address {
+        return Err((StatusCode::FORBIDDEN, "Not authorized to lock this listing".to_string()));
+    }
+
+    if listing.status != ListingStatus::Active {
+        return Err((StatusCode::BAD_REQUEST, "Listing is no longer active".to_string()));
+    }
+
+    let raw_bytes = hex::decode(&payload.raw_tx_hex)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid hex encoding".to_string()))?;
+
+    let signed_tx: Transaction = deserialize(&raw_bytes)
+        .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid transaction format".to_string()))?;
+
+    // The transaction must spend the listed ordinal into the expected lock output
+    let spends_ordinal = signed_tx.input.first().is_some_and(|input| {
+        input.previous_output.txid.to_string() == listing.ordinal_utxo.txid
+            && input.previous_output.vout == listing.ordinal_utxo.vout
+    });
+    let expected_lock = tx_builder::ordlock_script(&listing, &prefix, &suffix, state.config.network)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to build lock script: {}", e)))?;
+    let locks_ordinal = signed_tx
+        .output
+        .first()
+        .is_some_and(|out| out.script_pubkey == expected_lock);
+
+    if !spends_ordinal || !locks_ordinal {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            "Transaction does not lock the listed ordinal".to_string(),
+        ));
+    }
+
+    broadcast_raw_tx(&state.config.mapi_url, &payload.raw_tx_hex).await?;
+
+    let txid = signed_tx.txid().to_string();
+    listing.listing_utxo = Some(format!("{}:0", txid));
+    listing.psbt_hex = Some(payload.raw_tx_hex);
+    listing.updated_at = chrono::Utc::now();
+    state.listings_db.update_listing(&mut listing).map_err(|e| {
+        if is_revision_conflict(&e) {
+            (StatusCode::CONFLICT, "Listing changed while it was being locked; check its status".to_string())
+        } else {
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update listing".to_string())
+        }
+    })?;
+
+    info!("Listing {} locked on-chain in {}", listing_id, txid);
+
+    Ok(Json(json!({
+        "success": true,
+        "txid": txid,
+        "listing_utxo": listing.listing_utxo
+    })))
+}
+
+/// Build the `details` text for an insufficient-funds purchase error, suggesting
+/// consolidation when the buyer's balance is spread over many (or dust) UTXOs
+fn insufficient_funds_details(
+    available_sats: u64,
+    spendable_utxos: usize,
+    dust_utxos: usize,
+    consolidation_hint_min_utxos: usize,
+) -> String {
+    let mut details = format!(
+        "Available: {} sats across {} spendable UTXOs ({} below dust skipped).",
+        available_sats, spendable_utxos, dust_utxos
+    );
+
+    if spendable_utxos + dust_utxos >= consolidation_hint_min_utxos {
+        details.push_str(
+            " Your balance is fragmented - consolidate your UTXOs into fewer, larger outputs and try again.",
+        );
+    } else {
+        details.push_str(" Add funds to your payment address and try again.");
+    }
+
+    details
+}
+
+/// Query parameters for the purchase transaction template
+#[derive(Debug, Deserialize)]
+pub struct TxTemplateQuery {
+    /// Optional buyer ordinal address to fill in the ordinal output
+    pub buyer_ord_address: Option<String>,
+}
+
+/// Get the fixed inputs/outputs of a purchase without selecting buyer funds
+pub async fn get_purchase_tx_template(
+    Path(listing_id): Path<String>,
+    Query(params): Query<TxTemplateQuery>,
+    State(state): State<AppState>,
+) -> Result<Json<PurchaseTxTemplate>, (StatusCode, Json<ApiError>)> {
+    info!("Purchase TX template request for listing: {}", listing_id);
+
+    if let Some(ref address) = params.buyer_ord_address {
+        check_address("buyer_ord_address", address, state.config.network)?;
+    }
+
+    let listing = match state.listings_db.get_listing(&listing_id) {
+        Ok(Some(l)) => l,
+        Ok(None) => {
+            return Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))));
+        }
+        Err(e) => {
+            error!("Failed to get listing: {}", e);
+            return Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiError::new("db_error", "Failed to fetch listing")),
+            ));
+        }
+    };
+
+    if listing.status != ListingStatus::Active {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("listing_inactive", "Listing is no longer active")),
+        ));
+    }
+
+    let template = tx_builder::build_purchase_template(
+        &listing,
+        params.buyer_ord_address.as_deref(),
+        state.config.fee_address_at(chrono::Utc::now()),
+        state.config.network,
     )
     .map_err(|e| {
-        tracing::error!("Transaction build failed: {}", e);
         (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Failed to construct purchase transaction".to_string(),
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("tx_template_error", "Failed to build purchase template").with_details(e.to_string())),
         )
     })?;
 
-    Ok(Json(tx_result))
-}
+    Ok(Json(template))
+}
+
+/// Broadcast signed purchase transaction (Yours Wallet flow)
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BroadcastPurchaseRequest {
+    pub raw_tx_hex: String,
+    /// Optional http(s) URL for hosted checkout pages to redirect to after purchase
+    #[serde(default)]
+    pub return_url: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BroadcastPurchaseResponse {
+    pub success: bool,
+    pub txid: String,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_url: Option<String>,
+    /// Full mAPI response from the broadcaster
+    pub mapi: serde_json::Value,
+}
+
+/// Validate a client-supplied return URL, accepting only absolute http(s) URLs
+fn validate_return_url(url: &str) -> Result<reqwest::Url, String> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid return_url: {}", e))?;
+
+    match parsed.scheme() {
+        "http" | "https" if parsed.host_str().is_some() => Ok(parsed),
+        "http" | "https" => Err("Invalid return_url: missing host".to_string()),
+        scheme => Err(format!("Invalid return_url: scheme must be http or https, got {}", scheme)),
+    }
+}
+
+/// Submit a signed transaction to the mAPI at `mapi_url`, returning its full response
+async fn broadcast_raw_tx(mapi_url: &str, raw_tx_hex: &str) -> Result<serde_json::Value, (StatusCode, String)> {
+    let client = reqwest::Client::new();
+    let resp: serde_json::Value = client
+        .post(mapi_url)
+        .json(&json!({ "rawtx": raw_tx_hex }))
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::error!("Broadcast failed: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to send transaction".to_string())
+        })?
+        .json()
+        .await
+        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Invalid response from broadcaster".to_string()))?;
+
+    if resp["returnResult"].as_str() != Some("success") {
+        let msg = resp["resultDescription"].as_str().unwrap_or("Unknown error");
+        tracing::warn!("Broadcast rejected: {}", resp);
+        return Err((StatusCode::BAD_REQUEST, format!("Broadcast rejected: {} (mAPI response: {})", msg, resp)));
+    }
 
-/// Broadcast signed purchase transaction (Yours Wallet flow)
-#[derive(Debug, Deserialize)]
-pub struct BroadcastPurchaseRequest {
-    pub raw_tx_hex: String,
+    Ok(resp)
 }
 
-#[derive(Debug, Serialize)]
-pub struct BroadcastPurchaseResponse {
-    pub success: bool,
-    pub txid: String,
-    pub message: String,
+/// How far back a fee address rotation may have happened and still be honored
+/// when verifying a purchase transaction
+const FEE_ADDRESS_ROTATION_GRACE: chrono::Duration = chrono::Duration::hours(1);
+
+/// Map a failure to record a sale: losing to a concurrent sale or cancellation
+/// is a 409, anything else a 500
+fn sale_write_error(e: anyhow::Error) -> (StatusCode, String) {
+    match e.downcast_ref::<ListingWriteError>() {
+        Some(ListingWriteError::NotActive) => {
+            error!("Listing was already sold or cancelled when recording sale");
+            (StatusCode::CONFLICT, "Listing was already sold or cancelled".to_string())
+        }
+        Some(ListingWriteError::RevisionConflict) => {
+            (StatusCode::CONFLICT, "Listing is being modified concurrently, try again".to_string())
+        }
+        None => {
+            error!("Failed to mark listing sold: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update listing".to_string())
+        }
+    }
 }
 
+/// Longest `Idempotency-Key` accepted
+const MAX_IDEMPOTENCY_KEY_LEN: usize = 255;
+
 pub async fn broadcast_purchase(
     Path(listing_id): Path<String>,
     State(state): State<AppState>,
-    Json(payload): Json<BroadcastPurchaseRequest>,
-) -> Result<Json<BroadcastPurchaseResponse>, (StatusCode, String)> {
+    headers: HeaderMap,
+    ApiJson(payload): ApiJson<BroadcastPurchaseRequest>,
+) -> Result<Json<serde_json::Value>, (StatusCode, String)> {
     info!("Broadcast purchase request for listing: {}", listing_id);
 
+    // A retried submission with the same key gets the original response instead
+    // of a second broadcast
+    let idempotency_key = match headers.get("idempotency-key") {
+        Some(value) => {
+            let key = value
+                .to_str()
+                .ok()
+                .filter(|k| !k.is_empty() && k.len() <= MAX_IDEMPOTENCY_KEY_LEN)
+                .ok_or((StatusCode::BAD_REQUEST, "Invalid Idempotency-Key header".to_string()))?;
+            Some(key.to_string())
+        }
+        None => None,
+    };
+
+    // Claimed before anything is checked or broadcast, so concurrent duplicates
+    // can't both get through
+    if let Some(ref key) = idempotency_key {
+        let claim = state.listings_db.claim_idempotency_key(&listing_id, key).map_err(|e| {
+            error!("Failed to claim idempotency key: {}", e);
+            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to record idempotency key".to_string())
+        })?;
+        match claim {
+            IdempotencyClaim::Claimed => {}
+            IdempotencyClaim::InFlight => {
+                return Err((
+                    StatusCode::CONFLICT,
+                    "A purchase with this Idempotency-Key is already in progress".to_string(),
+                ));
+            }
+            IdempotencyClaim::Completed(response) => {
+                info!("Replaying broadcast response for listing {} (idempotency key {})", listing_id, key);
+                return Ok(Json(response));
+            }
+        }
+    }
+
+    let result = complete_purchase(&state, &listing_id, payload, idempotency_key.as_deref()).await;
+
+    // Nothing was broadcast, so the key is free for a corrected retry
+    if let (Err(_), Some(key)) = (&result, &idempotency_key) {
+        if let Err(e) = state.listings_db.release_idempotency_key(&listing_id, key) {
+            error!("Failed to release idempotency key: {}", e);
+        }
+    }
+
+    result.map(Json)
+}
+
+/// Verify and broadcast a signed purchase of `listing_id`, then record the sale.
+/// Fails only if nothing was broadcast: once mAPI accepts the transaction its
+/// response is stored under `idempotency_key` and returned, even if recording
+/// the sale afterwards fails.
+async fn complete_purchase(
+    state: &AppState,
+    listing_id: &str,
+    payload: BroadcastPurchaseRequest,
+    idempotency_key: Option<&str>,
+) -> Result<serde_json::Value, (StatusCode, String)> {
     let mut listing = state
         .listings_db
-        .get_listing(&listing_id)
+        .get_listing(listing_id)
         .map_err(|_| (StatusCode::NOT_FOUND, "Listing not found".to_string()))?
         .ok_or((StatusCode::NOT_FOUND, "Listing not found".to_string()))?;
 
@@ -506,56 +2700,115 @@ pub async fn broadcast_purchase(
         return Err((StatusCode::BAD_REQUEST, "Listing is no longer active".to_string()));
     }
 
+    let return_url = payload
+        .return_url
+        .as_deref()
+        .map(validate_return_url)
+        .transpose()
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    // The server itself will POST to this URL, so it must not reach internal hosts
+    if let (Some(url), true) = (&return_url, state.config.purchase_callbacks_enabled) {
+        state.purchase_callbacks.check_url(url)
+            .await
+            .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+    }
+
     let raw_bytes = hex::decode(&payload.raw_tx_hex)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid hex encoding".to_string()))?;
 
     let signed_tx: Transaction = deserialize(&raw_bytes)
         .map_err(|_| (StatusCode::BAD_REQUEST, "Invalid transaction format".to_string()))?;
 
+    // Output 0 carries the ordinal to the buyer
+    let buyer_address = signed_tx
+        .output
+        .first()
+        .and_then(|out| Address::from_script(&out.script_pubkey, state.config.network).ok())
+        .map(|addr| addr.to_string());
+
+    // Checked before verification so a lapsed offer price is never accepted
+    check_reservation(state, &mut listing, buyer_address.as_deref())
+        .map_err(|(status, Json(e))| (status, e.message))?;
+
+    // Accept the fee address in effect when the purchase was likely prepared, in
+    // case a scheduled rotation happened in between
+    let now = chrono::Utc::now();
+    let mut fee_addresses = vec![state.config.fee_address_at(now)];
+    let earlier = state.config.fee_address_at(now - FEE_ADDRESS_ROTATION_GRACE);
+    if !fee_addresses.contains(&earlier) {
+        fee_addresses.push(earlier);
+    }
+
+    if let Err(problems) = tx_builder::verify_purchase_tx(&signed_tx, &listing, &fee_addresses, state.config.network) {
+        tracing::warn!("Rejected purchase tx for listing {}: {}", listing_id, problems.join("; "));
+        return Err((
+            StatusCode::BAD_REQUEST,
+            format!("Transaction does not match the listing: {}", problems.join("; ")),
+        ));
+    }
+
     let txid = signed_tx.txid().to_string();
 
-    let client = reqwest::Client::new();
-    let resp: serde_json::Value = client
-        .post("https://mapi.gorillapool.io/mapi/tx")
-        .json(&json!({ "rawtx": payload.raw_tx_hex }))
-        .send()
-        .await
-        .map_err(|e| {
-            tracing::error!("Broadcast failed: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Failed to send transaction".to_string())
-        })?
-        .json()
-        .await
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Invalid response from broadcaster".to_string()))?;
+    let mapi = broadcast_raw_tx(&state.config.mapi_url, &payload.raw_tx_hex).await?;
 
-    if resp["returnResult"].as_str() != Some("success") {
-        let msg = resp["resultDescription"].as_str().unwrap_or("Unknown error");
-        return Err((StatusCode::BAD_REQUEST, format!("Broadcast rejected: {}", msg)));
+    let response = serde_json::to_value(BroadcastPurchaseResponse {
+        success: true,
+        txid: txid.clone(),
+        message: "Purchase successful and broadcasted".to_string(),
+        return_url: return_url.as_ref().map(|u| u.to_string()),
+        mapi,
+    })
+    .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to encode response".to_string()))?;
+
+    // The transaction is on the network now; a retry must see that, whatever
+    // happens below
+    if let Some(key) = idempotency_key {
+        if let Err(e) = state.listings_db.store_idempotent_response(listing_id, key, &response) {
+            error!("Failed to store idempotency record: {}", e);
+        }
+    }
+
+    if let Err(e) = state.listings_db.mark_listing_sold(
+        &listing.id,
+        buyer_address.as_deref(),
+        &txid,
+        Some(&response["mapi"]),
+    ) {
+        let (_, message) = sale_write_error(e);
+        error!("Purchase tx {} for listing {} was broadcast but not recorded: {}", txid, listing_id, message);
     }
 
-    listing.status = ListingStatus::Sold;
-    listing.purchase_txid = Some(txid.clone());
-    listing.sold_at = Some(Utc::now());
-    state.listings_db.update_listing(&listing)
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update listing".to_string()))?;
+    // The ordinal now belongs to the buyer; don't keep serving the seller as owner
+    state.cache.invalidate_ordinal(&listing.origin).await;
+    state.cache.invalidate_wallet(&listing.seller_ord_address).await;
+    if let Some(ref buyer) = buyer_address {
+        state.cache.invalidate_wallet(buyer).await;
+    }
 
     info!("Purchase completed! TXID: {}", txid);
 
-    Ok(Json(BroadcastPurchaseResponse {
-        success: true,
-        txid,
-        message: "Purchase successful and broadcasted".to_string(),
-    }))
+    if let Some(url) = return_url {
+        if state.config.purchase_callbacks_enabled {
+            state.purchase_callbacks.notify(url, listing_id.to_string(), txid);
+        }
+    }
+
+    Ok(response)
 }
 
-/// Purchase a listing (placeholder for now - actual implementation needs PSBT handling)
+/// Purchase a listing in one step: build the unsigned purchase TX from the
+/// buyer-supplied payment UTXOs and return it with sig requests
 pub async fn purchase_listing(
     Path(id): Path<String>,
     State(state): State<AppState>,
-    Json(request): Json<PurchaseListingRequest>,
-) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
+    ApiJson(request): ApiJson<PurchaseListingRequest>,
+) -> Result<Json<PreparePurchaseResponse>, (StatusCode, Json<ApiError>)> {
     info!("Purchase listing request: {}", id);
 
+    check_address("buyer_address", &request.buyer_address, state.config.network)?;
+    check_address("buyer_ord_address", &request.buyer_ord_address, state.config.network)?;
+
     if id != request.listing_id {
         return Err((
             StatusCode::BAD_REQUEST,
@@ -563,7 +2816,7 @@ pub async fn purchase_listing(
         ));
     }
 
-    let listing = match state.listings_db.get_listing(&id) {
+    let mut listing = match state.listings_db.get_listing(&id) {
         Ok(Some(l)) => l,
         Ok(None) => {
             return Err((StatusCode::NOT_FOUND, Json(ApiError::new("not_found", "Listing not found"))));
@@ -576,26 +2829,96 @@ pub async fn purchase_listing(
         }
     };
 
-    Ok(Json(json!({
-        "success": true,
-        "listing": listing,
-        "message": "Purchase ready - complete transaction client-side",
-        "purchase_info": {
-            "total_satoshis": listing.fees.total_price,
-            "seller_receives": listing.fees.seller_receives,
-            "marketplace_fee": listing.fees.marketplace_fee,
-            "tip_amount": listing.fees.tip_amount,
-            "seller_address": listing.seller_address,
-            "ordinal_utxo": listing.ordinal_utxo,
-        }
-    })))
+    if listing.status != ListingStatus::Active {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new("listing_inactive", "Listing is no longer active")),
+        ));
+    }
+
+    check_reservation(&state, &mut listing, Some(&request.buyer_ord_address))?;
+
+    let mut buyer_utxos = Vec::with_capacity(request.payment_utxos.len());
+    for utxo in &request.payment_utxos {
+        let buyer_utxo = utxo.to_buyer_utxo().map_err(|e| {
+            (
+                StatusCode::BAD_REQUEST,
+                Json(ApiError::new("invalid_utxo", format!("Invalid script for payment UTXO {}:{}", utxo.txid, utxo.vout))
+                    .with_details(e.to_string())),
+            )
+        })?;
+        buyer_utxos.push(buyer_utxo);
+    }
+
+    let params = tx_builder::TxParams::from_config(&state.config).omit_op_return(request.omit_op_return);
+    let available_sats: u64 = buyer_utxos.iter().map(|u| u.satoshis).sum();
+    let required_sats = tx_builder::funding_target(
+        listing.fees.total_price,
+        tx_builder::purchase_base_fee(std::slice::from_ref(&listing), params),
+        buyer_utxos.len(),
+        params.fee_rate_sat_per_byte,
+    );
+    if available_sats < required_sats {
+        return Err((
+            StatusCode::BAD_REQUEST,
+            Json(ApiError::new(
+                "insufficient_funds",
+                format!(
+                    "Insufficient funds: need {} sats (incl. miner fee), only have {}",
+                    required_sats, available_sats
+                ),
+            ).with_details(insufficient_funds_details(
+                available_sats,
+                buyer_utxos.len(),
+                0,
+                state.config.consolidation_hint_min_utxos,
+            ))),
+        ));
+    }
+
+    let tx_result = tx_builder::build_purchase_tx(
+        &listing,
+        &request.buyer_ord_address,
+        &request.buyer_address,
+        buyer_utxos,
+        state.config.fee_address_at(chrono::Utc::now()),
+        params,
+        None,
+    )
+    .map_err(|e| tx_build_error_response(e, "Failed to construct purchase transaction"))?;
+
+    info!("Built purchase TX for listing {} from {} payment UTXOs", id, request.payment_utxos.len());
+
+    Ok(Json(tx_result))
 }
 
 /// POST /listings/:id/purchase-handcash
 /// HandCash server-side purchase (trusted flow)
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct HandCashPurchaseRequest {
     pub auth_token: String,
+    /// Currency the buyer is charged in (BSV if omitted)
+    #[serde(default)]
+    pub currency: HandCashCurrency,
+}
+
+/// Currencies a HandCash purchase can be denominated in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum HandCashCurrency {
+    #[default]
+    Bsv,
+    Usd,
+}
+
+impl HandCashCurrency {
+    fn code(self) -> &'static str {
+        match self {
+            HandCashCurrency::Bsv => "BSV",
+            HandCashCurrency::Usd => "USD",
+        }
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -603,15 +2926,170 @@ pub struct HandCashPurchaseResponse {
     pub success: bool,
     pub txid: String,
     pub message: String,
+    /// Currency the buyer was charged in
+    pub currency: HandCashCurrency,
+    /// Amount charged, in `currency`
+    pub amount: f64,
+    /// BSV equivalent of the charged amount
+    pub amount_bsv: f64,
+}
+
+/// HandCash `(destination, sats)` payouts for buying `listing` at `at`: the seller's
+/// proceeds, then the marketplace fee and tip to the fee address in effect at `at`
+fn handcash_payouts(
+    config: &crate::config::Config,
+    listing: &Listing,
+    at: chrono::DateTime<chrono::Utc>,
+) -> Result<Vec<(String, u64)>, (StatusCode, String)> {
+    let seller_destination = handcash_destination(&listing.seller_address).map_err(|e| {
+        error!("Cannot pay seller of listing {} via HandCash: {}", listing.id, e);
+        (StatusCode::UNPROCESSABLE_ENTITY, format!("Seller cannot be paid via HandCash: {}", e))
+    })?;
+    let marketplace_destination = handcash_destination(config.fee_address_at(at))
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, format!("Invalid marketplace fee address: {}", e)))?;
+
+    let mut payouts = vec![(seller_destination, listing.fees.seller_receives)];
+    let marketplace_sats = listing.fees.marketplace_fee + listing.fees.tip_amount;
+    if marketplace_sats > 0 {
+        payouts.push((marketplace_destination, marketplace_sats));
+    }
+    Ok(payouts)
+}
+
+/// Convert `(destination, sats)` payouts to HandCash amounts: BSV when `rate` is
+/// `None`, otherwise fiat at `rate` units per BSV rounded up to the cent
+fn handcash_payments(payouts: Vec<(String, u64)>, rate: Option<f64>) -> Vec<(String, f64)> {
+    payouts
+        .into_iter()
+        .map(|(destination, sats)| {
+            let bsv = sats as f64 / 100_000_000.0;
+            let amount = match rate {
+                Some(rate) => (bsv * rate * 100.0).ceil() / 100.0,
+                None => bsv,
+            };
+            (destination, amount)
+        })
+        .collect()
+}
+
+/// Body for a HandCash Pay request paying each `(destination, amount)` in `currency`
+fn handcash_payment_body(
+    description: &str,
+    payments: &[(String, f64)],
+    currency: HandCashCurrency,
+) -> serde_json::Value {
+    let payments: Vec<serde_json::Value> = payments
+        .iter()
+        .map(|(destination, amount)| json!({
+            "destination": destination,
+            "amount": amount,
+            "currency": currency.code()
+        }))
+        .collect();
+
+    json!({
+        "description": description,
+        "payments": payments
+    })
+}
+
+/// Relative difference allowed between a fiat-denominated HandCash charge and the
+/// listing price, covering per-destination cent rounding and rate movement
+const HANDCASH_FIAT_AMOUNT_TOLERANCE: f64 = 0.02;
+
+/// Check a HandCash payment receipt carries a transaction ID and charged
+/// `expected_sats`, returning the transaction ID
+fn verify_handcash_receipt(
+    receipt: &serde_json::Value,
+    expected_sats: u64,
+    currency: HandCashCurrency,
+) -> Result<String, String> {
+    let transaction_id = receipt["transactionId"]
+        .as_str()
+        .filter(|id| !id.is_empty())
+        .ok_or("receipt has no transactionId")?;
+
+    let paid_sats = receipt["satoshiAmount"]
+        .as_u64()
+        .ok_or("receipt has no satoshiAmount")?;
+
+    // BSV amounts are exact up to float rounding of each destination
+    let tolerance = match currency {
+        HandCashCurrency::Bsv => 2,
+        _ => (expected_sats as f64 * HANDCASH_FIAT_AMOUNT_TOLERANCE).ceil() as u64,
+    };
+    if paid_sats.abs_diff(expected_sats) > tolerance {
+        return Err(format!(
+            "receipt shows {} sats paid but the listing costs {} sats",
+            paid_sats, expected_sats
+        ));
+    }
+
+    Ok(transaction_id.to_string())
+}
+
+/// Turn a payout address into a HandCash Pay destination. Handles (`$name`) and
+/// paymails pass through; anything else must be a valid mainnet P2PKH address,
+/// which HandCash pays directly.
+fn handcash_destination(address: &str) -> Result<String, String> {
+    let address = address.trim();
+    if address.starts_with('$') || address.contains('@') {
+        return Ok(address.to_string());
+    }
+    // HandCash only pays on mainnet, whatever network the marketplace runs on
+    validate_bsv_address(address, Network::Bitcoin)
+        .map(|_| address.to_string())
+        .map_err(|e| format!("`{}` is not a HandCash handle, paymail or BSV address: {}", address, e))
+}
+
+/// Fetch HandCash's current rate for `currency` (fiat units per BSV)
+async fn handcash_exchange_rate(
+    client: &reqwest::Client,
+    config: &crate::config::Config,
+    auth_token: &str,
+    currency: HandCashCurrency,
+) -> Result<f64, (StatusCode, String)> {
+    let url = format!("https://api.handcash.io/v3/wallet/exchangeRate/{}", currency.code());
+    let resp = client
+        .get(&url)
+        .header("app-id", &config.handcash_app_id)
+        .header("app-secret", &config.handcash_app_secret)
+        .header("auth-token", auth_token)
+        .send()
+        .await
+        .map_err(|e| {
+            tracing::error!("HandCash exchange rate request failed: {}", e);
+            (StatusCode::BAD_GATEWAY, "Failed to fetch HandCash exchange rate".to_string())
+        })?;
+
+    if !resp.status().is_success() {
+        return Err((StatusCode::BAD_GATEWAY, "HandCash exchange rate unavailable".to_string()));
+    }
+
+    let body: serde_json::Value = resp.json().await
+        .map_err(|_| (StatusCode::BAD_GATEWAY, "Failed to parse HandCash exchange rate".to_string()))?;
+
+    body["rate"]
+        .as_f64()
+        .filter(|rate| *rate > 0.0)
+        .ok_or((StatusCode::BAD_GATEWAY, "Invalid HandCash exchange rate".to_string()))
 }
 
 pub async fn purchase_handcash(
     Path(listing_id): Path<String>,
     State(state): State<AppState>,
-    Json(payload): Json<HandCashPurchaseRequest>,
+    ApiJson(payload): ApiJson<HandCashPurchaseRequest>,
 ) -> Result<Json<HandCashPurchaseResponse>, (StatusCode, String)> {
     info!("HandCash purchase request for listing: {}", listing_id);
 
+    if !state.config.handcash_configured() {
+        error!("HandCash purchase attempted without HANDCASH_APP_SECRET configured");
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "HandCash purchases are not configured on this server".to_string(),
+        ));
+    }
+
     // 1. Load and validate listing
     let mut listing = state
         .listings_db
@@ -623,6 +3101,9 @@ pub async fn purchase_handcash(
         return Err((StatusCode::BAD_REQUEST, "Listing is no longer active".to_string()));
     }
 
+    // HandCash delivers to the buyer's paymail, so it can't satisfy an offer reservation
+    check_reservation(&state, &mut listing, None).map_err(|(status, Json(e))| (status, e.message))?;
+
     // 2. Validate HandCash auth token and get buyer profile
     let client = reqwest::Client::new();
     let profile_resp = client
@@ -649,22 +3130,30 @@ pub async fn purchase_handcash(
         .ok_or((StatusCode::BAD_REQUEST, "No paymail in HandCash profile".to_string()))?
         .to_string();
 
-    // 3. Charge buyer via HandCash Pay API
+    // 3. Charge buyer via HandCash Pay API, paying the seller and the marketplace
+    // (fee + tip) as separate destinations. Fiat amounts are rounded up to the
+    // cent per destination and HandCash converts them to BSV at payment time.
+    let payouts = handcash_payouts(&state.config, &listing, chrono::Utc::now())?;
+
+    let rate = match payload.currency {
+        HandCashCurrency::Bsv => None,
+        currency => Some(handcash_exchange_rate(&client, &state.config, &payload.auth_token, currency).await?),
+    };
+    let payments = handcash_payments(payouts, rate);
+
     let amount_bsv = listing.fees.total_price as f64 / 100_000_000.0;
+    let amount: f64 = payments.iter().map(|(_, a)| a).sum();
 
     let payment_resp = client
         .post("https://api.handcash.io/v3/payments")
         .header("app-id", &state.config.handcash_app_id)
         .header("app-secret", &state.config.handcash_app_secret)
         .header("auth-token", &payload.auth_token)
-        .json(&json!({
-            "description": format!("Purchase ordinal {}", listing.origin),
-            "payments": [{
-                "destination": state.config.marketplace_fee_address, // You can split to seller + fee if desired
-                "amount": amount_bsv,
-                "currency": "BSV"
-            }]
-        }))
+        .json(&handcash_payment_body(
+            &format!("Purchase ordinal {}", listing.origin),
+            &payments,
+            payload.currency,
+        ))
         .send()
         .await
         .map_err(|e| {
@@ -677,39 +3166,61 @@ pub async fn purchase_handcash(
         return Err((StatusCode::PAYMENT_REQUIRED, format!("HandCash rejected payment: {}", error_text)));
     }
 
-    // 4. Payment succeeded — mark listing as sold
-    // Note: Ordinal transfer is handled off-chain via HandCash payment trust model
-    // For full on-chain transfer, your developer can later add a hot wallet to build/broadcast TX
-    listing.status = ListingStatus::Sold;
-    listing.purchase_txid = Some("handcash_payment".to_string());
-    listing.sold_at = Some(Utc::now());
-   listing.buyer_address = Some(buyer_paymail.clone());
+    // 4. Check the receipt really is for this purchase before treating it as paid
+    let receipt: serde_json::Value = payment_resp.json().await.map_err(|e| {
+        error!("Unparseable HandCash payment receipt for listing {}: {}", listing_id, e);
+        (StatusCode::BAD_GATEWAY, "Invalid HandCash payment receipt".to_string())
+    })?;
 
-info!("HandCash purchase completed for listing {} by {}", listing_id, buyer_paymail);
+    let transaction_id = verify_handcash_receipt(&receipt, listing.fees.total_price, payload.currency)
+        .map_err(|e| {
+            error!("HandCash receipt for listing {} failed verification: {} ({})", listing_id, e, receipt);
+            (StatusCode::BAD_GATEWAY, format!("HandCash payment could not be verified: {}", e))
+        })?;
 
-    state.listings_db.update_listing(&listing)
-        .map_err(|_| (StatusCode::INTERNAL_SERVER_ERROR, "Failed to update listing".to_string()))?;
+    // 5. Payment verified — mark listing as sold
+    // Note: Ordinal transfer is handled off-chain via HandCash payment trust model
+    // For full on-chain transfer, your developer can later add a hot wallet to build/broadcast TX
+    state.listings_db
+        .mark_listing_sold(&listing.id, Some(&buyer_paymail), &transaction_id, Some(&receipt))
+        .map_err(sale_write_error)?;
+    // The buyer is a paymail here, not a wallet address, so only the seller's view changes
+    state.cache.invalidate_wallet(&listing.seller_ord_address).await;
 
-    info!("HandCash purchase completed for listing {} by {}", listing_id, buyer_paymail);
+    info!(
+        "HandCash purchase completed for listing {} by {}: {} {} ({} BSV) in {}",
+        listing_id, buyer_paymail, amount, payload.currency.code(), amount_bsv, transaction_id
+    );
 
     Ok(Json(HandCashPurchaseResponse {
         success: true,
-        txid: "handcash_payment_confirmed".to_string(),
+        txid: transaction_id,
         message: "Payment successful via HandCash — ordinal purchased".to_string(),
+        currency: payload.currency,
+        amount,
+        amount_bsv,
     }))
 }
 
 
+#[derive(Debug, Deserialize)]
+pub struct OriginListingQuery {
+    /// Also return the ordinal's most recent closed (sold, cancelled or expired) listing
+    #[serde(default)]
+    pub include_closed: bool,
+}
+
 /// Get listing by origin
 pub async fn get_listing_by_origin(
     Path(origin): Path<String>,
+    Query(params): Query<OriginListingQuery>,
     State(state): State<AppState>,
 ) -> Result<Json<serde_json::Value>, (StatusCode, Json<ApiError>)> {
-    match state.listings_db.get_listing_by_origin(&origin) {
+    match state.listings_db.get_listing_by_origin(&origin, params.include_closed) {
         Ok(Some(listing)) => {
             Ok(Json(json!({
                 "success": true,
-                "listed": true,
+                "listed": listing.status == ListingStatus::Active,
                 "listing": listing
             })))
         }
@@ -735,6 +3246,7 @@ pub async fn get_listing_by_origin(
 // ============================================================================
 
 #[derive(Debug, Deserialize)]
+#[allow(dead_code)]
 pub struct SearchParams {
     pub content_type: Option<String>,
     pub collection_id: Option<String>,
@@ -755,4 +3267,576 @@ pub async fn search_ordinals(
         "error": "not_implemented",
         "message": "Search functionality coming soon"
     }))
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{address, app_state, create_listing, listing_request, listings_db, serve, txid};
+    use axum::routing::{get, post};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Config pointing GorillaPool at a mock answering every UTXO query with one
+    /// UTXO per entry of `utxo_sats`, the first in a tx of all `0xee` bytes
+    async fn config_with_wallet(utxo_sats: &[u64]) -> crate::config::Config {
+        let utxos: Vec<serde_json::Value> = utxo_sats
+            .iter()
+            .enumerate()
+            .map(|(i, sats)| json!({
+                "txid": txid(0xee - i as u8),
+                "vout": 0,
+                "satoshis": sats,
+                "lock": "",
+                "origin": "",
+            }))
+            .collect();
+        let gorillapool = axum::Router::new().route(
+            "/txos/address/:address/unspent",
+            get(move || async move { Json(utxos) }),
+        );
+        crate::config::Config {
+            gorillapool_base_url: serve(gorillapool).await,
+            gorillapool_retry_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    /// Mock mAPI accepting every transaction after `delay`, returning its URL and
+    /// the counter of broadcasts it received
+    async fn mapi(delay: std::time::Duration) -> (String, Arc<AtomicUsize>) {
+        let broadcasts = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&broadcasts);
+        let app = axum::Router::new().route(
+            "/mapi/tx",
+            post(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(delay).await;
+                Json(json!({ "returnResult": "success", "resultDescription": "" }))
+            }),
+        );
+        (format!("{}/mapi/tx", serve(app).await), broadcasts)
+    }
+
+    /// State with a funded wallet and broadcasts going to `mapi_url`
+    async fn broadcast_state(mapi_url: String) -> AppState {
+        let config = crate::config::Config {
+            mapi_url,
+            ..config_with_wallet(&[100_000]).await
+        };
+        app_state(config, listings_db())
+    }
+
+    /// Purchase tx for `listing` as built by prepare-purchase; signatures aren't
+    /// checked before broadcast, so it can be submitted as is
+    async fn prepared_purchase_hex(state: &AppState, listing: &Listing) -> String {
+        let Json(prepared) = prepare_purchase(Path(listing.id.clone()), State(state.clone()), ApiJson(purchase_request()))
+            .await
+            .unwrap();
+        prepared.raw_tx_hex
+    }
+
+    fn idempotency_key(key: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert("idempotency-key", HeaderValue::from_str(key).unwrap());
+        headers
+    }
+
+    async fn broadcast(
+        state: &AppState,
+        listing: &Listing,
+        headers: HeaderMap,
+        raw_tx_hex: &str,
+        return_url: Option<String>,
+    ) -> Result<serde_json::Value, (StatusCode, String)> {
+        let request = BroadcastPurchaseRequest { raw_tx_hex: raw_tx_hex.to_string(), return_url };
+        broadcast_purchase(Path(listing.id.clone()), State(state.clone()), headers, ApiJson(request))
+            .await
+            .map(|Json(body)| body)
+    }
+
+    fn purchase_request() -> PreparePurchaseRequest {
+        PreparePurchaseRequest {
+            buyer_ord_address: address(8),
+            buyer_payment_address: address(9),
+            additional_payment_addresses: Vec::new(),
+            split_change: None,
+            omit_op_return: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn insufficient_funds_details_report_the_wallet() {
+        let db = listings_db();
+        let listing = create_listing(&db, 1, 50_000);
+        let state = app_state(config_with_wallet(&[3_000, 2_000, 100]).await, db);
+
+        let (status, Json(error)) = prepare_purchase(Path(listing.id), State(state), ApiJson(purchase_request()))
+            .await
+            .unwrap_err();
+
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.error, "insufficient_funds");
+        let details = error.details.unwrap();
+        assert!(details.contains("Available: 5000 sats across 2 spendable UTXOs (1 below dust skipped)"), "{}", details);
+    }
+
+    #[tokio::test]
+    async fn utxos_below_the_dust_threshold_are_never_selected() {
+        let wallet = [1_999, 1_999, 1_999, 3_000];
+
+        // With the default threshold the whole wallet can fund the purchase
+        let db = listings_db();
+        let listing = create_listing(&db, 1, 5_000);
+        let state = app_state(config_with_wallet(&wallet).await, db);
+        let params = tx_builder::TxParams::from_config(&state.config);
+        let selected = select_buyer_utxos(&state, &[&address(2)], &[listing], params).await.unwrap();
+        assert!(selected.iter().any(|u| u.satoshis == 1_999));
+
+        // Raised above the small UTXOs, only the 3000 sat one may be spent
+        let db = listings_db();
+        let listing = create_listing(&db, 1, 5_000);
+        let config = crate::config::Config {
+            dust_threshold_sats: 2_000,
+            ..config_with_wallet(&wallet).await
+        };
+        let state = app_state(config, db);
+        let params = tx_builder::TxParams::from_config(&state.config);
+        let (status, Json(error)) = select_buyer_utxos(&state, &[&address(2)], &[listing], params)
+            .await
+            .unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let details = error.details.unwrap();
+        assert!(details.contains("Available: 3000 sats across 1 spendable UTXOs (3 below dust skipped)"), "{}", details);
+    }
+
+    #[test]
+    fn insufficient_funds_details_suggest_consolidating_fragmented_balances() {
+        let details = insufficient_funds_details(12_000, 30, 5, 20);
+        assert!(details.contains("12000 sats across 30 spendable UTXOs"));
+        assert!(details.contains("consolidate"));
+
+        let details = insufficient_funds_details(12_000, 2, 0, 20);
+        assert!(details.contains("Add funds"));
+    }
+
+    #[tokio::test]
+    async fn fee_address_attestation_verifies_against_the_configured_key() {
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let key = bitcoin::PrivateKey::new(
+            bitcoin::secp256k1::SecretKey::from_slice(&[7; 32]).unwrap(),
+            Network::Bitcoin,
+        );
+        let signer = Address::p2pkh(&key.public_key(&secp), Network::Bitcoin).to_string();
+        let config = crate::config::Config {
+            attestation_wif: Some(key.to_wif()),
+            ..Default::default()
+        };
+        let state = app_state(config, listings_db());
+
+        let Json(body) = get_fee_address(State(state.clone())).await.unwrap();
+
+        let fee_address = body["address"].as_str().unwrap();
+        let signature = body["signature"].as_str().unwrap();
+        assert_eq!(fee_address, state.config.fee_address_at(chrono::Utc::now()));
+        assert_eq!(body["signer_address"], signer.as_str());
+        assert!(signing::verify_message(&signer, fee_address, signature, Network::Bitcoin).unwrap());
+        // A swapped address doesn't carry the attestation over
+        assert!(!signing::verify_message(&signer, &address(1), signature, Network::Bitcoin).unwrap());
+    }
+
+    fn sample_payouts() -> Vec<(String, u64)> {
+        vec![("$seller".to_string(), 1_000_000), ("market@handcash.io".to_string(), 10_001)]
+    }
+
+    #[test]
+    fn handcash_bsv_body_pays_each_destination_in_bsv() {
+        let payments = handcash_payments(sample_payouts(), None);
+        let body = handcash_payment_body("Purchase ordinal x", &payments, HandCashCurrency::Bsv);
+
+        assert_eq!(
+            body,
+            json!({
+                "description": "Purchase ordinal x",
+                "payments": [
+                    { "destination": "$seller", "amount": 0.01, "currency": "BSV" },
+                    { "destination": "market@handcash.io", "amount": 0.00010001, "currency": "BSV" },
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn handcash_usd_body_rounds_each_destination_up_to_the_cent() {
+        // 50 USD per BSV: 0.01 BSV is 0.50 USD, 10001 sats is 0.0050005 USD
+        let payments = handcash_payments(sample_payouts(), Some(50.0));
+        let body = handcash_payment_body("Purchase ordinal x", &payments, HandCashCurrency::Usd);
+
+        assert_eq!(
+            body["payments"],
+            json!([
+                { "destination": "$seller", "amount": 0.5, "currency": "USD" },
+                { "destination": "market@handcash.io", "amount": 0.01, "currency": "USD" },
+            ])
+        );
+    }
+
+    #[test]
+    fn handcash_receipts_allow_fiat_rounding_but_not_bsv_shortfalls() {
+        let receipt = |sats: u64| json!({ "transactionId": "abc", "satoshiAmount": sats });
+
+        assert_eq!(verify_handcash_receipt(&receipt(1_010_001), 1_010_001, HandCashCurrency::Bsv).unwrap(), "abc");
+        assert!(verify_handcash_receipt(&receipt(1_000_000), 1_010_001, HandCashCurrency::Bsv).is_err());
+        assert!(verify_handcash_receipt(&receipt(1_000_000), 1_010_001, HandCashCurrency::Usd).is_ok());
+        assert!(verify_handcash_receipt(&json!({ "satoshiAmount": 1 }), 1, HandCashCurrency::Bsv).is_err());
+    }
+
+    #[test]
+    fn check_reservation_refuses_and_expires_over_age_listings() {
+        let db = listings_db().with_max_listing_lifetime(Some(std::time::Duration::from_secs(3600)));
+        let mut listing = create_listing(&db, 1, 10_000);
+        listing.created_at -= chrono::Duration::hours(2);
+        db.update_listing(&mut listing).unwrap();
+        let state = app_state(Default::default(), db);
+
+        let (status, Json(error)) = check_reservation(&state, &mut listing, Some(&address(8))).unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(error.error, "listing_inactive");
+
+        let stored = state.listings_db.get_listing(&listing.id).unwrap().unwrap();
+        assert_eq!(stored.status, ListingStatus::Expired);
+    }
+
+    #[tokio::test]
+    async fn prepare_batch_purchase_combines_and_holds_the_cart() {
+        let db = listings_db();
+        let first = create_listing(&db, 1, 10_000);
+        let second = create_listing(&db, 2, 20_000);
+        let state = app_state(config_with_wallet(&[100_000]).await, db);
+
+        let response = prepare_batch_purchase(
+            State(state.clone()),
+            ApiJson(BatchPreparePurchaseRequest {
+                listing_ids: vec![first.id.clone(), second.id.clone()],
+                buyer_ord_address: address(8),
+                buyer_payment_address: address(9),
+                split_change: None,
+                omit_op_return: true,
+            }),
+        )
+        .await
+        .unwrap();
+
+        let tx: Transaction = deserialize(&hex::decode(&response.raw_tx_hex).unwrap()).unwrap();
+        let inputs: Vec<String> = tx.input.iter().map(|i| i.previous_output.txid.to_string()).collect();
+        assert_eq!(inputs, [txid(1), txid(2), txid(0xee)]);
+
+        let pays = |addr: &str| {
+            let script = addr.parse::<Address<_>>().unwrap().assume_checked().script_pubkey();
+            tx.output
+                .iter()
+                .filter(|o| o.script_pubkey == script)
+                .map(|o| o.value.to_sat())
+                .collect::<Vec<_>>()
+        };
+        // Both ordinals land at the buyer at indexes 0 and 1
+        assert_eq!(tx.output[0].value.to_sat(), 1);
+        assert_eq!(tx.output[1].value.to_sat(), 1);
+        assert_eq!(pays(&address(8)), [1, 1]);
+        assert_eq!(pays(&address(1)), [first.fees.seller_receives]);
+        assert_eq!(pays(&address(2)), [second.fees.seller_receives]);
+        assert_eq!(
+            pays(state.config.fee_address_at(chrono::Utc::now())),
+            [first.fees.marketplace_fee, second.fees.marketplace_fee]
+        );
+
+        for listing in [&first, &second] {
+            let stored = state.listings_db.get_listing(&listing.id).unwrap().unwrap();
+            let hold = stored.cart_hold.expect("cart listing should be held");
+            assert_eq!(hold.buyer_ord_address, address(8));
+        }
+
+        // Another buyer can't take a held listing out of the cart
+        let mut held = state.listings_db.get_listing(&first.id).unwrap().unwrap();
+        let (status, _) = check_reservation(&state, &mut held, Some(&address(7))).unwrap_err();
+        assert_eq!(status, StatusCode::CONFLICT);
+    }
+
+    #[test]
+    fn validate_return_url_accepts_only_absolute_http_urls() {
+        assert!(validate_return_url("https://shop.example/done?order=1").is_ok());
+        assert!(validate_return_url("http://shop.example/done").is_ok());
+
+        assert!(validate_return_url("/relative/path").is_err());
+        assert!(validate_return_url("ftp://shop.example/done").is_err());
+        assert!(validate_return_url("javascript:alert(1)").is_err());
+        assert!(validate_return_url("file:///etc/passwd").is_err());
+    }
+
+    #[tokio::test]
+    async fn recent_sales_limit_is_capped() {
+        let state = app_state(Default::default(), listings_db());
+        for n in 0..=MAX_RECENT_SALES as u8 {
+            let listing = create_listing(&state.listings_db, n, 10_000);
+            state.listings_db.mark_listing_sold(&listing.id, None, &txid(n), None).unwrap();
+        }
+
+        let Json(body) = get_recent_sales(Query(RecentSalesQuery { limit: 1_000 }), State(state))
+            .await
+            .unwrap();
+        assert_eq!(body["count"], MAX_RECENT_SALES);
+        assert_eq!(body["sales"].as_array().unwrap().len(), MAX_RECENT_SALES);
+    }
+
+    #[tokio::test]
+    async fn portfolio_totals_sold_and_listed_value_separately() {
+        // The seller's wallet holds two inscriptions alongside a plain payment UTXO
+        let held_origins = [format!("{}_0", txid(0x21)), format!("{}_1", txid(0x22))];
+        let mut unspent: Vec<serde_json::Value> = held_origins
+            .iter()
+            .map(|origin| json!({
+                "txid": txid(0x30),
+                "vout": 0,
+                "outpoint": format!("{}_0", txid(0x30)),
+                "satoshis": 1,
+                "origin": { "outpoint": origin },
+            }))
+            .collect();
+        unspent.push(json!({ "txid": txid(0x31), "vout": 0, "satoshis": 50_000, "origin": null }));
+        let gorillapool = axum::Router::new().route(
+            "/txos/address/:address/unspent",
+            get(move || async move { Json(unspent) }),
+        );
+        let config = crate::config::Config {
+            gorillapool_base_url: serve(gorillapool).await,
+            gorillapool_retry_attempts: 1,
+            ..Default::default()
+        };
+        let state = app_state(config, listings_db());
+        let seller = address(1);
+        let list = |n: u8, price: u64| {
+            let request = CreateListingRequest {
+                seller_address: seller.clone(),
+                seller_ord_address: seller.clone(),
+                ..listing_request(n, price)
+            };
+            state.listings_db.create_listing(request, Default::default(), None, None).unwrap()
+        };
+
+        let sold = list(1, 10_000);
+        let listed = [list(2, 20_000), list(3, 30_000)];
+        let cancelled = list(4, 40_000);
+        state.listings_db.mark_listing_sold(&sold.id, Some(&address(9)), &txid(9), None).unwrap();
+        state.listings_db.cancel_listing(&cancelled.id, &seller).unwrap();
+        // Another seller's listing doesn't count
+        create_listing(&state.listings_db, 5, 50_000);
+
+        let Json(body) = get_wallet_portfolio(Path(seller.clone()), State(state)).await.unwrap();
+        let portfolio = &body["data"];
+        assert_eq!(portfolio["realized_value_sats"], sold.fees.seller_receives);
+        assert_eq!(
+            portfolio["unrealized_value_sats"],
+            listed.iter().map(|l| l.fees.seller_receives).sum::<u64>()
+        );
+        assert_eq!(portfolio["active_listings"].as_array().unwrap().len(), 2);
+        assert_eq!(portfolio["realized_sales"][0]["id"], sold.id);
+        let held: Vec<&str> = portfolio["held"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|ordinal| ordinal["origin"].as_str().unwrap())
+            .collect();
+        assert_eq!(held, held_origins);
+    }
+
+    #[tokio::test]
+    async fn broadcast_replays_the_stored_response_for_a_repeated_key() {
+        let (mapi_url, broadcasts) = mapi(std::time::Duration::ZERO).await;
+        let state = broadcast_state(mapi_url).await;
+        let listing = create_listing(&state.listings_db, 1, 50_000);
+        let raw_tx_hex = prepared_purchase_hex(&state, &listing).await;
+
+        let first = broadcast(&state, &listing, idempotency_key("k1"), &raw_tx_hex, None).await.unwrap();
+        assert_eq!(first["success"], true);
+        assert_eq!(first["mapi"]["returnResult"], "success");
+
+        // The listing is sold now, but the retry still gets the original result
+        let replay = broadcast(&state, &listing, idempotency_key("k1"), &raw_tx_hex, None).await.unwrap();
+        assert_eq!(replay, first);
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 1);
+
+        // Without the key the retry is refused
+        let (status, _) = broadcast(&state, &listing, HeaderMap::new(), &raw_tx_hex, None).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn concurrent_broadcasts_with_one_key_broadcast_once() {
+        let (mapi_url, broadcasts) = mapi(std::time::Duration::from_millis(200)).await;
+        let state = broadcast_state(mapi_url).await;
+        let listing = create_listing(&state.listings_db, 1, 50_000);
+        let raw_tx_hex = prepared_purchase_hex(&state, &listing).await;
+
+        let (a, b) = tokio::join!(
+            broadcast(&state, &listing, idempotency_key("k1"), &raw_tx_hex, None),
+            broadcast(&state, &listing, idempotency_key("k1"), &raw_tx_hex, None),
+        );
+
+        let (ok, in_flight) = if a.is_ok() { (a, b) } else { (b, a) };
+        assert_eq!(ok.unwrap()["success"], true);
+        assert_eq!(in_flight.unwrap_err().0, StatusCode::CONFLICT);
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn failed_broadcast_frees_its_key_for_a_retry() {
+        let (mapi_url, broadcasts) = mapi(std::time::Duration::ZERO).await;
+        let state = broadcast_state(mapi_url).await;
+        let listing = create_listing(&state.listings_db, 1, 50_000);
+        let raw_tx_hex = prepared_purchase_hex(&state, &listing).await;
+
+        let (status, _) = broadcast(&state, &listing, idempotency_key("k1"), "zz", None).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let retried = broadcast(&state, &listing, idempotency_key("k1"), &raw_tx_hex, None).await.unwrap();
+        assert_eq!(retried["success"], true);
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn broadcast_posts_the_completion_callback_only_when_enabled() {
+        let (tx, mut callbacks) = tokio::sync::mpsc::unbounded_channel();
+        let checkout = axum::Router::new().route(
+            "/done",
+            post(move |Json(body): Json<serde_json::Value>| async move {
+                tx.send(body).unwrap();
+            }),
+        );
+        let return_url = format!("{}/done", serve(checkout).await);
+        let (mapi_url, _) = mapi(std::time::Duration::ZERO).await;
+
+        // Disabled: the URL is echoed back but never called
+        let state = broadcast_state(mapi_url.clone()).await;
+        let listing = create_listing(&state.listings_db, 1, 50_000);
+        let raw_tx_hex = prepared_purchase_hex(&state, &listing).await;
+        let body = broadcast(&state, &listing, HeaderMap::new(), &raw_tx_hex, Some(return_url.clone()))
+            .await
+            .unwrap();
+        assert_eq!(body["return_url"], return_url.as_str());
+        let waited = tokio::time::timeout(std::time::Duration::from_millis(300), callbacks.recv()).await;
+        assert!(waited.is_err(), "callback sent while disabled");
+
+        // Enabled: the checkout page hears about the sale. Its loopback host would
+        // be refused in production, so the state's callbacks skip that check.
+        let mut state = broadcast_state(mapi_url).await;
+        state.config.purchase_callbacks_enabled = true;
+        state.purchase_callbacks = PurchaseCallbacks::allowing_any_host();
+        let listing = create_listing(&state.listings_db, 1, 50_000);
+        let raw_tx_hex = prepared_purchase_hex(&state, &listing).await;
+        let body = broadcast(&state, &listing, HeaderMap::new(), &raw_tx_hex, Some(return_url))
+            .await
+            .unwrap();
+
+        let callback = tokio::time::timeout(std::time::Duration::from_secs(5), callbacks.recv())
+            .await
+            .expect("no callback received")
+            .unwrap();
+        assert_eq!(callback["listing_id"], listing.id.as_str());
+        assert_eq!(callback["txid"], body["txid"]);
+        assert_eq!(callback["status"], "sold");
+    }
+
+    #[tokio::test]
+    async fn broadcast_refuses_an_internal_return_url_when_callbacks_are_enabled() {
+        let (mapi_url, broadcasts) = mapi(std::time::Duration::ZERO).await;
+        let mut state = broadcast_state(mapi_url).await;
+        state.config.purchase_callbacks_enabled = true;
+        let listing = create_listing(&state.listings_db, 1, 50_000);
+        let raw_tx_hex = prepared_purchase_hex(&state, &listing).await;
+
+        let internal = Some("http://169.254.169.254/latest/meta-data".to_string());
+        let (status, _) = broadcast(&state, &listing, HeaderMap::new(), &raw_tx_hex, internal).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 0);
+    }
+
+    /// `config` with the fee address rotating from its default to address 2 at `boundary`
+    fn rotating_fee_address(
+        config: crate::config::Config,
+        boundary: chrono::DateTime<chrono::Utc>,
+    ) -> crate::config::Config {
+        crate::config::Config {
+            fee_address_schedule: vec![(boundary, address(2))],
+            ..config
+        }
+    }
+
+    /// Satoshis each output of `raw_tx_hex` pays to `addr`
+    fn paid_to(raw_tx_hex: &str, addr: &str) -> Vec<u64> {
+        let tx: Transaction = deserialize(&hex::decode(raw_tx_hex).unwrap()).unwrap();
+        let script = addr.parse::<Address<_>>().unwrap().assume_checked().script_pubkey();
+        tx.output.iter().filter(|o| o.script_pubkey == script).map(|o| o.value.to_sat()).collect()
+    }
+
+    #[tokio::test]
+    async fn purchases_pay_the_fee_address_in_effect_and_honor_the_rotation_grace() {
+        let (mapi_url, broadcasts) = mapi(std::time::Duration::ZERO).await;
+        let wallet = crate::config::Config { mapi_url, ..config_with_wallet(&[100_000]).await };
+        let original = wallet.marketplace_fee_address.clone();
+        let db = listings_db();
+        let listing = create_listing(&db, 1, 50_000);
+        let fee = vec![listing.fees.marketplace_fee];
+        let now = chrono::Utc::now();
+        let state_rotating_at = |boundary| app_state(rotating_fee_address(wallet.clone(), boundary), db.clone());
+
+        // Prepared before the boundary: the original address is paid
+        let before = state_rotating_at(now + chrono::Duration::hours(1));
+        let old_tx = prepared_purchase_hex(&before, &listing).await;
+        assert_eq!(paid_to(&old_tx, &original), fee);
+        assert!(paid_to(&old_tx, &address(2)).is_empty());
+        let Json(body) = get_fee_address(State(before)).await.unwrap();
+        assert_eq!(body["address"], original.as_str());
+
+        // Prepared after it: the new one is
+        let after = state_rotating_at(now - chrono::Duration::minutes(30));
+        let new_tx = prepared_purchase_hex(&after, &listing).await;
+        assert_eq!(paid_to(&new_tx, &address(2)), fee);
+        assert!(paid_to(&new_tx, &original).is_empty());
+
+        // HandCash follows the same schedule
+        let handcash_fee = |at| handcash_payouts(&after.config, &listing, at).unwrap()[1].clone();
+        assert_eq!(handcash_fee(now - chrono::Duration::hours(1)), (original.clone(), fee[0]));
+        assert_eq!(handcash_fee(now), (address(2), fee[0]));
+
+        // A tx paying the old address is refused once the grace window has passed...
+        let long_after = state_rotating_at(now - FEE_ADDRESS_ROTATION_GRACE - chrono::Duration::minutes(1));
+        let (status, message) = broadcast(&long_after, &listing, HeaderMap::new(), &old_tx, None).await.unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert!(message.contains("marketplace fee"), "{}", message);
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 0);
+
+        // ...but accepted within it
+        let body = broadcast(&after, &listing, HeaderMap::new(), &old_tx, None).await.unwrap();
+        assert_eq!(body["success"], true);
+        assert_eq!(broadcasts.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn content_cache_control_is_immutable_only_once_deeply_confirmed() {
+        let short = "public, max-age=60";
+        let immutable = "public, max-age=31536000, immutable";
+
+        // Unconfirmed, or the chain tip is unknown
+        assert_eq!(content_cache_control(None, Some(800_000)), short);
+        assert_eq!(content_cache_control(Some(800_000), None), short);
+
+        // Confirmed but still within re-org range
+        assert_eq!(content_cache_control(Some(800_000), Some(800_000)), short);
+        assert_eq!(content_cache_control(Some(800_000), Some(800_004)), short);
+
+        // Exactly CONTENT_FINAL_CONFIRMATIONS deep, and beyond
+        assert_eq!(content_cache_control(Some(800_000), Some(800_005)), immutable);
+        assert_eq!(content_cache_control(Some(800_000), Some(900_000)), immutable);
+
+        // A tip behind the inscription's height (stale tip) isn't trusted
+        assert_eq!(content_cache_control(Some(800_010), Some(800_000)), short);
+    }
+}
@@ -1,59 +1,282 @@
+pub mod admin_auth;
+pub mod extract;
 pub mod handlers;
+pub mod rate_limit;
+pub mod timeout;
 
 pub use handlers::{
-    AppState, root, health, 
-    get_wallet_ordinals, get_ordinal_details, get_ordinal_content, 
-    search_ordinals,
-    get_listings, get_listing, create_listing, cancel_listing, purchase_listing,
-    get_listing_by_origin, calculate_fees,
-    prepare_purchase,     // ← NEW
+    AppState, root, health, health_ready, get_stats, get_metrics, track_requests, get_fee_address, listing_events, startup_report, evict_content, clear_caches, invalidate_wallet_cache, invalidate_ordinal_cache, prune_listings,
+    get_wallet_ordinals, get_wallet_portfolio, get_wallet_history, watch_listing, unwatch_listing, get_watchlist, get_ordinal_details, get_ordinal_content, get_ordinal_preview,
+    search_ordinals, get_collection_holders, list_collections,
+    get_listings, get_listing, get_listing_by_seq, create_listing, create_listings_batch, cancel_listing, reprice_listing,
+    create_offer, get_listing_offers, accept_offer, reject_offer, place_bid, get_listing_bids, purchase_listing,
+    get_listing_by_origin, calculate_fees, get_recent_sales, get_purchase_tx_template,
+    prepare_purchase,
+    estimate_purchase,
+    prepare_batch_purchase,
     broadcast_purchase,
-    purchase_handcash,   // ← NEW
+    purchase_handcash,
+    prepare_lock_tx,
+    broadcast_lock,
 };
 
-use axum::{routing::{get, post}, Router};
-use tower_http::cors::{Any, CorsLayer};
+use axum::{middleware, routing::{delete, get, post}, Router};
+use std::sync::Arc;
+use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, Any, CorsLayer};
+use axum::http::{HeaderName, HeaderValue, Method, Request};
+use crate::config::Config;
+use admin_auth::{require_admin_token, AdminToken};
+use rate_limit::{limit_by_ip, InboundRateLimiter};
+use timeout::{enforce_timeout, RequestTimeout};
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
 use tower_http::trace::TraceLayer;
 
 /// Build the API router with all routes
 pub fn create_router(state: AppState) -> Router {
-    let cors = CorsLayer::new()
-        .allow_origin(Any)
-        .allow_methods(Any)
-        .allow_headers(Any);
+    let cors = cors_layer(&state.config);
+
+    // Operational endpoints, all behind the admin bearer token
+    let admin_routes = Router::new()
+        .route("/startup-report", get(startup_report))
+        .route("/cache/content/:origin", delete(evict_content))
+        .route("/cache/clear", post(clear_caches))
+        .route("/cache/invalidate/wallet/:address", post(invalidate_wallet_cache))
+        .route("/cache/invalidate/ordinal/:origin", post(invalidate_ordinal_cache))
+        .route("/prune", post(prune_listings))
+        .route_layer(middleware::from_fn_with_state(
+            AdminToken::new(state.config.admin_token.as_deref()),
+            require_admin_token,
+        ));
+
+    // Media downloads get their own, tighter per-IP budget so they can't starve the
+    // JSON endpoints, and a longer timeout since large inscriptions are slow to fetch
+    let content_routes = Router::new()
+        .route(
+            "/ordinal/:origin/content",
+            get(get_ordinal_content).layer(middleware::from_fn_with_state(
+                InboundRateLimiter::new(state.config.content_rate_limit_per_second, state.config.trusted_proxy_hops),
+                limit_by_ip,
+            )),
+        )
+        .route_layer(middleware::from_fn_with_state(
+            RequestTimeout(state.config.content_request_timeout),
+            enforce_timeout,
+        ));
 
     Router::new()
         // Info endpoints
         .route("/", get(root))
         .route("/health", get(health))
+        .route("/health/ready", get(health_ready))
+        .route("/stats", get(get_stats))
+        .route("/metrics", get(get_metrics))
+        .route("/fee-address", get(get_fee_address))
+        .route("/events", get(listing_events))
+        .nest("/admin", admin_routes)
         
         // Wallet endpoints
         .route("/wallet/:address", get(get_wallet_ordinals))
+        .route("/wallet/:address/portfolio", get(get_wallet_portfolio))
+        .route("/wallet/:address/history", get(get_wallet_history))
+        .route("/watchlist/:address", get(get_watchlist))
+        .route("/watchlist/:address/:listing_id", post(watch_listing).delete(unwatch_listing))
         
-        // Ordinal endpoints
+        // Ordinal endpoints (content is in `content_routes`)
         .route("/ordinal/:origin", get(get_ordinal_details))
-        .route("/ordinal/:origin/content", get(get_ordinal_content))
+        .route("/ordinal/:origin/preview", get(get_ordinal_preview))
         .route("/ordinal/:origin/listing", get(get_listing_by_origin))
         
+        // Collection endpoints
+        .route("/collections", get(list_collections))
+        .route("/collections/:collection_id/holders", get(get_collection_holders))
+        
         // Listings endpoints
         .route("/listings", get(get_listings))
         .route("/listings", post(create_listing))
+        .route("/listings/batch", post(create_listings_batch))
         .route("/listings/:id", get(get_listing))
+        .route("/listings/seq/:seq", get(get_listing_by_seq))
         .route("/listings/:id/cancel", post(cancel_listing))
+        .route("/listings/:id/reprice", post(reprice_listing))
+        .route("/listings/:id/offers", get(get_listing_offers).post(create_offer))
+        .route("/offers/:id/accept", post(accept_offer))
+        .route("/offers/:id/reject", post(reject_offer))
+        .route("/listings/:id/bids", get(get_listing_bids).post(place_bid))
+        .route("/listings/:id/prepare-lock-tx", post(prepare_lock_tx))
+        .route("/listings/:id/broadcast-lock", post(broadcast_lock))
         .route("/listings/:id/purchase", post(purchase_listing))
-        .route("/listings/:id/prepare-purchase", post(prepare_purchase))     // ← NEW
-        .route("/listings/:id/broadcast-purchase", post(broadcast_purchase)) // ← NEW
+        .route("/listings/:id/tx-template", get(get_purchase_tx_template))
+        .route("/listings/:id/prepare-purchase", post(prepare_purchase))
+        .route("/listings/:id/estimate", post(estimate_purchase))
+        .route("/listings/:id/broadcast-purchase", post(broadcast_purchase))
         .route("/listings/:id/purchase-handcash", post(purchase_handcash))
+        .route("/purchases/prepare-batch", post(prepare_batch_purchase))
+        .route("/sales/recent", get(get_recent_sales))
         // Fee calculation
         .route("/fees/calculate", get(calculate_fees))
         
         // Search
         .route("/search", get(search_ordinals))
         
+        // Every route but content gets the default timeout
+        .route_layer(middleware::from_fn_with_state(
+            RequestTimeout(state.config.request_timeout),
+            enforce_timeout,
+        ))
+        .merge(content_routes)
+
         // Middleware
+        .route_layer(middleware::from_fn_with_state(Arc::clone(&state.metrics), track_requests))
+        .layer(middleware::from_fn_with_state(
+            InboundRateLimiter::new(state.config.inbound_rate_limit_per_second, state.config.trusted_proxy_hops),
+            limit_by_ip,
+        ))
         .layer(cors)
-        .layer(TraceLayer::new_for_http())
+        .layer(TraceLayer::new_for_http().make_span_with(|request: &Request<_>| {
+            // Every log line emitted while serving the request (including
+            // GorillaPool client calls) carries its request ID
+            let request_id = request
+                .headers()
+                .get("x-request-id")
+                .and_then(|v| v.to_str().ok())
+                .unwrap_or_default();
+            tracing::info_span!(
+                "request",
+                method = %request.method(),
+                uri = %request.uri(),
+                request_id = %request_id,
+            )
+        }))
+        // Keep a client-supplied x-request-id, otherwise generate a UUID, and echo it back
+        .layer(PropagateRequestIdLayer::x_request_id())
+        .layer(SetRequestIdLayer::x_request_id(MakeRequestUuid))
         
         // State
         .with_state(state)
-}
\ No newline at end of file
+}
+
+/// CORS policy from the `CORS_*` settings. An explicit origin list is echoed back
+/// per matching origin with credentials allowed; browsers refuse credentials with
+/// wildcards, so methods and headers then mirror the preflight instead of `*`.
+fn cors_layer(config: &Config) -> CorsLayer {
+    let wildcard = (config.cors_allowed_origins.is_empty() && cfg!(debug_assertions))
+        || config.cors_allowed_origins.iter().any(|o| o == "*");
+
+    let (origin, credentials) = if wildcard {
+        (AllowOrigin::from(Any), false)
+    } else {
+        let origins: Vec<HeaderValue> = config
+            .cors_allowed_origins
+            .iter()
+            .filter_map(|o| {
+                HeaderValue::from_str(o.trim_end_matches('/'))
+                    .map_err(|_| tracing::warn!("Ignoring invalid CORS origin: {}", o))
+                    .ok()
+            })
+            .collect();
+        if origins.is_empty() {
+            tracing::warn!("No CORS origins configured; cross-origin requests will be refused");
+        }
+        (AllowOrigin::list(origins), true)
+    };
+
+    let methods = if config.cors_allowed_methods.is_empty() {
+        if credentials { AllowMethods::mirror_request() } else { AllowMethods::from(Any) }
+    } else {
+        AllowMethods::list(config.cors_allowed_methods.iter().filter_map(|m| {
+            Method::from_bytes(m.as_bytes())
+                .map_err(|_| tracing::warn!("Ignoring invalid CORS method: {}", m))
+                .ok()
+        }))
+    };
+
+    let headers = if config.cors_allowed_headers.is_empty() {
+        if credentials { AllowHeaders::mirror_request() } else { AllowHeaders::from(Any) }
+    } else {
+        AllowHeaders::list(config.cors_allowed_headers.iter().filter_map(|h| {
+            HeaderName::from_bytes(h.as_bytes())
+                .map_err(|_| tracing::warn!("Ignoring invalid CORS header: {}", h))
+                .ok()
+        }))
+    };
+
+    CorsLayer::new()
+        .allow_origin(origin)
+        .allow_methods(methods)
+        .allow_headers(headers)
+        .allow_credentials(credentials)
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{app_state, listings_db, serve, txid};
+    use axum::Json;
+    use reqwest::StatusCode;
+
+    /// Mock GorillaPool serving one text inscription at any origin
+    fn gorillapool() -> Router {
+        Router::new()
+            .route(
+                "/inscriptions/origin/:origin",
+                get(|| async {
+                    Json(serde_json::json!({
+                        "txid": txid(1),
+                        "vout": 0,
+                        "origin": format!("{}_0", txid(1)),
+                        "file": { "hash": "", "size": 2, "type": "text/plain" },
+                    }))
+                }),
+            )
+            .route("/files/inscriptions/:origin", get(|| async { "hi" }))
+            .route("/blocks/tip", get(|| async { Json(serde_json::json!({ "height": 800_000 })) }))
+    }
+
+    #[tokio::test]
+    async fn content_limit_leaves_metadata_requests_alone() {
+        let config = Config {
+            gorillapool_base_url: serve(gorillapool()).await,
+            gorillapool_retry_attempts: 1,
+            inbound_rate_limit_per_second: 100,
+            content_rate_limit_per_second: 1,
+            ..Default::default()
+        };
+        let base = serve(create_router(app_state(config, listings_db()))).await;
+        let client = reqwest::Client::new();
+        let get = |path: String| client.get(format!("{}{}", base, path)).send();
+        let origin = format!("{}_0", txid(1));
+
+        let first = get(format!("/ordinal/{}/content", origin)).await.unwrap();
+        assert_eq!(first.status(), StatusCode::OK);
+        let second = get(format!("/ordinal/{}/content", origin)).await.unwrap();
+        assert_eq!(second.status(), StatusCode::TOO_MANY_REQUESTS);
+        assert!(second.headers().contains_key(reqwest::header::RETRY_AFTER));
+
+        for _ in 0..3 {
+            let metadata = get(format!("/ordinal/{}", origin)).await.unwrap();
+            assert_eq!(metadata.status(), StatusCode::OK);
+        }
+    }
+
+    #[tokio::test]
+    async fn rotating_forwarded_for_does_not_escape_the_limit() {
+        let config = Config {
+            inbound_rate_limit_per_second: 1,
+            ..Default::default()
+        };
+        let base = serve(create_router(app_state(config, listings_db()))).await;
+        let client = reqwest::Client::new();
+
+        let statuses: Vec<StatusCode> = futures_util::future::join_all((1..=3).map(|n| {
+            client
+                .get(format!("{}/health", base))
+                .header("x-forwarded-for", format!("203.0.113.{}", n))
+                .send()
+        }))
+        .await
+        .into_iter()
+        .map(|r| r.unwrap().status())
+        .collect();
+
+        assert!(statuses.contains(&StatusCode::TOO_MANY_REQUESTS), "{:?}", statuses);
+    }
+}
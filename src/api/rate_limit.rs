@@ -0,0 +1,144 @@
+use crate::models::ApiError;
+use axum::{
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use governor::clock::{Clock, DefaultClock};
+use governor::{DefaultKeyedRateLimiter, Quota, RateLimiter};
+use std::net::{IpAddr, SocketAddr};
+use std::num::NonZeroU32;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+/// How often idle client entries are dropped from the limiter
+const RETAIN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Per-client-IP limit on inbound requests (disabled when the configured rate is 0)
+#[derive(Clone)]
+pub struct InboundRateLimiter {
+    limiter: Option<Arc<DefaultKeyedRateLimiter<IpAddr>>>,
+    /// Proxies in front of the server whose `X-Forwarded-For` entries are trusted
+    trusted_proxy_hops: usize,
+}
+
+impl InboundRateLimiter {
+    pub fn new(per_second: u32, trusted_proxy_hops: usize) -> Self {
+        let Some(rate) = NonZeroU32::new(per_second) else {
+            return Self { limiter: None, trusted_proxy_hops };
+        };
+
+        let limiter = Arc::new(RateLimiter::keyed(Quota::per_second(rate)));
+
+        // Keep memory bounded by forgetting clients whose buckets have refilled
+        let retained = Arc::clone(&limiter);
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(RETAIN_INTERVAL);
+            loop {
+                ticker.tick().await;
+                retained.retain_recent();
+                retained.shrink_to_fit();
+            }
+        });
+
+        Self { limiter: Some(limiter), trusted_proxy_hops }
+    }
+}
+
+/// Reject requests over the per-IP limit with 429 and a `Retry-After`
+pub async fn limit_by_ip(
+    State(limiter): State<InboundRateLimiter>,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(ref keyed) = limiter.limiter else {
+        return next.run(request).await;
+    };
+
+    let peer = connect_info.map(|c| c.0);
+    let Some(ip) = client_ip(request.headers(), peer, limiter.trusted_proxy_hops) else {
+        return next.run(request).await;
+    };
+
+    match keyed.check_key(&ip) {
+        Ok(()) => next.run(request).await,
+        Err(not_until) => {
+            let wait = not_until.wait_time_from(DefaultClock::default().now());
+            let retry_after = wait.as_secs_f64().ceil().max(1.0) as u64;
+            debug!("Rate limited {} for {}s", ip, retry_after);
+            (
+                StatusCode::TOO_MANY_REQUESTS,
+                [(header::RETRY_AFTER, retry_after.to_string())],
+                Json(ApiError::new("rate_limited", "Too many requests, slow down")),
+            )
+                .into_response()
+        }
+    }
+}
+
+/// Client IP. Behind `trusted_proxy_hops` proxies it's the `X-Forwarded-For` entry
+/// the outermost proxy appended, `trusted_proxy_hops` from the right; entries left
+/// of it are client-supplied and ignored. Otherwise, or if the header is too short
+/// to have passed every proxy, it's the peer address.
+fn client_ip(headers: &HeaderMap, peer: Option<SocketAddr>, trusted_proxy_hops: usize) -> Option<IpAddr> {
+    let peer = peer.map(|p| p.ip());
+    if trusted_proxy_hops == 0 {
+        return peer;
+    }
+
+    // Proxies may each add their own header line rather than extend one
+    let hops: Vec<&str> = headers
+        .get_all("x-forwarded-for")
+        .iter()
+        .filter_map(|v| v.to_str().ok())
+        .flat_map(|v| v.split(','))
+        .map(str::trim)
+        .collect();
+
+    hops.len()
+        .checked_sub(trusted_proxy_hops)
+        .and_then(|i| hops[i].parse().ok())
+        .or(peer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    fn forwarded_for(values: &[&str]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for value in values {
+            headers.append("x-forwarded-for", HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn client_ip_ignores_forwarded_for_without_trusted_proxies() {
+        let peer = SocketAddr::new(ip("198.51.100.1"), 40000);
+        let headers = forwarded_for(&["203.0.113.7"]);
+        assert_eq!(client_ip(&headers, Some(peer), 0), Some(ip("198.51.100.1")));
+    }
+
+    #[test]
+    fn client_ip_takes_the_entry_the_outermost_trusted_proxy_appended() {
+        let peer = SocketAddr::new(ip("10.0.0.2"), 40000);
+        // The client forged the first entry; the edge proxy appended the second and
+        // the inner proxy the third
+        let headers = forwarded_for(&["1.2.3.4, 203.0.113.7", "10.0.0.1"]);
+
+        assert_eq!(client_ip(&headers, Some(peer), 1), Some(ip("10.0.0.1")));
+        assert_eq!(client_ip(&headers, Some(peer), 2), Some(ip("203.0.113.7")));
+        // Fewer entries than proxies: the request didn't come through them all
+        assert_eq!(client_ip(&headers, Some(peer), 4), Some(ip("10.0.0.2")));
+    }
+}
@@ -0,0 +1,36 @@
+use crate::models::ApiError;
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+    Json,
+};
+use std::time::Duration;
+use tracing::warn;
+
+/// How long a group of routes has to produce a response
+#[derive(Clone, Copy)]
+pub struct RequestTimeout(pub Duration);
+
+/// Answer 504 when the handler hasn't produced a response within the route's
+/// budget. Only the response head is timed, so streamed bodies (SSE) aren't cut off.
+pub async fn enforce_timeout(
+    State(RequestTimeout(limit)): State<RequestTimeout>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.uri().path().to_string();
+
+    match tokio::time::timeout(limit, next.run(request)).await {
+        Ok(response) => response,
+        Err(_) => {
+            warn!("Request to {} timed out after {}s", path, limit.as_secs());
+            (
+                StatusCode::GATEWAY_TIMEOUT,
+                Json(ApiError::new("timeout", "The request took too long; try again later")),
+            )
+                .into_response()
+        }
+    }
+}
@@ -1,16 +1,43 @@
 use crate::config::Config;
-use crate::models::{CacheStats, OrdinalDetails, WalletOrdinals};
+use crate::models::{CacheConfig, CacheEntryCounts, CacheStats, CollectionHolders, MarketplaceStats, OrdinalDetails, WalletOrdinals};
 use moka::future::Cache;
+use moka::notification::RemovalCause;
+use std::future::Future;
 use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
 use tracing::{debug, info};
 
+/// How long a computed collection holder aggregate is served before recomputing
+const HOLDERS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How long marketplace stats are served before rescanning listings
+const STATS_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// How long the chain tip height is reused before asking GorillaPool again
+const CHAIN_TIP_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// How long a BSV/USD rate from the price oracle is reused
+const EXCHANGE_RATE_CACHE_TTL: Duration = Duration::from_secs(60);
+
 /// Cache manager for ordinal data with different TTLs per data type
 pub struct CacheManager {
     wallet_cache: Cache<String, WalletOrdinals>,
     ordinal_cache: Cache<String, OrdinalDetails>,
     content_cache: Cache<String, (Vec<u8>, String)>,
+    /// Resized JPEG previews keyed by (origin, max width)
+    preview_cache: Cache<(String, u32), Vec<u8>>,
+    holders_cache: Cache<String, CollectionHolders>,
+    stats_cache: Cache<(), MarketplaceStats>,
+    chain_tip_cache: Cache<(), u64>,
+    exchange_rate_cache: Cache<(), f64>,
+    /// Keys GorillaPool recently answered "not found" for (absent if negative caching is off)
+    not_found_cache: Option<Cache<String, ()>>,
     hits: AtomicU64,
     misses: AtomicU64,
+    /// Content entries removed by capacity or TTL (not explicit eviction)
+    content_evictions: Arc<AtomicU64>,
+    cache_config: CacheConfig,
 }
 
 impl CacheManager {
@@ -25,11 +52,51 @@ impl CacheManager {
             .time_to_live(config.metadata_cache_ttl)
             .build();
 
+        let content_evictions = Arc::new(AtomicU64::new(0));
+        let eviction_counter = Arc::clone(&content_evictions);
         let content_cache = Cache::builder()
             .max_capacity(config.max_cache_entries / 10)
             .time_to_live(config.content_cache_ttl)
+            .eviction_listener(move |_key, _value, cause: RemovalCause| {
+                if cause.was_evicted() {
+                    eviction_counter.fetch_add(1, Ordering::Relaxed);
+                }
+            })
+            .build();
+
+        let preview_cache = Cache::builder()
+            .max_capacity(config.max_cache_entries / 10)
+            .time_to_live(config.content_cache_ttl)
+            .support_invalidation_closures()
+            .build();
+
+        let holders_cache = Cache::builder()
+            .max_capacity(1_000)
+            .time_to_live(HOLDERS_CACHE_TTL)
+            .build();
+
+        let stats_cache = Cache::builder()
+            .max_capacity(1)
+            .time_to_live(STATS_CACHE_TTL)
+            .build();
+
+        let chain_tip_cache = Cache::builder()
+            .max_capacity(1)
+            .time_to_live(CHAIN_TIP_CACHE_TTL)
             .build();
 
+        let exchange_rate_cache = Cache::builder()
+            .max_capacity(1)
+            .time_to_live(EXCHANGE_RATE_CACHE_TTL)
+            .build();
+
+        let not_found_cache = config.negative_cache_ttl.map(|ttl| {
+            Cache::builder()
+                .max_capacity(config.max_cache_entries)
+                .time_to_live(ttl)
+                .build()
+        });
+
         info!(
             "Cache initialized: wallet TTL={}s, metadata TTL={}s, content TTL={}s",
             config.ownership_cache_ttl.as_secs(),
@@ -41,8 +108,21 @@ impl CacheManager {
             wallet_cache,
             ordinal_cache,
             content_cache,
+            preview_cache,
+            holders_cache,
+            stats_cache,
+            chain_tip_cache,
+            exchange_rate_cache,
+            not_found_cache,
             hits: AtomicU64::new(0),
             misses: AtomicU64::new(0),
+            content_evictions,
+            cache_config: CacheConfig {
+                ownership_cache_ttl_secs: config.ownership_cache_ttl.as_secs(),
+                metadata_cache_ttl_secs: config.metadata_cache_ttl.as_secs(),
+                content_cache_ttl_secs: config.content_cache_ttl.as_secs(),
+                max_cache_entries: config.max_cache_entries,
+            },
         }
     }
 
@@ -62,15 +142,23 @@ impl CacheManager {
         }
     }
 
-    pub async fn set_wallet_ordinals(&self, address: &str, data: &WalletOrdinals) {
+    /// Cached wallet data, or the result of `fetch` (cached on success). Concurrent
+    /// misses for the same address share a single in-flight `fetch`.
+    pub async fn get_or_fetch_wallet_ordinals(
+        &self,
+        address: &str,
+        fetch: impl Future<Output = anyhow::Result<WalletOrdinals>>,
+    ) -> Result<WalletOrdinals, Arc<anyhow::Error>> {
         let key = format!("wallet:{}", address);
-        self.wallet_cache.insert(key, data.clone()).await;
+        self.wallet_cache.try_get_with(key, fetch).await
     }
 
-    pub async fn invalidate_wallet(&self, address: &str) {
+    /// Drop a wallet's cached ordinals, returning whether an entry was cached
+    pub async fn invalidate_wallet(&self, address: &str) -> bool {
         let key = format!("wallet:{}", address);
-        self.wallet_cache.invalidate(&key).await;
+        let removed = self.wallet_cache.remove(&key).await.is_some();
         debug!("Invalidated wallet cache: {}", address);
+        removed
     }
 
     pub async fn get_ordinal_details(&self, origin: &str) -> Option<OrdinalDetails> {
@@ -89,9 +177,95 @@ impl CacheManager {
 
     pub async fn set_ordinal_details(&self, origin: &str, data: &OrdinalDetails) {
         let key = format!("ordinal:{}", origin);
+        // A positive result replaces any earlier "not found"
+        if let Some(ref not_found) = self.not_found_cache {
+            not_found.invalidate(&key).await;
+        }
         self.ordinal_cache.insert(key, data.clone()).await;
     }
 
+    /// Whether GorillaPool recently reported `origin` as unknown
+    pub async fn is_ordinal_not_found(&self, origin: &str) -> bool {
+        let Some(ref not_found) = self.not_found_cache else {
+            return false;
+        };
+        let found = not_found.contains_key(&format!("ordinal:{}", origin));
+        if found {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        }
+        found
+    }
+
+    /// Remember that GorillaPool doesn't know `origin`, for the negative cache TTL
+    pub async fn set_ordinal_not_found(&self, origin: &str) {
+        if let Some(ref not_found) = self.not_found_cache {
+            not_found.insert(format!("ordinal:{}", origin), ()).await;
+        }
+    }
+
+    /// Drop an ordinal's cached details, returning whether an entry was cached
+    pub async fn invalidate_ordinal(&self, origin: &str) -> bool {
+        let key = format!("ordinal:{}", origin);
+        if let Some(ref not_found) = self.not_found_cache {
+            not_found.invalidate(&key).await;
+        }
+        let removed = self.ordinal_cache.remove(&key).await.is_some();
+        debug!("Invalidated ordinal cache: {}", origin);
+        removed
+    }
+
+    /// All cached ordinal details belonging to a collection
+    pub fn ordinals_in_collection(&self, collection_id: &str) -> Vec<OrdinalDetails> {
+        self.ordinal_cache
+            .iter()
+            .filter(|(_, details)| details.collection_id.as_deref() == Some(collection_id))
+            .map(|(_, details)| details)
+            .collect()
+    }
+
+    pub async fn get_collection_holders(&self, collection_id: &str) -> Option<CollectionHolders> {
+        let key = format!("holders:{}", collection_id);
+        match self.holders_cache.get(&key).await {
+            Some(v) => {
+                self.hits.fetch_add(1, Ordering::Relaxed);
+                Some(v)
+            }
+            None => {
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                None
+            }
+        }
+    }
+
+    pub async fn set_collection_holders(&self, collection_id: &str, data: &CollectionHolders) {
+        let key = format!("holders:{}", collection_id);
+        self.holders_cache.insert(key, data.clone()).await;
+    }
+
+    pub async fn get_marketplace_stats(&self) -> Option<MarketplaceStats> {
+        self.stats_cache.get(&()).await
+    }
+
+    pub async fn set_marketplace_stats(&self, stats: &MarketplaceStats) {
+        self.stats_cache.insert((), stats.clone()).await;
+    }
+
+    pub async fn get_chain_tip(&self) -> Option<u64> {
+        self.chain_tip_cache.get(&()).await
+    }
+
+    pub async fn set_chain_tip(&self, height: u64) {
+        self.chain_tip_cache.insert((), height).await;
+    }
+
+    pub async fn get_bsv_usd_rate(&self) -> Option<f64> {
+        self.exchange_rate_cache.get(&()).await
+    }
+
+    pub async fn set_bsv_usd_rate(&self, rate: f64) {
+        self.exchange_rate_cache.insert((), rate).await;
+    }
+
     pub async fn get_content(&self, origin: &str) -> Option<(Vec<u8>, String)> {
         let key = format!("content:{}", origin);
         match self.content_cache.get(&key).await {
@@ -111,6 +285,35 @@ impl CacheManager {
         self.content_cache.insert(key, (data.to_vec(), content_type.to_string())).await;
     }
 
+    pub async fn get_preview(&self, origin: &str, width: u32) -> Option<Vec<u8>> {
+        let cached = self.preview_cache.get(&(origin.to_string(), width)).await;
+        if cached.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        cached
+    }
+
+    pub async fn set_preview(&self, origin: &str, width: u32, jpeg: &[u8]) {
+        self.preview_cache.insert((origin.to_string(), width), jpeg.to_vec()).await;
+    }
+
+    /// Drop an ordinal's cached content and previews, returning whether content was cached
+    pub async fn invalidate_content(&self, origin: &str) -> bool {
+        let key = format!("content:{}", origin);
+        let removed = self.content_cache.remove(&key).await.is_some();
+
+        // Previews are derived from the content, so drop every width
+        let preview_origin = origin.to_string();
+        let _ = self
+            .preview_cache
+            .invalidate_entries_if(move |(o, _), _| *o == preview_origin);
+
+        debug!("Invalidated content cache for {}: {}", origin, removed);
+        removed
+    }
+
     pub fn stats(&self) -> CacheStats {
         let hits = self.hits.load(Ordering::Relaxed);
         let misses = self.misses.load(Ordering::Relaxed);
@@ -125,14 +328,98 @@ impl CacheManager {
         CacheStats {
             ownership_entries: self.wallet_cache.entry_count(),
             content_entries: self.content_cache.entry_count(),
+            content_evictions: self.content_evictions.load(Ordering::Relaxed),
             hit_rate_percent: hit_rate,
+            cache_config: self.cache_config.clone(),
         }
     }
 
-    pub async fn clear_all(&self) {
+    /// Total cache (hits, misses) since startup
+    pub fn hit_counts(&self) -> (u64, u64) {
+        (self.hits.load(Ordering::Relaxed), self.misses.load(Ordering::Relaxed))
+    }
+
+    /// Empty every cache, returning how many entries each held beforehand
+    pub async fn clear_all(&self) -> CacheEntryCounts {
+        // Entry counts lag behind recent writes until pending maintenance runs
+        self.wallet_cache.run_pending_tasks().await;
+        self.ordinal_cache.run_pending_tasks().await;
+        self.content_cache.run_pending_tasks().await;
+        self.preview_cache.run_pending_tasks().await;
+        self.holders_cache.run_pending_tasks().await;
+        self.stats_cache.run_pending_tasks().await;
+        self.chain_tip_cache.run_pending_tasks().await;
+        self.exchange_rate_cache.run_pending_tasks().await;
+        if let Some(ref not_found) = self.not_found_cache {
+            not_found.run_pending_tasks().await;
+        }
+
+        let counts = CacheEntryCounts {
+            wallet: self.wallet_cache.entry_count(),
+            ordinal: self.ordinal_cache.entry_count(),
+            content: self.content_cache.entry_count(),
+            preview: self.preview_cache.entry_count(),
+            holders: self.holders_cache.entry_count(),
+            stats: self.stats_cache.entry_count(),
+            chain_tip: self.chain_tip_cache.entry_count(),
+            exchange_rate: self.exchange_rate_cache.entry_count(),
+            not_found: self.not_found_cache.as_ref().map_or(0, |c| c.entry_count()),
+        };
+
         self.wallet_cache.invalidate_all();
         self.ordinal_cache.invalidate_all();
         self.content_cache.invalidate_all();
+        self.preview_cache.invalidate_all();
+        self.holders_cache.invalidate_all();
+        self.stats_cache.invalidate_all();
+        self.chain_tip_cache.invalidate_all();
+        self.exchange_rate_cache.invalidate_all();
+        if let Some(ref not_found) = self.not_found_cache {
+            not_found.invalidate_all();
+        }
         info!("All caches cleared");
+        counts
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cache_with_content_capacity(entries: u64) -> CacheManager {
+        CacheManager::new(&Config {
+            max_cache_entries: entries * 10,
+            ..Default::default()
+        })
+    }
+
+    #[tokio::test]
+    async fn content_past_capacity_counts_evictions() {
+        let cache = cache_with_content_capacity(2);
+
+        for i in 0..10 {
+            cache.set_content(&format!("origin{}", i), b"data", "text/plain").await;
+        }
+        cache.content_cache.run_pending_tasks().await;
+
+        let stats = cache.stats();
+        assert!(stats.content_entries <= 2);
+        assert!(stats.content_evictions >= 8, "{} evictions", stats.content_evictions);
+    }
+
+    #[tokio::test]
+    async fn invalidate_content_removes_only_that_entry() {
+        let cache = cache_with_content_capacity(10);
+        cache.set_content("keep", b"a", "text/plain").await;
+        cache.set_content("drop", b"b", "text/plain").await;
+
+        assert!(cache.invalidate_content("drop").await);
+        assert!(!cache.invalidate_content("drop").await);
+        cache.content_cache.run_pending_tasks().await;
+
+        assert!(cache.get_content("drop").await.is_none());
+        assert_eq!(cache.get_content("keep").await, Some((b"a".to_vec(), "text/plain".to_string())));
+        // Explicit removal isn't an eviction
+        assert_eq!(cache.stats().content_evictions, 0);
     }
 }
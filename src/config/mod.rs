@@ -1,3 +1,5 @@
+use bitcoin::Network;
+use chrono::{DateTime, Utc};
 use std::time::Duration;
 
 /// Application configuration
@@ -10,6 +12,17 @@ pub struct Config {
     
     /// GorillaPool API base URL
     pub gorillapool_base_url: String,
+    /// Hosts tried in order when the primary GorillaPool host is down
+    pub gorillapool_fallback_urls: Vec<String>,
+    /// Total tries for a GorillaPool request that fails with 429, 5xx or a connection error
+    pub gorillapool_retry_attempts: u32,
+    /// Backoff before the first GorillaPool retry (doubles each retry, with jitter)
+    pub gorillapool_retry_base_delay: Duration,
+    /// Per-request timeout for GorillaPool API calls (content downloads use
+    /// `content_request_timeout`)
+    pub gorillapool_timeout: Duration,
+    /// mAPI endpoint signed transactions are broadcast to
+    pub mapi_url: String,
     /// WhatsOnChain API base URL  
     pub whatsonchain_base_url: String,
     
@@ -24,18 +37,114 @@ pub struct Config {
     pub content_cache_ttl: Duration,
     /// Cache TTL for inscription metadata
     pub metadata_cache_ttl: Duration,
+    /// Cached wallet data older than this is refetched regardless of TTL (no ceiling if `None`)
+    pub max_wallet_cache_age: Option<Duration>,
+    /// How long a lookup GorillaPool answered with "not found" is remembered (disabled if `None`)
+    pub negative_cache_ttl: Option<Duration>,
     /// Maximum cache entries
     pub max_cache_entries: u64,
     
+    /// Inbound requests per second allowed from one client IP (0 disables)
+    pub inbound_rate_limit_per_second: u32,
+    /// Content downloads per second allowed from one client IP, on top of the inbound limit (0 disables)
+    pub content_rate_limit_per_second: u32,
+    /// Reverse proxies in front of the server that append to `X-Forwarded-For`.
+    /// 0 ignores the header and rate-limits on the peer address.
+    pub trusted_proxy_hops: usize,
+
+    /// Inscription content larger than this is served but not cached
+    pub max_content_bytes: u64,
+    /// Content types eligible for the content cache, lowercased (all types if empty)
+    pub content_type_allowlist: Vec<String>,
+
+    /// How long a request may take before it's answered with 504
+    pub request_timeout: Duration,
+    /// Timeout for inscription content requests, inbound and to GorillaPool
+    pub content_request_timeout: Duration,
+
+    /// Origins allowed to make cross-origin requests; `*` allows any. When empty,
+    /// debug builds allow any origin and release builds none.
+    pub cors_allowed_origins: Vec<String>,
+    /// Methods allowed cross-origin (any if empty)
+    pub cors_allowed_methods: Vec<String>,
+    /// Request headers allowed cross-origin (any if empty)
+    pub cors_allowed_headers: Vec<String>,
+
     /// Concurrent API request limit
     pub max_concurrent_requests: usize,
+
+    /// UTXO count at which purchase errors suggest consolidating the buyer's wallet
+    pub consolidation_hint_min_utxos: usize,
+
+    /// Miner fee rate used when estimating purchase transaction fees
+    pub fee_rate_sat_per_byte: f64,
+    /// Smallest output worth creating or UTXO worth spending; change below it goes to the miner
+    pub dust_threshold_sats: u64,
+    /// Network that addresses and transactions must belong to (mainnet unless testing)
+    pub network: Network,
+    /// Prefix of the OP_RETURN attribution output added to purchases (none when unset)
+    pub op_return_prefix: Option<String>,
+
+    /// Marketplace fee charged on top of the seller's price, in percent
+    pub marketplace_fee_percent: f64,
+    /// Largest tip a seller may add, in percent
+    pub max_tip_percent: f64,
+    /// Most listings one `POST /listings/batch` call may create
+    pub max_batch_listings: usize,
+    /// BSV/USD exchange rate endpoint used to convert fiat-priced listings
+    pub price_oracle_url: String,
+
+    /// Reject request bodies containing unknown JSON fields instead of ignoring them
+    pub strict_json: bool,
+
+    /// POST a completion callback to the purchase's `return_url` after a successful broadcast
+    pub purchase_callbacks_enabled: bool,
+
+    /// Mask buyer addresses in the public recent sales feed
+    pub redact_sale_buyers: bool,
     
     /// Database path
     pub db_path: String,
 
-    /// BSV address that receives the 1% marketplace fee and optional tips
+    /// Upgrade legacy listing records to the current schema when they're read
+    pub migrate_legacy_listings: bool,
+
+    /// Active listings older than this are expired when read (unbounded if `None`)
+    pub max_listing_lifetime: Option<Duration>,
+
+    /// How often active listings are re-checked against the chain (disabled if `None`)
+    pub stale_listing_check_interval: Option<Duration>,
+
+    /// How often listings past their expiry are swept to expired (disabled if `None`;
+    /// reads still hide them)
+    pub listing_expiry_sweep_interval: Option<Duration>,
+
+    /// How often GorillaPool is pinged to enter or leave degraded mode
+    pub upstream_probe_interval: Duration,
+
+    /// How often ended auctions are closed
+    pub auction_close_check_interval: Duration,
+    /// How long an auction winner has to complete the purchase before the listing expires
+    pub auction_settlement_window: Duration,
+
+    /// BSV address that receives the marketplace fee and optional tips
     pub marketplace_fee_address: String,
 
+    /// Fee address rotations as `(effective from, address)`, sorted by time. Before
+    /// the first entry `marketplace_fee_address` applies.
+    pub fee_address_schedule: Vec<(DateTime<Utc>, String)>,
+
+    /// Ordinal-lock contract script before the listing payload (hex, optional)
+    pub ordlock_prefix_hex: Option<String>,
+    /// Ordinal-lock contract script after the listing payload (hex, optional)
+    pub ordlock_suffix_hex: Option<String>,
+
+    /// WIF key used to sign the fee address attestation (optional)
+    pub attestation_wif: Option<String>,
+
+    /// Bearer token required by mutating admin endpoints (they're disabled if unset)
+    pub admin_token: Option<String>,
+
     /// HandCash App ID (public)
     pub handcash_app_id: String,
     /// HandCash App Secret (server-only - keep secret!)
@@ -49,6 +158,11 @@ impl Default for Config {
             server_port: 3000,
             
             gorillapool_base_url: "https://ordinals.gorillapool.io/api".to_string(),
+            gorillapool_fallback_urls: Vec::new(),
+            gorillapool_retry_attempts: 3,
+            gorillapool_retry_base_delay: Duration::from_millis(200),
+            gorillapool_timeout: Duration::from_secs(30),
+            mapi_url: "https://mapi.gorillapool.io/mapi/tx".to_string(),
             whatsonchain_base_url: "https://plugins.whatsonchain.com/api/plugin/main".to_string(),
             
             // Conservative rate limiting to stay well under ceiling
@@ -59,20 +173,61 @@ impl Default for Config {
             ownership_cache_ttl: Duration::from_secs(30),
             content_cache_ttl: Duration::from_secs(86400),
             metadata_cache_ttl: Duration::from_secs(300),
+            max_wallet_cache_age: Some(Duration::from_secs(300)),
+            negative_cache_ttl: Some(Duration::from_secs(10)),
             max_cache_entries: 10_000,
             
+            inbound_rate_limit_per_second: 20,
+            content_rate_limit_per_second: 5,
+            trusted_proxy_hops: 0,
+            max_content_bytes: 10 * 1024 * 1024,
+            content_type_allowlist: Vec::new(),
+            request_timeout: Duration::from_secs(30),
+            content_request_timeout: Duration::from_secs(120),
+            cors_allowed_origins: Vec::new(),
+            cors_allowed_methods: Vec::new(),
+            cors_allowed_headers: Vec::new(),
             max_concurrent_requests: 5,
+
+            consolidation_hint_min_utxos: 10,
+
+            fee_rate_sat_per_byte: 1.0,
+            dust_threshold_sats: 546,
+            network: Network::Bitcoin,
+            marketplace_fee_percent: 1.0,
+            max_tip_percent: 10.0,
+            max_batch_listings: 50,
+            op_return_prefix: None,
+            price_oracle_url: "https://api.whatsonchain.com/v1/bsv/main/exchangerate".to_string(),
+
+            strict_json: false,
+            purchase_callbacks_enabled: false,
+            redact_sale_buyers: false,
             
             db_path: "marketplace_db".to_string(),
 
+            migrate_legacy_listings: true,
+            max_listing_lifetime: None,
+            stale_listing_check_interval: Some(Duration::from_secs(600)),
+            listing_expiry_sweep_interval: Some(Duration::from_secs(300)),
+            upstream_probe_interval: Duration::from_secs(15),
+            auction_close_check_interval: Duration::from_secs(30),
+            auction_settlement_window: Duration::from_secs(24 * 60 * 60),
+
             // Real marketplace fee address
             marketplace_fee_address: "15BvxtG9U61ndVZccSmuG9nQzygzjDqC41".to_string(),
 
+            fee_address_schedule: Vec::new(),
+            ordlock_prefix_hex: None,
+            ordlock_suffix_hex: None,
+            attestation_wif: None,
+            admin_token: None,
+
             // Real HandCash App ID (public - safe to hardcode)
             handcash_app_id: "68082b6ba117aae3817ec15f".to_string(),
 
             // Placeholder for secret - MUST be overridden in production via env var
-            handcash_app_secret: "PLACEHOLDER_SECRET_DO_NOT_USE_IN_PRODUCTION".to_string(),
+            handcash_app_secret: HANDCASH_SECRET_PLACEHOLDER.to_string(),
         }
     }
 }
@@ -88,16 +243,232 @@ impl Config {
             }
         }
         
+        if let Ok(url) = std::env::var("MAPI_URL") {
+            config.mapi_url = url;
+        }
+
+        // Comma-separated, tried in order after the primary host
+        if let Ok(urls) = std::env::var("GORILLAPOOL_FALLBACK_URLS") {
+            config.gorillapool_fallback_urls = urls
+                .split(',')
+                .map(|u| u.trim().trim_end_matches('/').to_string())
+                .filter(|u| !u.is_empty())
+                .collect();
+        }
+
+        if let Ok(attempts) = std::env::var("GORILLAPOOL_RETRY_ATTEMPTS") {
+            if let Ok(a) = attempts.parse() {
+                config.gorillapool_retry_attempts = a;
+            }
+        }
+
+        if let Ok(ms) = std::env::var("GORILLAPOOL_RETRY_BASE_DELAY_MS") {
+            if let Ok(m) = ms.parse() {
+                config.gorillapool_retry_base_delay = Duration::from_millis(m);
+            }
+        }
+
         if let Ok(path) = std::env::var("DB_PATH") {
             config.db_path = path;
         }
         
+        if let Ok(enabled) = std::env::var("MIGRATE_LEGACY_LISTINGS") {
+            config.migrate_legacy_listings = enabled == "true" || enabled == "1";
+        }
+
+        // 0 leaves listing lifetime unbounded
+        if let Ok(secs) = std::env::var("MAX_LISTING_LIFETIME_SECS") {
+            if let Ok(s) = secs.parse::<u64>() {
+                config.max_listing_lifetime = (s > 0).then(|| Duration::from_secs(s));
+            }
+        }
+
+        // 0 disables the background stale listing check
+        if let Ok(secs) = std::env::var("STALE_LISTING_CHECK_SECS") {
+            if let Ok(s) = secs.parse::<u64>() {
+                config.stale_listing_check_interval = (s > 0).then(|| Duration::from_secs(s));
+            }
+        }
+
+        if let Ok(secs) = std::env::var("LISTING_EXPIRY_SWEEP_SECS") {
+            if let Ok(s) = secs.parse::<u64>() {
+                config.listing_expiry_sweep_interval = (s > 0).then(|| Duration::from_secs(s));
+            }
+        }
+
+        if let Ok(secs) = std::env::var("UPSTREAM_PROBE_INTERVAL_SECS") {
+            match secs.parse() {
+                Ok(s) if s > 0 => config.upstream_probe_interval = Duration::from_secs(s),
+                _ => tracing::warn!("Ignoring invalid UPSTREAM_PROBE_INTERVAL_SECS: {}", secs),
+            }
+        }
+
+        if let Ok(secs) = std::env::var("AUCTION_CLOSE_CHECK_SECS") {
+            match secs.parse() {
+                Ok(s) if s > 0 => config.auction_close_check_interval = Duration::from_secs(s),
+                _ => tracing::warn!("Ignoring invalid AUCTION_CLOSE_CHECK_SECS: {}", secs),
+            }
+        }
+
+        if let Ok(secs) = std::env::var("AUCTION_SETTLEMENT_SECS") {
+            match secs.parse() {
+                Ok(s) if s > 0 => config.auction_settlement_window = Duration::from_secs(s),
+                _ => tracing::warn!("Ignoring invalid AUCTION_SETTLEMENT_SECS: {}", secs),
+            }
+        }
+
         if let Ok(rate) = std::env::var("API_RATE_LIMIT") {
             if let Ok(r) = rate.parse() {
                 config.api_rate_limit_per_second = r;
             }
         }
 
+        // 0 disables negative caching
+        if let Ok(secs) = std::env::var("NEGATIVE_CACHE_TTL_SECS") {
+            if let Ok(s) = secs.parse::<u64>() {
+                config.negative_cache_ttl = (s > 0).then(|| Duration::from_secs(s));
+            }
+        }
+
+        // 0 removes the ceiling and leaves expiry to the cache TTL
+        if let Ok(secs) = std::env::var("MAX_WALLET_CACHE_AGE_SECS") {
+            if let Ok(s) = secs.parse::<u64>() {
+                config.max_wallet_cache_age = (s > 0).then(|| Duration::from_secs(s));
+            }
+        }
+
+        if let Ok(bytes) = std::env::var("MAX_CONTENT_BYTES") {
+            if let Ok(b) = bytes.parse() {
+                config.max_content_bytes = b;
+            }
+        }
+
+        // Comma-separated, e.g. `image/*,text/plain`
+        if let Ok(types) = std::env::var("CONTENT_TYPE_ALLOWLIST") {
+            config.content_type_allowlist = types
+                .split(',')
+                .map(|t| t.trim().to_ascii_lowercase())
+                .filter(|t| !t.is_empty())
+                .collect();
+        }
+
+        if let Ok(secs) = std::env::var("GORILLAPOOL_TIMEOUT_SECS") {
+            match secs.parse() {
+                Ok(s) if s > 0 => config.gorillapool_timeout = Duration::from_secs(s),
+                _ => tracing::warn!("Ignoring invalid GORILLAPOOL_TIMEOUT_SECS: {}", secs),
+            }
+        }
+
+        if let Ok(secs) = std::env::var("REQUEST_TIMEOUT_SECS") {
+            match secs.parse() {
+                Ok(s) if s > 0 => config.request_timeout = Duration::from_secs(s),
+                _ => tracing::warn!("Ignoring invalid REQUEST_TIMEOUT_SECS: {}", secs),
+            }
+        }
+
+        if let Ok(secs) = std::env::var("CONTENT_REQUEST_TIMEOUT_SECS") {
+            match secs.parse() {
+                Ok(s) if s > 0 => config.content_request_timeout = Duration::from_secs(s),
+                _ => tracing::warn!("Ignoring invalid CONTENT_REQUEST_TIMEOUT_SECS: {}", secs),
+            }
+        }
+
+        if let Ok(origins) = std::env::var("CORS_ALLOWED_ORIGINS") {
+            config.cors_allowed_origins = comma_list(&origins);
+        }
+
+        if let Ok(methods) = std::env::var("CORS_ALLOWED_METHODS") {
+            config.cors_allowed_methods = comma_list(&methods.to_ascii_uppercase());
+        }
+
+        if let Ok(headers) = std::env::var("CORS_ALLOWED_HEADERS") {
+            config.cors_allowed_headers = comma_list(&headers.to_ascii_lowercase());
+        }
+
+        if let Ok(rate) = std::env::var("INBOUND_RATE_LIMIT") {
+            if let Ok(r) = rate.parse() {
+                config.inbound_rate_limit_per_second = r;
+            }
+        }
+
+        if let Ok(rate) = std::env::var("CONTENT_RATE_LIMIT") {
+            if let Ok(r) = rate.parse() {
+                config.content_rate_limit_per_second = r;
+            }
+        }
+
+        if let Ok(hops) = std::env::var("TRUSTED_PROXY_HOPS") {
+            match hops.parse() {
+                Ok(h) => config.trusted_proxy_hops = h,
+                Err(_) => tracing::warn!("Ignoring invalid TRUSTED_PROXY_HOPS: {}", hops),
+            }
+        }
+
+        if let Ok(min) = std::env::var("CONSOLIDATION_HINT_MIN_UTXOS") {
+            if let Ok(m) = min.parse() {
+                config.consolidation_hint_min_utxos = m;
+            }
+        }
+
+        if let Ok(rate) = std::env::var("FEE_RATE_SAT_PER_BYTE") {
+            if let Ok(r) = rate.parse() {
+                config.fee_rate_sat_per_byte = r;
+            }
+        }
+
+        if let Ok(dust) = std::env::var("DUST_THRESHOLD_SATS") {
+            match dust.parse() {
+                Ok(d) if d > 0 => config.dust_threshold_sats = d,
+                _ => tracing::warn!("Ignoring invalid DUST_THRESHOLD_SATS: {}", dust),
+            }
+        }
+
+        if let Ok(network) = std::env::var("BSV_NETWORK") {
+            match network.to_lowercase().as_str() {
+                "main" | "mainnet" => config.network = Network::Bitcoin,
+                "test" | "testnet" => config.network = Network::Testnet,
+                "regtest" => config.network = Network::Regtest,
+                _ => tracing::warn!("Ignoring invalid BSV_NETWORK: {}", network),
+            }
+        }
+
+        if let Ok(percent) = std::env::var("MARKETPLACE_FEE_PERCENT") {
+            match percent.parse::<f64>() {
+                Ok(p) if (0.0..=100.0).contains(&p) => config.marketplace_fee_percent = p,
+                _ => tracing::warn!("Ignoring invalid MARKETPLACE_FEE_PERCENT: {}", percent),
+            }
+        }
+
+        if let Ok(percent) = std::env::var("MAX_TIP_PERCENT") {
+            match percent.parse::<f64>() {
+                Ok(p) if (0.0..=100.0).contains(&p) => config.max_tip_percent = p,
+                _ => tracing::warn!("Ignoring invalid MAX_TIP_PERCENT: {}", percent),
+            }
+        }
+
+        if let Ok(max) = std::env::var("MAX_BATCH_LISTINGS") {
+            match max.parse() {
+                Ok(m) if m > 0 => config.max_batch_listings = m,
+                _ => tracing::warn!("Ignoring invalid MAX_BATCH_LISTINGS: {}", max),
+            }
+        }
+
+        if let Ok(url) = std::env::var("PRICE_ORACLE_URL") {
+            config.price_oracle_url = url;
+        }
+
+        if let Ok(enabled) = std::env::var("PURCHASE_CALLBACKS_ENABLED") {
+            config.purchase_callbacks_enabled = enabled == "true" || enabled == "1";
+        }
+
+        if let Ok(enabled) = std::env::var("REDACT_SALE_BUYERS") {
+            config.redact_sale_buyers = enabled == "true" || enabled == "1";
+        }
+
+        if let Ok(strict) = std::env::var("STRICT_JSON") {
+            config.strict_json = strict == "true" || strict == "1";
+        }
+
         // Load marketplace fee address - REQUIRED in production
         if let Ok(addr) = std::env::var("MARKETPLACE_FEE_ADDRESS") {
             config.marketplace_fee_address = addr;
@@ -107,6 +478,21 @@ impl Config {
             panic!("MARKETPLACE_FEE_ADDRESS environment variable is required");
         }
 
+        // Comma-separated `RFC3339=address` rotations, e.g. `2026-01-01T00:00:00Z=1Abc...`
+        if let Ok(schedule) = std::env::var("FEE_ADDRESS_SCHEDULE") {
+            config.fee_address_schedule = parse_fee_address_schedule(&schedule)
+                .unwrap_or_else(|e| panic!("Invalid FEE_ADDRESS_SCHEDULE: {}", e));
+            if let Err(e) = config.validate_fee_address_schedule() {
+                panic!("Invalid FEE_ADDRESS_SCHEDULE: {}", e);
+            }
+        }
+
+        config.ordlock_prefix_hex = std::env::var("ORDLOCK_PREFIX_HEX").ok();
+        config.ordlock_suffix_hex = std::env::var("ORDLOCK_SUFFIX_HEX").ok();
+        config.attestation_wif = std::env::var("ATTESTATION_WIF").ok();
+        config.op_return_prefix = std::env::var("OP_RETURN_PREFIX").ok().filter(|p| !p.is_empty());
+        config.admin_token = std::env::var("ADMIN_TOKEN").ok().filter(|t| !t.is_empty());
+
         // HandCash App ID - public, can fall back to default
         config.handcash_app_id = std::env::var("HANDCASH_APP_ID")
             .unwrap_or_else(|_| config.handcash_app_id.clone());
@@ -124,4 +510,177 @@ impl Config {
 
         config
     }
-}
\ No newline at end of file
+}
+
+impl Config {
+    /// Whether a real HandCash app secret has been configured
+    pub fn handcash_configured(&self) -> bool {
+        !self.handcash_app_secret.is_empty() && self.handcash_app_secret != HANDCASH_SECRET_PLACEHOLDER
+    }
+
+    /// Fee address in effect at `at`: the latest scheduled rotation at or before
+    /// it, or `marketplace_fee_address` if none has started
+    pub fn fee_address_at(&self, at: DateTime<Utc>) -> &str {
+        self.fee_address_schedule
+            .iter()
+            .rev()
+            .find(|(from, _)| *from <= at)
+            .map(|(_, address)| address.as_str())
+            .unwrap_or(&self.marketplace_fee_address)
+    }
+
+    /// Check the fee address schedule is strictly increasing in time and every
+    /// address is a valid P2PKH address on the configured network
+    pub fn validate_fee_address_schedule(&self) -> Result<(), String> {
+        for pair in self.fee_address_schedule.windows(2) {
+            if pair[1].0 <= pair[0].0 {
+                return Err(format!("entries out of order at {}", pair[1].0.to_rfc3339()));
+            }
+        }
+        for (from, address) in &self.fee_address_schedule {
+            crate::validation::validate_bsv_address(address, self.network)
+                .map_err(|e| format!("address for {} is invalid: {}", from.to_rfc3339(), e))?;
+        }
+        Ok(())
+    }
+
+    /// Decoded ordinal-lock contract `(prefix, suffix)`, if both are configured and valid hex
+    pub fn ordlock_template(&self) -> Option<(Vec<u8>, Vec<u8>)> {
+        let prefix = hex::decode(self.ordlock_prefix_hex.as_ref()?).ok()?;
+        let suffix = hex::decode(self.ordlock_suffix_hex.as_ref()?).ok()?;
+        Some((prefix, suffix))
+    }
+
+    /// Config values in effect, with secrets redacted, for the startup report
+    pub fn redacted_summary(&self) -> serde_json::Value {
+        serde_json::json!({
+            "server_addr": self.server_addr,
+            "server_port": self.server_port,
+            "gorillapool_base_url": self.gorillapool_base_url,
+            "gorillapool_fallback_urls": self.gorillapool_fallback_urls,
+            "gorillapool_retry_attempts": self.gorillapool_retry_attempts,
+            "gorillapool_retry_base_delay_ms": self.gorillapool_retry_base_delay.as_millis() as u64,
+            "gorillapool_timeout_secs": self.gorillapool_timeout.as_secs(),
+            "mapi_url": self.mapi_url,
+            "whatsonchain_base_url": self.whatsonchain_base_url,
+            "api_rate_limit_per_second": self.api_rate_limit_per_second,
+            "api_rate_limit_burst": self.api_rate_limit_burst,
+            "ownership_cache_ttl_secs": self.ownership_cache_ttl.as_secs(),
+            "content_cache_ttl_secs": self.content_cache_ttl.as_secs(),
+            "metadata_cache_ttl_secs": self.metadata_cache_ttl.as_secs(),
+            "max_wallet_cache_age_secs": self.max_wallet_cache_age.map(|d| d.as_secs()),
+            "negative_cache_ttl_secs": self.negative_cache_ttl.map(|d| d.as_secs()),
+            "max_cache_entries": self.max_cache_entries,
+            "inbound_rate_limit_per_second": self.inbound_rate_limit_per_second,
+            "content_rate_limit_per_second": self.content_rate_limit_per_second,
+            "trusted_proxy_hops": self.trusted_proxy_hops,
+            "max_content_bytes": self.max_content_bytes,
+            "content_type_allowlist": self.content_type_allowlist,
+            "request_timeout_secs": self.request_timeout.as_secs(),
+            "content_request_timeout_secs": self.content_request_timeout.as_secs(),
+            "cors_allowed_origins": self.cors_allowed_origins,
+            "cors_allowed_methods": self.cors_allowed_methods,
+            "cors_allowed_headers": self.cors_allowed_headers,
+            "max_concurrent_requests": self.max_concurrent_requests,
+            "consolidation_hint_min_utxos": self.consolidation_hint_min_utxos,
+            "fee_rate_sat_per_byte": self.fee_rate_sat_per_byte,
+            "dust_threshold_sats": self.dust_threshold_sats,
+            "network": self.network.to_string(),
+            "op_return_prefix": self.op_return_prefix,
+            "marketplace_fee_percent": self.marketplace_fee_percent,
+            "max_tip_percent": self.max_tip_percent,
+            "max_batch_listings": self.max_batch_listings,
+            "price_oracle_url": self.price_oracle_url,
+            "strict_json": self.strict_json,
+            "purchase_callbacks_enabled": self.purchase_callbacks_enabled,
+            "redact_sale_buyers": self.redact_sale_buyers,
+            "db_path": self.db_path,
+            "migrate_legacy_listings": self.migrate_legacy_listings,
+            "max_listing_lifetime_secs": self.max_listing_lifetime.map(|d| d.as_secs()),
+            "stale_listing_check_secs": self.stale_listing_check_interval.map(|d| d.as_secs()),
+            "listing_expiry_sweep_secs": self.listing_expiry_sweep_interval.map(|d| d.as_secs()),
+            "upstream_probe_interval_secs": self.upstream_probe_interval.as_secs(),
+            "auction_close_check_secs": self.auction_close_check_interval.as_secs(),
+            "auction_settlement_secs": self.auction_settlement_window.as_secs(),
+            "marketplace_fee_address": self.marketplace_fee_address,
+            "fee_address_schedule": self.fee_address_schedule
+                .iter()
+                .map(|(from, address)| serde_json::json!({ "from": from, "address": address }))
+                .collect::<Vec<_>>(),
+            "ordlock_configured": self.ordlock_template().is_some(),
+            "attestation_wif": self.attestation_wif.as_ref().map(|_| REDACTED),
+            "admin_token": self.admin_token.as_ref().map(|_| REDACTED),
+            "handcash_app_id": self.handcash_app_id,
+            "handcash_app_secret": REDACTED,
+        })
+    }
+}
+
+/// Parse `RFC3339=address` entries separated by commas
+fn parse_fee_address_schedule(raw: &str) -> Result<Vec<(DateTime<Utc>, String)>, String> {
+    raw.split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let (from, address) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("expected RFC3339=address, got {:?}", entry))?;
+            let from = DateTime::parse_from_rfc3339(from.trim())
+                .map_err(|e| format!("bad timestamp {:?}: {}", from, e))?
+                .with_timezone(&Utc);
+            Ok((from, address.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Split a comma-separated setting into trimmed, non-empty entries
+fn comma_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Default HandCash secret; HandCash endpoints refuse to run while it's in use
+pub const HANDCASH_SECRET_PLACEHOLDER: &str = "PLACEHOLDER_SECRET_DO_NOT_USE_IN_PRODUCTION";
+
+/// Placeholder shown in place of secret config values
+pub const REDACTED: &str = "[REDACTED]";
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::address;
+
+    fn at(rfc3339: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(rfc3339).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn fee_address_rotates_at_each_scheduled_boundary() {
+        let schedule = format!("2026-01-01T00:00:00Z={},2026-07-01T00:00:00Z={}", address(1), address(2));
+        let config = Config {
+            fee_address_schedule: parse_fee_address_schedule(&schedule).unwrap(),
+            ..Default::default()
+        };
+        assert!(config.validate_fee_address_schedule().is_ok());
+
+        let original = config.marketplace_fee_address.as_str();
+        assert_eq!(config.fee_address_at(at("2025-12-31T23:59:59Z")), original);
+        assert_eq!(config.fee_address_at(at("2026-01-01T00:00:00Z")), address(1));
+        assert_eq!(config.fee_address_at(at("2026-06-30T23:59:59Z")), address(1));
+        assert_eq!(config.fee_address_at(at("2026-07-01T00:00:00Z")), address(2));
+        assert_eq!(config.fee_address_at(at("2030-01-01T00:00:00Z")), address(2));
+    }
+
+    #[test]
+    fn fee_address_schedule_must_be_in_order() {
+        let schedule = format!("2026-07-01T00:00:00Z={},2026-01-01T00:00:00Z={}", address(1), address(2));
+        let config = Config {
+            fee_address_schedule: parse_fee_address_schedule(&schedule).unwrap(),
+            ..Default::default()
+        };
+        assert!(config.validate_fee_address_schedule().is_err());
+    }
+}
@@ -1,24 +1,46 @@
+// serde_json::json! in Config::redacted_summary outgrows the default limit
+#![recursion_limit = "256"]
+
 mod api;
 mod cache;
 mod config;
+mod metrics;
 mod models;
 mod services;
+mod validation;
+#[cfg(test)]
+mod test_support;
 
 use api::create_router;
 use api::handlers::AppState;  // ← Import the correct AppState from handlers.rs
 use cache::CacheManager;
 use config::Config;
-use services::{GorillaPoolClient, OrdinalService, ListingsDb};
+use metrics::Metrics;
+use services::{GorillaPoolClient, OrdinalService, ListingsDb, PriceOracle, PurchaseCallbacks, UpstreamHealth};
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Instant;
-use tracing::{info, Level};
-use tracing_subscriber::FmtSubscriber;
+use tokio::sync::{broadcast, watch};
+use tracing::{info, warn};
+use tracing_subscriber::{EnvFilter, FmtSubscriber};
+
+/// Listing events buffered per SSE subscriber before slow clients start skipping
+const LISTING_EVENTS_CAPACITY: usize = 256;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize logging
-    let _subscriber = FmtSubscriber::builder()
-        .with_max_level(Level::INFO)
+    // Initialize logging: LOG_LEVEL, else RUST_LOG, else info. Either takes a level
+    // or full directives, e.g. `info,bsv_ordinals_marketplace::services=debug`.
+    let log_directives = std::env::var("LOG_LEVEL")
+        .or_else(|_| std::env::var("RUST_LOG"))
+        .ok();
+    let (filter, invalid_directives) = match log_directives.as_deref().map(EnvFilter::try_new) {
+        Some(Ok(filter)) => (filter, None),
+        Some(Err(e)) => (EnvFilter::new("info"), Some(e)),
+        None => (EnvFilter::new("info"), None),
+    };
+    FmtSubscriber::builder()
+        .with_env_filter(filter)
         .with_target(true)
         .with_thread_ids(false)
         .with_file(false)
@@ -26,6 +48,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .init();
 
     info!("🚀 BSV 1Sat Ordinals Marketplace starting...");
+    if let Some(e) = invalid_directives {
+        warn!("Ignoring invalid log filter {:?}, logging at info: {}", log_directives.unwrap_or_default(), e);
+    }
 
     // Load configuration
     let config = Config::from_env();
@@ -37,8 +62,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("Database opened at: {}", config.db_path);
 
     // Initialize services
+    let metrics = Arc::new(Metrics::new());
+
     let gorillapool = GorillaPoolClient::new(&config)
-        .expect("Failed to create GorillaPool client");
+        .expect("Failed to create GorillaPool client")
+        .with_metrics(Arc::clone(&metrics));
     
     let cache = Arc::new(CacheManager::new(&config));
     
@@ -48,10 +76,53 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         config.clone(),
     );
 
-    let listings_db = ListingsDb::new(Arc::clone(&db));
+    let price_oracle = PriceOracle::new(&config).expect("Failed to create price oracle client");
+    let purchase_callbacks = PurchaseCallbacks::new().expect("Failed to create purchase callback client");
+
+    let (listing_events, _) = broadcast::channel(LISTING_EVENTS_CAPACITY);
+    let listings_db = ListingsDb::new(Arc::clone(&db))
+        .with_max_listing_lifetime(config.max_listing_lifetime)
+        .with_migrate_on_read(config.migrate_legacy_listings)
+        .with_marketplace_fee_percent(config.marketplace_fee_percent)
+        .with_event_sender(listing_events.clone());
+    listings_db.ensure_active_index().expect("Failed to build active listing index");
     let active_listings = listings_db.count_active_listings();
     info!("Listings database loaded: {} active listings", active_listings);
 
+    let startup_report = services::startup::build_startup_report(
+        &config,
+        &listings_db,
+        ordinal_service.gorillapool(),
+    ).await;
+    services::startup::log_startup_report(&startup_report);
+
+    if let Some(interval) = config.stale_listing_check_interval {
+        services::listing_monitor::spawn_stale_listing_checker(
+            listings_db.clone(),
+            ordinal_service.gorillapool().clone(),
+            interval,
+        );
+    }
+
+    if let Some(interval) = config.listing_expiry_sweep_interval {
+        services::listing_monitor::spawn_expiry_sweeper(listings_db.clone(), interval);
+    }
+
+    let upstream_health = UpstreamHealth::new(startup_report.gorillapool_reachable);
+    services::spawn_upstream_probe(
+        ordinal_service.gorillapool().clone(),
+        upstream_health.clone(),
+        config.upstream_probe_interval,
+    );
+
+    services::listing_monitor::spawn_auction_closer(
+        listings_db.clone(),
+        config.auction_close_check_interval,
+        config.auction_settlement_window,
+    );
+
+    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+
     // Create application state — using the AppState from handlers.rs
     let state = AppState {
         ordinal_service,
@@ -59,6 +130,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         listings_db,
         start_time: Instant::now(),
         config: config.clone(),
+        startup_report: Arc::new(startup_report),
+        listing_events,
+        shutdown: shutdown_rx,
+        metrics,
+        price_oracle,
+        upstream_health,
+        purchase_callbacks,
     };
 
     // Build router
@@ -72,18 +150,85 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     info!("📖 API Endpoints:");
     info!("   GET  /                        → API info");
     info!("   GET  /health                  → Health check");
+    info!("   GET  /health/ready            → Readiness check (GorillaPool + database)");
+    info!("   GET  /stats                   → Marketplace statistics");
+    info!("   GET  /metrics                 → Prometheus metrics");
+    info!("   GET  /fee-address             → Signed marketplace fee address");
+    info!("   GET  /events                  → Listing events (SSE)");
+    info!("   GET  /admin/startup-report    → Startup validation report");
+    info!("   POST /admin/cache/clear       → Empty every cache");
+    info!("   POST /admin/cache/invalidate/wallet/:address → Drop a wallet's cached ordinals");
+    info!("   POST /admin/cache/invalidate/ordinal/:origin → Drop an ordinal's cached details and content");
+    info!("   POST /admin/prune             → Delete old closed listings");
     info!("   GET  /wallet/:address         → Get wallet ordinals");
+    info!("   GET  /wallet/:address/portfolio → Holdings, listings and sales");
+    info!("   GET  /wallet/:address/history → Listings and purchases over time");
+    info!("   GET  /watchlist/:address      → Watched listings with current state");
+    info!("   POST /watchlist/:address/:id  → Watch a listing (DELETE to stop)");
     info!("   GET  /ordinal/:origin         → Get ordinal details");
     info!("   GET  /ordinal/:origin/content → Get content");
+    info!("   GET  /ordinal/:origin/preview → Image preview (JPEG, ?w=256)");
+    info!("   GET  /collections             → Collections with active listings");
+    info!("   GET  /collections/:id/holders → Collection holder counts");
     info!("   GET  /listings                → Get active listings");
     info!("   POST /listings                → Create listing");
+    info!("   POST /listings/batch          → Create several listings");
     info!("   POST /listings/:id/cancel     → Cancel listing");
+    info!("   POST /listings/:id/reprice    → Change a listing's price");
+    info!("   POST /listings/:id/offers     → Make an offer (GET lists offers)");
+    info!("   POST /offers/:id/accept       → Accept an offer (or /reject)");
+    info!("   POST /listings/:id/bids       → Bid on an auction (GET lists bids)");
+    info!("   POST /listings/:id/prepare-lock-tx → Prepare unsigned ordinal-lock TX for the seller");
+    info!("   POST /listings/:id/broadcast-lock → Broadcast signed lock TX");
+    info!("   GET  /listings/:id/tx-template → Purchase TX template (no buyer funds)");
     info!("   POST /listings/:id/prepare-purchase → Prepare unsigned TX for Yours Wallet purchase");
+    info!("   POST /listings/:id/estimate → Size, miner fee and change of a prospective purchase");
+    info!("   POST /listings/:id/broadcast-purchase → Broadcast signed Yours Wallet purchase TX");
     info!("   POST /listings/:id/purchase   → Purchase listing");
+    info!("   POST /listings/:id/purchase-handcash → HandCash purchase");
+    info!("   POST /purchases/prepare-batch → Prepare one TX for a cart of listings");
+    info!("   GET  /sales/recent            → Recent sales feed");
     info!("   GET  /fees/calculate          → Calculate fees");
     info!("");
 
-    axum::serve(listener, app).await?;
+    // In-flight requests (e.g. purchase broadcasts) finish before serve returns
+    // Peer addresses are needed for per-IP rate limiting
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            shutdown_signal().await;
+            // Ends open /events streams, which would otherwise hold shutdown open
+            let _ = shutdown_tx.send(true);
+        })
+        .await?;
+
+    info!("Flushing database...");
+    db.flush_async().await?;
+    info!("👋 Shut down cleanly");
 
     Ok(())
+}
+
+/// Resolve on SIGINT (Ctrl+C) or SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        tokio::signal::ctrl_c()
+            .await
+            .expect("Failed to install Ctrl+C handler");
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate())
+            .expect("Failed to install SIGTERM handler")
+            .recv()
+            .await;
+    };
+
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => info!("Received SIGINT, shutting down..."),
+        _ = terminate => info!("Received SIGTERM, shutting down..."),
+    }
 }
\ No newline at end of file
@@ -0,0 +1,133 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Upper bounds (seconds) of the GorillaPool latency histogram buckets
+const LATENCY_BUCKETS: [f64; 8] = [0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0];
+
+/// Latency histogram for one GorillaPool endpoint
+#[derive(Default)]
+struct LatencyHistogram {
+    /// Non-cumulative counts per bucket; the last slot is `+Inf`
+    buckets: [u64; LATENCY_BUCKETS.len() + 1],
+    sum_secs: f64,
+    count: u64,
+}
+
+impl LatencyHistogram {
+    fn observe(&mut self, elapsed: Duration) {
+        let secs = elapsed.as_secs_f64();
+        let slot = LATENCY_BUCKETS
+            .iter()
+            .position(|bound| secs <= *bound)
+            .unwrap_or(LATENCY_BUCKETS.len());
+        self.buckets[slot] += 1;
+        self.sum_secs += secs;
+        self.count += 1;
+    }
+}
+
+/// In-process counters exported at `GET /metrics` in the Prometheus text format
+#[derive(Default)]
+pub struct Metrics {
+    /// (method, route, status) -> requests served
+    http_requests: Mutex<BTreeMap<(String, String, u16), u64>>,
+    /// (endpoint, status) -> GorillaPool calls; status is "error" if no response came back
+    gorillapool_calls: Mutex<BTreeMap<(&'static str, String), u64>>,
+    gorillapool_latency: Mutex<BTreeMap<&'static str, LatencyHistogram>>,
+}
+
+/// Point-in-time values owned elsewhere, sampled when metrics are scraped
+pub struct MetricsSnapshot {
+    pub cache_hits: u64,
+    pub cache_misses: u64,
+    pub active_listings: usize,
+}
+
+impl Metrics {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a served HTTP request against its matched route
+    pub fn record_request(&self, method: &str, route: &str, status: u16) {
+        let mut requests = self.http_requests.lock().unwrap();
+        *requests.entry((method.to_string(), route.to_string(), status)).or_default() += 1;
+    }
+
+    /// Count a GorillaPool call and its latency; `status` is `None` if the request failed
+    pub fn record_gorillapool_call(&self, endpoint: &'static str, status: Option<u16>, elapsed: Duration) {
+        let status = status.map_or_else(|| "error".to_string(), |s| s.to_string());
+        *self.gorillapool_calls.lock().unwrap().entry((endpoint, status)).or_default() += 1;
+        self.gorillapool_latency.lock().unwrap().entry(endpoint).or_default().observe(elapsed);
+    }
+
+    /// Render all metrics in the Prometheus text exposition format
+    pub fn render(&self, snapshot: &MetricsSnapshot) -> String {
+        let mut out = String::new();
+
+        out.push_str("# HELP marketplace_http_requests_total HTTP requests served, by route and status\n");
+        out.push_str("# TYPE marketplace_http_requests_total counter\n");
+        for ((method, route, status), count) in self.http_requests.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "marketplace_http_requests_total{{method=\"{}\",route=\"{}\",status=\"{}\"}} {}",
+                method, route, status, count
+            );
+        }
+
+        out.push_str("# HELP marketplace_gorillapool_requests_total GorillaPool API calls, by endpoint and status\n");
+        out.push_str("# TYPE marketplace_gorillapool_requests_total counter\n");
+        for ((endpoint, status), count) in self.gorillapool_calls.lock().unwrap().iter() {
+            let _ = writeln!(
+                out,
+                "marketplace_gorillapool_requests_total{{endpoint=\"{}\",status=\"{}\"}} {}",
+                endpoint, status, count
+            );
+        }
+
+        out.push_str("# HELP marketplace_gorillapool_request_duration_seconds GorillaPool API call latency\n");
+        out.push_str("# TYPE marketplace_gorillapool_request_duration_seconds histogram\n");
+        for (endpoint, histogram) in self.gorillapool_latency.lock().unwrap().iter() {
+            let mut cumulative = 0;
+            for (bound, count) in LATENCY_BUCKETS.iter().zip(histogram.buckets.iter()) {
+                cumulative += count;
+                let _ = writeln!(
+                    out,
+                    "marketplace_gorillapool_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"{}\"}} {}",
+                    endpoint, bound, cumulative
+                );
+            }
+            let _ = writeln!(
+                out,
+                "marketplace_gorillapool_request_duration_seconds_bucket{{endpoint=\"{}\",le=\"+Inf\"}} {}",
+                endpoint, histogram.count
+            );
+            let _ = writeln!(
+                out,
+                "marketplace_gorillapool_request_duration_seconds_sum{{endpoint=\"{}\"}} {}",
+                endpoint, histogram.sum_secs
+            );
+            let _ = writeln!(
+                out,
+                "marketplace_gorillapool_request_duration_seconds_count{{endpoint=\"{}\"}} {}",
+                endpoint, histogram.count
+            );
+        }
+
+        out.push_str("# HELP marketplace_cache_hits_total Cache lookups served from cache\n");
+        out.push_str("# TYPE marketplace_cache_hits_total counter\n");
+        let _ = writeln!(out, "marketplace_cache_hits_total {}", snapshot.cache_hits);
+
+        out.push_str("# HELP marketplace_cache_misses_total Cache lookups that missed\n");
+        out.push_str("# TYPE marketplace_cache_misses_total counter\n");
+        let _ = writeln!(out, "marketplace_cache_misses_total {}", snapshot.cache_misses);
+
+        out.push_str("# HELP marketplace_active_listings Listings currently active\n");
+        out.push_str("# TYPE marketplace_active_listings gauge\n");
+        let _ = writeln!(out, "marketplace_active_listings {}", snapshot.active_listings);
+
+        out
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use chrono::{DateTime, Utc};
+use std::collections::BTreeMap;
 
 /// UTXO with ordinal data from GorillaPool API
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +55,9 @@ pub struct InscriptionFile {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct OrdinalDetails {
     pub origin: String,
+    /// Origin split into its components so clients needn't parse `origin`
+    pub origin_txid: String,
+    pub origin_vout: u32,
     pub txid: String,
     pub vout: u32,
     pub owner_address: String,
@@ -87,6 +91,36 @@ pub struct WalletOrdinals {
     pub fetch_time_ms: u64,
 }
 
+/// Number of ordinals a single address holds within a collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolderCount {
+    pub address: String,
+    pub count: usize,
+}
+
+/// Ownership distribution of a collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectionHolders {
+    pub collection_id: String,
+    pub distinct_holders: usize,
+    pub total_ordinals: usize,
+    /// Holders sorted by count descending (trimmed to the requested `top`)
+    pub top_holders: Vec<HolderCount>,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// A collection represented among active listings
+#[derive(Debug, Clone, Serialize)]
+pub struct CollectionSummary {
+    pub collection_id: String,
+    /// Active listings in the collection
+    pub listing_count: usize,
+    /// Origin of one listed ordinal in the collection
+    pub sample_origin: String,
+    /// Preview image path for `sample_origin`
+    pub sample_preview_url: String,
+}
+
 /// API error response
 #[derive(Debug, Serialize)]
 pub struct ApiError {
@@ -121,12 +155,83 @@ pub struct HealthCheck {
     pub listings_count: usize,
 }
 
+/// Readiness check response: whether each dependency answered a cheap probe
+#[derive(Debug, Serialize)]
+pub struct ReadinessCheck {
+    /// "ready", or "not_ready" if any dependency failed
+    pub status: String,
+    pub gorillapool: DependencyStatus,
+    pub database: DependencyStatus,
+}
+
+/// Outcome of probing one dependency
+#[derive(Debug, Serialize)]
+pub struct DependencyStatus {
+    pub ok: bool,
+    pub latency_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of checking the listing indexes against the main listing records
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct IndexConsistency {
+    pub consistent: bool,
+    pub origin_index_entries: usize,
+    pub seller_index_entries: usize,
+    /// Human-readable description of each problem found
+    pub problems: Vec<String>,
+}
+
+/// Summary of a deployment's state, built once at startup
+#[derive(Debug, Clone, Serialize)]
+pub struct StartupReport {
+    pub version: String,
+    pub generated_at: DateTime<Utc>,
+    /// Config values in effect (secrets redacted)
+    pub config: serde_json::Value,
+    pub active_listings: usize,
+    pub index_consistency: IndexConsistency,
+    pub gorillapool_reachable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gorillapool_error: Option<String>,
+    pub db_path: String,
+    pub db_size_bytes: u64,
+}
+
 /// Cache statistics
 #[derive(Debug, Serialize, Default)]
 pub struct CacheStats {
     pub ownership_entries: u64,
     pub content_entries: u64,
+    /// Content entries dropped for capacity or expiry
+    pub content_evictions: u64,
     pub hit_rate_percent: f64,
+    pub cache_config: CacheConfig,
+}
+
+/// Entries held by each cache, as reported before `POST /admin/cache/clear` empties them
+#[derive(Debug, Clone, Serialize)]
+pub struct CacheEntryCounts {
+    pub wallet: u64,
+    pub ordinal: u64,
+    pub content: u64,
+    pub preview: u64,
+    pub holders: u64,
+    pub stats: u64,
+    pub chain_tip: u64,
+    pub exchange_rate: u64,
+    /// Negative ("not found") entries
+    pub not_found: u64,
+}
+
+/// Configured cache TTLs and capacity
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct CacheConfig {
+    pub ownership_cache_ttl_secs: u64,
+    pub metadata_cache_ttl_secs: u64,
+    pub content_cache_ttl_secs: u64,
+    pub max_cache_entries: u64,
 }
 
 // =============================================================================
@@ -139,7 +244,54 @@ pub struct CacheStats {
 pub enum ListingStatus {
     Active,
     Sold,
+    /// Withdrawn by the seller, or by the marketplace when the ordinal left the listed UTXO
     Cancelled,
+    /// Timed out: past its expiry or the maximum lifetime, or an auction that ended
+    /// unsold
+    Expired,
+}
+
+/// Listing lifecycle change pushed to `/events` subscribers
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", content = "listing", rename_all = "snake_case")]
+pub enum ListingEvent {
+    Created(Listing),
+    Cancelled(Listing),
+    Expired(Listing),
+    Sold(Listing),
+    Repriced(Listing),
+    BidPlaced(Listing),
+    AuctionClosed(Listing),
+}
+
+impl ListingEvent {
+    /// SSE event name
+    pub fn name(&self) -> &'static str {
+        match self {
+            ListingEvent::Created(_) => "listing_created",
+            ListingEvent::Cancelled(_) => "listing_cancelled",
+            ListingEvent::Expired(_) => "listing_expired",
+            ListingEvent::Sold(_) => "listing_sold",
+            ListingEvent::Repriced(_) => "listing_repriced",
+            ListingEvent::BidPlaced(_) => "auction_bid",
+            ListingEvent::AuctionClosed(_) => "auction_closed",
+        }
+    }
+}
+
+/// Aggregate marketplace numbers computed from all listings
+#[derive(Debug, Clone, Serialize, Default)]
+pub struct MarketplaceStats {
+    pub active_listings: usize,
+    pub sold_listings: usize,
+    pub cancelled_listings: usize,
+    pub expired_listings: usize,
+    /// Sum of `total_price` across sold listings (satoshis)
+    pub total_volume_sats: u64,
+    /// Average `total_price` of sold listings (satoshis)
+    pub average_sale_price_sats: u64,
+    pub distinct_sellers: usize,
+    pub computed_at: DateTime<Utc>,
 }
 
 /// Fee breakdown for a listing
@@ -147,20 +299,29 @@ pub enum ListingStatus {
 pub struct ListingFees {
     /// Price the seller wants to receive (in satoshis)
     pub seller_receives: u64,
-    /// Marketplace fee (1%) in satoshis
+    /// Marketplace fee in satoshis
     pub marketplace_fee: u64,
     /// Optional tip to the platform (in satoshis)
     pub tip_amount: u64,
-    /// Tip percentage (0, 2.5, or 5)
+    /// Tip percentage, one decimal place
     pub tip_percent: f64,
     /// Total price buyer pays (in satoshis)
     pub total_price: u64,
 }
 
 impl ListingFees {
-    pub fn calculate(seller_wants: u64, tip_percent: f64) -> Self {
-        // Marketplace fee is 1% of what seller wants
-        let marketplace_fee = (seller_wants as f64 * 0.01).ceil() as u64;
+    /// Check a seller-chosen tip is within `0..=max_tip_percent` and round it to
+    /// one decimal place
+    pub fn validate_tip_percent(tip_percent: f64, max_tip_percent: f64) -> Result<f64, String> {
+        if !tip_percent.is_finite() || tip_percent < 0.0 || tip_percent > max_tip_percent {
+            return Err(format!("Tip must be between 0% and {}%", max_tip_percent));
+        }
+        Ok((tip_percent * 10.0).round() / 10.0)
+    }
+
+    pub fn calculate(seller_wants: u64, marketplace_fee_percent: f64, tip_percent: f64) -> Self {
+        // Marketplace fee is a percentage of what seller wants
+        let marketplace_fee = (seller_wants as f64 * (marketplace_fee_percent / 100.0)).ceil() as u64;
         
         // Tip is percentage of seller_wants
         let tip_amount = (seller_wants as f64 * (tip_percent / 100.0)).ceil() as u64;
@@ -186,6 +347,15 @@ impl ListingFees {
 pub struct Listing {
     /// Unique listing ID
     pub id: String,
+    /// Record format version (0 for records written before versioning)
+    #[serde(default)]
+    pub schema_version: u32,
+    /// Incremented on every write; updates only apply if it still matches the stored record
+    #[serde(default)]
+    pub revision: u64,
+    /// Monotonic listing number (0 for listings created before sequencing)
+    #[serde(default)]
+    pub seq: u64,
     /// Ordinal origin (txid_vout)
     pub origin: String,
     /// Seller's BSV address (receives payment)
@@ -204,6 +374,12 @@ pub struct Listing {
     pub listing_utxo: Option<String>,
     /// Original ordinal UTXO being listed
     pub ordinal_utxo: OrdinalUtxoRef,
+    /// MAP metadata traits of the ordinal (lowercased), used for attribute filters
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub attributes: BTreeMap<String, String>,
+    /// Collection the ordinal belongs to (from its MAP data), used for collection filters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub collection_id: Option<String>,
     /// When the listing was created
     pub created_at: DateTime<Utc>,
     /// When the listing was updated
@@ -217,6 +393,129 @@ pub struct Listing {
     /// Purchase transaction ID (if sold)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub purchase_txid: Option<String>,
+    /// Broadcaster's (mAPI) response to the purchase transaction, kept for audit
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broadcast_response: Option<serde_json::Value>,
+    /// Why the marketplace closed the listing on the seller's behalf (expired, or
+    /// cancelled because the ordinal moved)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expired_reason: Option<String>,
+    /// When the listing stops being buyable, if the seller asked for an expiry
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    /// Fiat price the seller asked for, if the listing was priced in fiat
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fiat_price: Option<FiatPrice>,
+    /// Offer the seller accepted; while it is unexpired only its buyer may purchase,
+    /// at the offer price now in `fees`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accepted_offer: Option<AcceptedOffer>,
+    /// Cart purchase the listing is held for; while it is unexpired only its buyer may purchase
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cart_hold: Option<CartHold>,
+    /// Set for timed auctions, which can't be bought at `fees` until won
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub auction: Option<Auction>,
+}
+
+/// Settings and bidding state of an auction listing. Bids are off-chain; the
+/// winner settles with an ordinary purchase at the winning bid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Auction {
+    /// Lowest acceptable first bid, as the seller's share (fees go on top)
+    pub start_price_sats: u64,
+    pub ends_at: DateTime<Utc>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub high_bid: Option<Bid>,
+    #[serde(default)]
+    pub bid_count: u32,
+    /// When the auction was closed; from then on `fees` hold the winning bid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub closed_at: Option<DateTime<Utc>>,
+    /// The winner must purchase before this, or the listing expires
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub settle_by: Option<DateTime<Utc>>,
+}
+
+/// A bid on an auction listing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Bid {
+    /// Where the bidder receives the ordinal if they win
+    pub bidder_ord_address: String,
+    /// Seller's share offered, like `seller_wants_satoshis`
+    pub amount_sats: u64,
+    pub placed_at: DateTime<Utc>,
+}
+
+/// Short reservation of a listing for the buyer who prepared a cart purchase
+/// including it, so the combined transaction isn't beaten by a single purchase
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CartHold {
+    /// Only purchases delivering the ordinal to this address are allowed
+    pub buyer_ord_address: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Reservation of a listing for the buyer of an accepted offer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AcceptedOffer {
+    pub offer_id: String,
+    /// Only purchases delivering the ordinal to this address are allowed
+    pub buyer_ord_address: String,
+    /// After this the reservation lapses and `ask_fees` are restored
+    pub expires_at: DateTime<Utc>,
+    /// The listing's fees before the offer was accepted
+    pub ask_fees: ListingFees,
+}
+
+/// Offer lifecycle
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OfferStatus {
+    Pending,
+    /// Accepted by the seller; the listing is reserved for the buyer at the offer price
+    Accepted,
+    Rejected,
+    Expired,
+}
+
+/// A buyer's offer below a listing's asking price
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Offer {
+    pub id: String,
+    pub listing_id: String,
+    /// Where the buyer receives the ordinal; once accepted, only purchases to it are allowed
+    pub buyer_ord_address: String,
+    /// What the seller would receive, like `seller_wants_satoshis` (fees are added on top)
+    pub amount_sats: u64,
+    pub status: OfferStatus,
+    pub expires_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Offer {
+    /// Status as of `now`: a pending offer past its expiry reads as expired
+    pub fn status_at(&self, now: DateTime<Utc>) -> OfferStatus {
+        if self.status == OfferStatus::Pending && self.expires_at <= now {
+            OfferStatus::Expired
+        } else {
+            self.status
+        }
+    }
+}
+
+/// A fiat asking price and the exchange rate that locked in the satoshi amount
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FiatPrice {
+    /// ISO currency code (currently always `USD`)
+    pub currency: String,
+    /// Price in `currency`
+    pub amount: f64,
+    /// Price of one BSV in `currency` when the listing was created
+    pub rate: f64,
+    /// When the rate was fetched
+    pub quoted_at: DateTime<Utc>,
 }
 
 /// Reference to an ordinal UTXO
@@ -228,6 +527,26 @@ pub struct OrdinalUtxoRef {
     pub script: String,  // Base64 encoded
 }
 
+impl OrdinalUtxoRef {
+    /// Locking script as hex (decoded from the base64 `script`)
+    pub fn script_hex(&self) -> Result<String, base64::DecodeError> {
+        use base64::Engine;
+        let bytes = base64::engine::general_purpose::STANDARD.decode(&self.script)?;
+        Ok(hex::encode(bytes))
+    }
+
+    /// Convert to a buyer payment UTXO for transaction building
+    pub fn to_buyer_utxo(&self) -> Result<BuyerUtxo, base64::DecodeError> {
+        Ok(BuyerUtxo {
+            txid: self.txid.clone(),
+            vout: self.vout,
+            satoshis: self.satoshis,
+            script_hex: self.script_hex()?,
+            address: None,
+        })
+    }
+}
+
 /// Simple representation of a buyer’s payment UTXO (used when building purchase TX)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BuyerUtxo {
@@ -235,19 +554,82 @@ pub struct BuyerUtxo {
     pub vout: u32,
     pub satoshis: u64,
     pub script_hex: String,  // Hex-encoded locking script (P2PKH usually)
+    /// Address the UTXO was fetched from, when known
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
 }
 
+/// Most extra payment addresses one purchase may draw UTXOs from
+pub const MAX_ADDITIONAL_PAYMENT_ADDRESSES: usize = 10;
+
 /// Request from frontend to prepare a Yours Wallet purchase
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PreparePurchaseRequest {
+    pub buyer_ord_address: String,
+    /// Funds the purchase and receives its change
+    pub buyer_payment_address: String,
+    /// Other buyer addresses whose UTXOs may also fund the purchase
+    #[serde(default)]
+    pub additional_payment_addresses: Vec<String>,
+    /// Split change into this many roughly-equal outputs (each kept above dust)
+    #[serde(default)]
+    pub split_change: Option<u32>,
+    /// Leave out the marketplace's OP_RETURN attribution output
+    #[serde(default)]
+    pub omit_op_return: bool,
+}
+
+/// Request to prepare one transaction buying several listings (a shopping cart)
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BatchPreparePurchaseRequest {
+    pub listing_ids: Vec<String>,
     pub buyer_ord_address: String,
     pub buyer_payment_address: String,
+    /// Split change into this many roughly-equal outputs (each kept above dust)
+    #[serde(default)]
+    pub split_change: Option<u32>,
+    /// Leave out the marketplace's OP_RETURN attribution output
+    #[serde(default)]
+    pub omit_op_return: bool,
+}
+
+/// Request from the seller to prepare the transaction locking their ordinal for sale
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PrepareLockRequest {
+    /// Must match the listing's seller ordinal address
+    pub seller_ord_address: String,
+}
+
+/// Signed lock transaction from the seller, to broadcast and attach to the listing
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BroadcastLockRequest {
+    pub seller_ord_address: String,
+    pub raw_tx_hex: String,
+}
+
+/// Which party's wallet must sign an input
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InputSigner {
+    /// Owner of the ordinal being sold or locked
+    Seller,
+    /// Owner of the payment UTXOs funding a purchase
+    Buyer,
 }
 
 /// Signature request format expected by Yours Wallet (yours.getSignatures)
 #[derive(Debug, Clone, Serialize)]
 pub struct SigRequest {
     pub input_index: u32,
+    /// Whose wallet signs this input
+    pub signer: InputSigner,
+    /// Address whose key signs this input, when known
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
     pub prev_txid: String,
     pub prev_vout: u32,
     pub satoshis: u64,
@@ -258,25 +640,122 @@ pub struct SigRequest {
 #[derive(Debug, Serialize)]
 pub struct PreparePurchaseResponse {
     pub raw_tx_hex: String,
+    /// One request per input, in input order. For purchases the ordinal inputs come
+    /// first and are signed by the seller; the payment inputs after them by the buyer.
+    /// Every input of a lock transaction is signed by the seller.
     pub sig_requests: Vec<SigRequest>,
 }
 
+/// Dry-run result for a prospective purchase: what it would cost without the transaction
+#[derive(Debug, Serialize)]
+pub struct PurchaseEstimate {
+    /// Size of the transaction once signed (BSV has no witness discount, so this is also its vsize)
+    pub estimated_size_bytes: u64,
+    pub miner_fee: u64,
+    pub input_count: usize,
+    /// Total returned to the buyer's payment address (0 when change would be dust)
+    pub change_sats: u64,
+}
+
+/// A fixed output of a purchase transaction
+#[derive(Debug, Clone, Serialize)]
+pub struct TemplateOutput {
+    /// What the output pays for: `buyer_ordinal`, `seller` or `marketplace_fee`
+    pub role: String,
+    /// Destination address (omitted for the buyer ordinal output if not supplied)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    pub satoshis: u64,
+    /// Hex-encoded locking script
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script_hex: Option<String>,
+}
+
+/// Non-funding parts of a purchase transaction, for wallets that add their own inputs
+#[derive(Debug, Serialize)]
+pub struct PurchaseTxTemplate {
+    pub listing_id: String,
+    /// Ordinal UTXO that must be spent as input 0
+    pub ordinal_input: OrdinalUtxoRef,
+    /// Fixed outputs, in order, before any buyer change
+    pub outputs: Vec<TemplateOutput>,
+    /// Sum of all fixed outputs (the buyer's inputs must cover this plus the miner fee)
+    pub total_fixed_sats: u64,
+}
+
 /// Request to create a new listing
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CreateListingRequest {
     /// Ordinal origin to list
     pub origin: String,
     /// The UTXO containing the ordinal
     pub ordinal_utxo: OrdinalUtxoRef,
-    /// What the seller wants to receive (in satoshis)
+    /// What the seller wants to receive (in satoshis); omit when pricing with `price_usd`
+    #[serde(default)]
     pub seller_wants_satoshis: u64,
-    /// Tip percentage (0, 2.5, or 5)
+    /// Price in USD, converted to satoshis at the oracle rate when the listing is created
+    #[serde(default)]
+    pub price_usd: Option<f64>,
+    /// Tip percentage, 0 up to the configured maximum
     #[serde(default)]
     pub tip_percent: f64,
     /// Seller's BSV address (to receive payment)
     pub seller_address: String,
     /// Seller's ordinal address (for cancellation return)
     pub seller_ord_address: String,
+    /// Bitcoin Signed Message over `signing_message()` by the `seller_ord_address` key
+    pub signature: String,
+    /// Unix time (seconds) the request was signed
+    pub signed_at: i64,
+    /// List as a timed auction lasting this long, with `seller_wants_satoshis` as the
+    /// starting price
+    #[serde(default)]
+    pub auction_duration_secs: Option<u64>,
+    /// Client-chosen key making the listing ID deterministic, so a retried create
+    /// returns the listing it already made instead of failing as already listed
+    #[serde(default)]
+    pub idempotency_key: Option<String>,
+    /// Expire the listing this many days after creation
+    #[serde(default)]
+    pub expires_in_days: Option<u32>,
+}
+
+impl CreateListingRequest {
+    /// The message the seller signs: origin, asking (or starting) price, auction
+    /// length if any, and signing time
+    pub fn signing_message(&self) -> String {
+        let price = match self.price_usd {
+            Some(usd) => format!("{} USD", usd),
+            None => format!("{} sats", self.seller_wants_satoshis),
+        };
+        match self.auction_duration_secs {
+            Some(secs) => format!(
+                "List {} at auction from {} for {} seconds at {}",
+                self.origin, price, secs, self.signed_at
+            ),
+            None => format!("List {} for {} at {}", self.origin, price, self.signed_at),
+        }
+    }
+}
+
+/// Request to bid on an auction listing
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlaceBidRequest {
+    pub bidder_ord_address: String,
+    pub amount_sats: u64,
+    /// Bitcoin Signed Message over `signing_message()` by the `bidder_ord_address` key
+    pub signature: String,
+    /// Unix time (seconds) the request was signed
+    pub signed_at: i64,
+}
+
+impl PlaceBidRequest {
+    /// The message the bidder signs: amount, listing ID and signing time
+    pub fn signing_message(&self, listing_id: &str) -> String {
+        format!("Bid {} sats on listing {} at {}", self.amount_sats, listing_id, self.signed_at)
+    }
 }
 
 /// Response when creating a listing
@@ -288,21 +767,131 @@ pub struct CreateListingResponse {
     pub message: String,
 }
 
+/// Outcome of one item of a `POST /listings/batch` call
+#[derive(Debug, Serialize)]
+pub struct BatchListingResult {
+    /// Position of the item in the request
+    pub index: usize,
+    pub origin: String,
+    pub success: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub listing: Option<Listing>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiError>,
+}
+
+/// Response to `POST /listings/batch`
+#[derive(Debug, Serialize)]
+pub struct BatchCreateListingsResponse {
+    pub created: usize,
+    pub failed: usize,
+    /// One result per requested listing, in request order
+    pub results: Vec<BatchListingResult>,
+}
+
 /// Request to cancel a listing
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct CancelListingRequest {
     pub listing_id: String,
     pub seller_ord_address: String,
+    /// Bitcoin Signed Message over `signing_message()` by the `seller_ord_address` key
+    pub signature: String,
+    /// Unix time (seconds) the request was signed
+    pub signed_at: i64,
+}
+
+impl CancelListingRequest {
+    /// The message the seller signs: listing ID and signing time
+    pub fn signing_message(&self) -> String {
+        format!("Cancel listing {} at {}", self.listing_id, self.signed_at)
+    }
+}
+
+/// Request to change an active listing's price
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RepriceListingRequest {
+    pub seller_ord_address: String,
+    pub seller_wants_satoshis: u64,
+    /// New tip percent; the current tip is kept if omitted
+    #[serde(default)]
+    pub tip_percent: Option<f64>,
+    /// Bitcoin Signed Message over `signing_message()` by the `seller_ord_address` key
+    pub signature: String,
+    /// Unix time (seconds) the request was signed
+    pub signed_at: i64,
+}
+
+impl RepriceListingRequest {
+    /// The message the seller signs: listing ID, new price (and tip, if given) and signing time
+    pub fn signing_message(&self, listing_id: &str) -> String {
+        match self.tip_percent {
+            Some(tip) => format!(
+                "Reprice listing {} to {} sats with {}% tip at {}",
+                listing_id, self.seller_wants_satoshis, tip, self.signed_at
+            ),
+            None => format!(
+                "Reprice listing {} to {} sats at {}",
+                listing_id, self.seller_wants_satoshis, self.signed_at
+            ),
+        }
+    }
+}
+
+/// Request to make an offer on a listing
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CreateOfferRequest {
+    /// Where the buyer will receive the ordinal; the purchase must pay out to it
+    pub buyer_ord_address: String,
+    pub amount_sats: u64,
+    /// How long the offer (and, once accepted, the reservation) lasts; defaults to a day
+    #[serde(default)]
+    pub expires_in_secs: Option<u64>,
+    /// Bitcoin Signed Message over `signing_message()` by the `buyer_ord_address` key
+    pub signature: String,
+    /// Unix time (seconds) the request was signed
+    pub signed_at: i64,
+}
+
+impl CreateOfferRequest {
+    /// The message the buyer signs: amount, listing ID and signing time
+    pub fn signing_message(&self, listing_id: &str) -> String {
+        format!("Offer {} sats for listing {} at {}", self.amount_sats, listing_id, self.signed_at)
+    }
+}
+
+/// Seller's request to accept or reject an offer
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RespondToOfferRequest {
+    pub seller_ord_address: String,
+    /// Bitcoin Signed Message over `signing_message()` by the `seller_ord_address` key
+    pub signature: String,
+    /// Unix time (seconds) the request was signed
+    pub signed_at: i64,
+}
+
+impl RespondToOfferRequest {
+    /// The message the seller signs: `Accept` or `Reject`, the offer ID and signing time
+    pub fn signing_message(&self, action: &str, offer_id: &str) -> String {
+        format!("{} offer {} at {}", action, offer_id, self.signed_at)
+    }
 }
 
 /// Request to purchase a listing
 #[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct PurchaseListingRequest {
     pub listing_id: String,
     pub buyer_address: String,
     pub buyer_ord_address: String,
     /// UTXOs to fund the purchase
     pub payment_utxos: Vec<OrdinalUtxoRef>,
+    /// Leave out the marketplace's OP_RETURN attribution output
+    #[serde(default)]
+    pub omit_op_return: bool,
 }
 
 /// Paginated listings response
@@ -313,6 +902,112 @@ pub struct ListingsResponse {
     pub total: usize,
     pub page: usize,
     pub per_page: usize,
+    pub total_pages: usize,
+    pub has_next: bool,
+    pub has_prev: bool,
+}
+
+impl ListingsResponse {
+    /// Wrap one page of `total` results, deriving the page navigation fields.
+    /// `page` is 1-based and `per_page` must be non-zero.
+    pub fn paginated(listings: Vec<Listing>, total: usize, page: usize, per_page: usize) -> Self {
+        let total_pages = total.div_ceil(per_page);
+        Self {
+            success: true,
+            listings,
+            total,
+            page,
+            per_page,
+            total_pages,
+            has_next: page < total_pages,
+            has_prev: page > 1,
+        }
+    }
+}
+
+/// A wallet's holdings, open listings and completed sales in one view
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletPortfolio {
+    pub address: String,
+    /// Ordinals currently held by the address
+    pub held: Vec<OrdinalDetails>,
+    /// Listings by this seller that are still active
+    pub active_listings: Vec<Listing>,
+    /// Listings by this seller that have sold
+    pub realized_sales: Vec<Listing>,
+    /// Sum of what the seller received across `realized_sales`
+    pub realized_value_sats: u64,
+    /// Sum of asking prices (seller's share) across `active_listings`
+    pub unrealized_value_sats: u64,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// A listing on an address's watchlist, with its current state
+#[derive(Debug, Clone, Serialize)]
+pub struct WatchedListing {
+    pub listing_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub watched_at: Option<DateTime<Utc>>,
+    /// Current listing, so watchers see sales and cancellations (`None` if since pruned)
+    pub listing: Option<Listing>,
+}
+
+/// A wallet's marketplace activity as seller and buyer
+#[derive(Debug, Clone, Serialize)]
+pub struct WalletHistory {
+    pub address: String,
+    /// Listings created by this seller in any status, most recently updated first
+    pub listings: Vec<Listing>,
+    /// Listings this address bought, most recently updated first
+    pub purchases: Vec<Listing>,
+    /// Number of this seller's listings that sold
+    pub sales_count: usize,
+    /// Sum of what the seller received across sold listings
+    pub total_sold_sats: u64,
+    /// Sum of total prices paid across `purchases`
+    pub total_spent_sats: u64,
+    pub computed_at: DateTime<Utc>,
+}
+
+/// Body for `POST /admin/prune`
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PruneRequest {
+    /// Only prune listings closed at least this long ago
+    pub older_than_secs: u64,
+    /// Keep a `sold_archive:` record of each pruned sale
+    #[serde(default = "default_archive_sold")]
+    pub archive_sold: bool,
+}
+
+fn default_archive_sold() -> bool { true }
+
+/// What remains of a pruned sale under `sold_archive:<id>`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SoldArchiveEntry {
+    pub listing_id: String,
+    pub origin: String,
+    pub price_sats: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchase_txid: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sold_at: Option<DateTime<Utc>>,
+}
+
+/// A completed sale for the recent sales feed
+#[derive(Debug, Clone, Serialize)]
+pub struct SaleRecord {
+    pub listing_id: String,
+    pub seq: u64,
+    pub origin: String,
+    /// Total price paid by the buyer, including fees
+    pub price_sats: u64,
+    pub seller_address: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub buyer_address: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub purchase_txid: Option<String>,
+    pub sold_at: DateTime<Utc>,
 }
 
 /// Query parameters for listing listings
@@ -324,7 +1019,10 @@ pub struct ListingsQuery {
     pub per_page: usize,
     /// Filter by seller address
     pub seller: Option<String>,
+    /// Filter by collection
+    pub collection_id: Option<String>,
     /// Filter by status
+    #[allow(dead_code)]
     pub status: Option<String>,
 }
 
@@ -1,26 +1,37 @@
 use crate::config::Config;
+use crate::metrics::Metrics;
 use crate::models::{Inscription, OrdinalUtxo};
 use anyhow::{Context, Result};
 use governor::{Quota, RateLimiter};
-use reqwest::Client;
+use rand::Rng;
+use reqwest::{Client, RequestBuilder, Response, StatusCode};
 use std::num::NonZeroU32;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tokio::sync::Semaphore;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// GorillaPool API client with built-in rate limiting
 pub struct GorillaPoolClient {
     client: Client,
     base_url: String,
+    /// Hosts tried in order after `base_url` on a 5xx or connection error
+    fallback_urls: Vec<String>,
+    /// Total tries per request for transient failures (at least 1)
+    retry_attempts: u32,
+    /// Backoff before the first retry; doubles on each later retry
+    retry_base_delay: Duration,
+    /// Per-request timeout for content downloads; other calls use the client's
+    content_timeout: Duration,
     rate_limiter: Arc<RateLimiter<governor::state::NotKeyed, governor::state::InMemoryState, governor::clock::DefaultClock>>,
     concurrent_semaphore: Arc<Semaphore>,
+    metrics: Option<Arc<Metrics>>,
 }
 
 impl GorillaPoolClient {
     pub fn new(config: &Config) -> Result<Self> {
         let client = Client::builder()
-            .timeout(Duration::from_secs(30))
+            .timeout(config.gorillapool_timeout)
             .pool_max_idle_per_host(10)
             .build()
             .context("Failed to create HTTP client")?;
@@ -41,11 +52,109 @@ impl GorillaPoolClient {
         Ok(Self {
             client,
             base_url: config.gorillapool_base_url.clone(),
+            fallback_urls: config.gorillapool_fallback_urls.clone(),
+            retry_attempts: config.gorillapool_retry_attempts.max(1),
+            retry_base_delay: config.gorillapool_retry_base_delay,
+            content_timeout: config.content_request_timeout,
             rate_limiter,
             concurrent_semaphore,
+            metrics: None,
         })
     }
 
+    /// Record call counts and latencies in `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Send a request, recording its outcome and latency under `endpoint`
+    async fn send(&self, endpoint: &'static str, request: RequestBuilder) -> reqwest::Result<Response> {
+        let started = Instant::now();
+        let result = request.send().await;
+        if let Some(ref metrics) = self.metrics {
+            let status = result.as_ref().ok().map(|r| r.status().as_u16());
+            metrics.record_gorillapool_call(endpoint, status, started.elapsed());
+        }
+        result
+    }
+
+    /// GET `path` with failover, retrying transient failures (429, 5xx, connection
+    /// errors) with exponential backoff and jitter. Anything else, including 404,
+    /// is returned immediately. `timeout` overrides the client's per-request timeout.
+    async fn get_with_retry(
+        &self,
+        endpoint: &'static str,
+        path: &str,
+        timeout: Option<Duration>,
+    ) -> reqwest::Result<Response> {
+        let mut attempt = 1;
+        loop {
+            let result = self.get_with_failover(endpoint, path, timeout).await;
+            let (retryable, retry_after) = match &result {
+                Ok(response) if response.status() == StatusCode::TOO_MANY_REQUESTS => {
+                    (true, retry_after(response))
+                }
+                Ok(response) => (response.status().is_server_error(), None),
+                Err(_) => (true, None),
+            };
+            if !retryable || attempt >= self.retry_attempts {
+                return result;
+            }
+
+            let delay = retry_after.unwrap_or_else(|| backoff_delay(self.retry_base_delay, attempt));
+            warn!(
+                "GorillaPool {} attempt {}/{} failed, retrying in {}ms",
+                path, attempt, self.retry_attempts, delay.as_millis()
+            );
+            tokio::time::sleep(delay).await;
+            self.wait_for_rate_limit().await;
+            attempt += 1;
+        }
+    }
+
+    /// GET `path` from the primary host, moving on to each fallback host when one
+    /// fails with a connection error or 5xx. Returns the last host's 5xx response
+    /// if every host fails that way.
+    async fn get_with_failover(
+        &self,
+        endpoint: &'static str,
+        path: &str,
+        timeout: Option<Duration>,
+    ) -> reqwest::Result<Response> {
+        let hosts: Vec<&str> = std::iter::once(self.base_url.as_str())
+            .chain(self.fallback_urls.iter().map(String::as_str))
+            .collect();
+        let last = hosts.len() - 1;
+
+        for (i, host) in hosts.iter().enumerate() {
+            let url = format!("{}{}", host, path);
+            let mut request = self.client.get(&url);
+            if let Some(timeout) = timeout {
+                request = request.timeout(timeout);
+            }
+            match self.send(endpoint, request).await {
+                Ok(response) if !response.status().is_server_error() || i == last => {
+                    if i > 0 {
+                        info!("GorillaPool fallback host {} served {}", host, path);
+                    } else {
+                        debug!("GorillaPool host {} served {}", host, path);
+                    }
+                    return Ok(response);
+                }
+                Ok(response) => {
+                    warn!("GorillaPool host {} returned {} for {}, trying next host", host, response.status(), path);
+                }
+                Err(e) if i == last => return Err(e),
+                Err(e) => {
+                    warn!("GorillaPool host {} failed for {}: {}, trying next host", host, path, e);
+                }
+            }
+        }
+
+        unreachable!("host list always contains the primary base URL")
+    }
+
     async fn wait_for_rate_limit(&self) {
         self.rate_limiter.until_ready().await;
     }
@@ -57,10 +166,10 @@ impl GorillaPoolClient {
         self.wait_for_rate_limit().await;
 
         // Use the correct endpoint: /txos/address/:address/unspent
-        let url = format!("{}/txos/address/{}/unspent", self.base_url, address);
-        debug!("Fetching UTXOs from: {}", url);
+        let path = format!("/txos/address/{}/unspent", address);
+        debug!("Fetching UTXOs: {}", path);
 
-        let response = self.client.get(&url).send().await.context("Failed to fetch UTXOs")?;
+        let response = self.get_with_retry("address_utxos", &path, None).await.context("Failed to fetch UTXOs")?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -86,10 +195,10 @@ impl GorillaPoolClient {
         self.wait_for_rate_limit().await;
 
         // Use the correct endpoint that actually works
-        let url = format!("{}/txos/address/{}/unspent", self.base_url, address);
-        debug!("Fetching inscriptions from: {}", url);
+        let path = format!("/txos/address/{}/unspent", address);
+        debug!("Fetching inscriptions: {}", path);
 
-        let response = self.client.get(&url).send().await.context("Failed to fetch inscriptions")?;
+        let response = self.get_with_retry("address_inscriptions", &path, None).await.context("Failed to fetch inscriptions")?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -114,10 +223,10 @@ impl GorillaPoolClient {
         let _permit = self.concurrent_semaphore.acquire().await?;
         self.wait_for_rate_limit().await;
 
-        let url = format!("{}/inscriptions/origin/{}", self.base_url, origin);
-        debug!("Fetching inscription: {}", url);
+        let path = format!("/inscriptions/origin/{}", origin);
+        debug!("Fetching inscription: {}", path);
 
-        let response = self.client.get(&url).send().await.context("Failed to fetch inscription")?;
+        let response = self.get_with_retry("inscription", &path, None).await.context("Failed to fetch inscription")?;
 
         if response.status().as_u16() == 404 {
             return Ok(None);
@@ -139,10 +248,10 @@ impl GorillaPoolClient {
         let _permit = self.concurrent_semaphore.acquire().await?;
         self.wait_for_rate_limit().await;
 
-        let url = format!("{}/files/inscriptions/{}", self.base_url, origin);
-        debug!("Fetching content: {}", url);
+        let path = format!("/files/inscriptions/{}", origin);
+        debug!("Fetching content: {}", path);
 
-        let response = self.client.get(&url).send().await.context("Failed to fetch inscription content")?;
+        let response = self.get_with_retry("inscription_content", &path, Some(self.content_timeout)).await.context("Failed to fetch inscription content")?;
 
         if !response.status().is_success() {
             let status = response.status();
@@ -161,6 +270,38 @@ impl GorillaPoolClient {
         Ok((bytes, content_type))
     }
 
+    /// Current chain tip height
+    pub async fn get_chain_tip(&self) -> Result<u64> {
+        let _permit = self.concurrent_semaphore.acquire().await?;
+        self.wait_for_rate_limit().await;
+
+        let path = "/blocks/tip";
+        debug!("Fetching chain tip: {}", path);
+
+        let response = self.get_with_retry("chain_tip", path, None).await.context("Failed to fetch chain tip")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("GorillaPool API returned {} for chain tip", response.status());
+        }
+
+        let tip: serde_json::Value = response.json().await.context("Failed to parse chain tip response")?;
+        tip["height"].as_u64().context("Chain tip response has no height")
+    }
+
+    /// Cheap reachability check against the API host
+    pub async fn ping(&self) -> Result<()> {
+        let _permit = self.concurrent_semaphore.acquire().await?;
+        self.wait_for_rate_limit().await;
+
+        let response = self.send("ping", self.client.head(&self.base_url)).await.context("GorillaPool unreachable")?;
+
+        if response.status().is_server_error() {
+            anyhow::bail!("GorillaPool returned {}", response.status());
+        }
+
+        Ok(())
+    }
+
     pub fn content_url(&self, origin: &str) -> String {
         format!("{}/files/inscriptions/{}", self.base_url, origin)
     }
@@ -175,8 +316,84 @@ impl Clone for GorillaPoolClient {
         Self {
             client: self.client.clone(),
             base_url: self.base_url.clone(),
+            fallback_urls: self.fallback_urls.clone(),
+            retry_attempts: self.retry_attempts,
+            retry_base_delay: self.retry_base_delay,
+            content_timeout: self.content_timeout,
             rate_limiter: Arc::clone(&self.rate_limiter),
             concurrent_semaphore: Arc::clone(&self.concurrent_semaphore),
+            metrics: self.metrics.clone(),
         }
     }
 }
+
+/// Delay before retry number `attempt` (1-based): `base * 2^(attempt - 1)`, with
+/// jitter picking uniformly from the upper half so concurrent retries spread out
+fn backoff_delay(base: Duration, attempt: u32) -> Duration {
+    let max = base.saturating_mul(2u32.saturating_pow(attempt - 1));
+    let jitter = rand::thread_rng().gen_range(0.5..=1.0);
+    max.mul_f64(jitter)
+}
+
+/// Longest `Retry-After` we'll honour; anything beyond is clamped
+const MAX_RETRY_AFTER: Duration = Duration::from_secs(60);
+
+/// Parse a `Retry-After` header given as delay-seconds or an HTTP date
+fn retry_after(response: &Response) -> Option<Duration> {
+    let value = response.headers().get(reqwest::header::RETRY_AFTER)?.to_str().ok()?.trim();
+
+    let delay = match value.parse::<u64>() {
+        Ok(secs) => Duration::from_secs(secs),
+        Err(_) => {
+            let at = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+            (at.with_timezone(&chrono::Utc) - chrono::Utc::now()).to_std().unwrap_or(Duration::ZERO)
+        }
+    };
+
+    Some(delay.min(MAX_RETRY_AFTER))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::serve;
+    use axum::{http::StatusCode as AxumStatus, routing::get, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Client against a mock answering every inscription lookup with `status`,
+    /// returning the counter of requests it received
+    async fn client_answering(status: AxumStatus) -> (GorillaPoolClient, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let app = Router::new().route(
+            "/inscriptions/origin/:origin",
+            get(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                status
+            }),
+        );
+        let config = Config {
+            gorillapool_base_url: serve(app).await,
+            gorillapool_retry_attempts: 3,
+            gorillapool_retry_base_delay: Duration::from_millis(1),
+            ..Default::default()
+        };
+        (GorillaPoolClient::new(&config).unwrap(), hits)
+    }
+
+    #[tokio::test]
+    async fn not_found_is_not_retried() {
+        let (client, hits) = client_answering(AxumStatus::NOT_FOUND).await;
+
+        assert!(client.get_inscription_by_origin("abc_0").await.unwrap().is_none());
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn service_unavailable_is_retried_up_to_the_attempt_limit() {
+        let (client, hits) = client_answering(AxumStatus::SERVICE_UNAVAILABLE).await;
+
+        assert!(client.get_inscription_by_origin("abc_0").await.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+}
@@ -0,0 +1,131 @@
+use crate::models::Listing;
+use chrono::Utc;
+use crate::services::{GorillaPoolClient, ListingsDb};
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Periodically re-check active listings against GorillaPool and expire any whose
+/// ordinal has moved away from the listed UTXO outside the marketplace
+pub fn spawn_stale_listing_checker(
+    listings_db: ListingsDb,
+    gorillapool: GorillaPoolClient,
+    interval: Duration,
+) {
+    info!("Stale listing checker running every {}s", interval.as_secs());
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately; skip it so startup isn't slowed by a full scan
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            check_active_listings(&listings_db, &gorillapool).await;
+        }
+    });
+}
+
+async fn check_active_listings(listings_db: &ListingsDb, gorillapool: &GorillaPoolClient) {
+    let listings = match listings_db.get_active_listings(1, usize::MAX) {
+        Ok((listings, _)) => listings,
+        Err(e) => {
+            warn!("Stale listing check failed to load listings: {}", e);
+            return;
+        }
+    };
+
+    let mut expired = 0;
+    for listing in &listings {
+        let inscription = match gorillapool.get_inscription_by_origin(&listing.origin).await {
+            Ok(Some(inscription)) => inscription,
+            // Unknown to the indexer or a transient error: leave the listing alone
+            Ok(None) => continue,
+            Err(e) => {
+                debug!("Skipping stale check for {}: {}", listing.origin, e);
+                continue;
+            }
+        };
+
+        if is_at_listed_utxo(listing, &inscription.txid, inscription.vout) {
+            continue;
+        }
+
+        let reason = format!(
+            "ordinal moved to {}_{} outside the marketplace",
+            inscription.txid, inscription.vout
+        );
+        match listings_db.withdraw_listing(&listing.id, &reason) {
+            Ok(_) => expired += 1,
+            Err(e) => warn!("Failed to expire listing {}: {}", listing.id, e),
+        }
+    }
+
+    info!("Stale listing check: {} active, {} expired", listings.len(), expired);
+}
+
+/// Whether the ordinal's current location is the listed UTXO or its lock output
+fn is_at_listed_utxo(listing: &Listing, txid: &str, vout: u32) -> bool {
+    let location = format!("{}:{}", txid, vout);
+    (listing.ordinal_utxo.txid == txid && listing.ordinal_utxo.vout == vout)
+        || listing.listing_utxo.as_deref() == Some(location.as_str())
+}
+
+/// Periodically close auctions whose end time has passed, and expire closed
+/// auctions whose winner didn't buy within the settlement window
+pub fn spawn_auction_closer(listings_db: ListingsDb, interval: Duration, settlement_window: Duration) {
+    info!("Auction closer running every {}s", interval.as_secs());
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            close_ended_auctions(&listings_db, settlement_window);
+        }
+    });
+}
+
+fn close_ended_auctions(listings_db: &ListingsDb, settlement_window: Duration) {
+    let listings = match listings_db.get_active_listings(1, usize::MAX) {
+        Ok((listings, _)) => listings,
+        Err(e) => {
+            warn!("Auction closer failed to load listings: {}", e);
+            return;
+        }
+    };
+
+    let now = Utc::now();
+    for listing in &listings {
+        let Some(ref auction) = listing.auction else {
+            continue;
+        };
+
+        let result = match (auction.closed_at, auction.settle_by) {
+            (None, _) if auction.ends_at <= now => listings_db.close_auction(&listing.id, settlement_window),
+            (Some(_), Some(settle_by)) if settle_by <= now => {
+                listings_db.mark_expired(&listing.id, "auction winner did not settle")
+            }
+            _ => continue,
+        };
+        // A listing that sold or changed in the meantime is picked up again next tick
+        if let Err(e) = result {
+            warn!("Failed to close auction {}: {}", listing.id, e);
+        }
+    }
+}
+
+/// Periodically expire listings past their expiry or the maximum lifetime, so
+/// they're closed (and announced) even if nothing reads the active listings
+pub fn spawn_expiry_sweeper(listings_db: ListingsDb, interval: Duration) {
+    info!("Listing expiry sweeper running every {}s", interval.as_secs());
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        loop {
+            ticker.tick().await;
+            let expired = listings_db.expire_due_listings();
+            if expired > 0 {
+                info!("Expiry sweep: {} listings expired", expired);
+            }
+        }
+    });
+}
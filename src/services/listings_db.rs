@@ -1,36 +1,255 @@
-use crate::models::{Listing, ListingStatus, ListingFees, CreateListingRequest, OrdinalUtxoRef};
+use crate::models::{
+    Listing, ListingEvent, ListingStatus, ListingFees, CreateListingRequest, IndexConsistency,
+    MarketplaceStats, SoldArchiveEntry, FiatPrice, CollectionSummary, WatchedListing,
+    Offer, OfferStatus, AcceptedOffer, Auction, Bid, CartHold,
+};
 use anyhow::{Context, Result};
-use chrono::Utc;
+use bitcoin::hashes::{sha256, Hash};
+use chrono::{DateTime, Duration, Utc};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use sled::transaction::{ConflictableTransactionError, TransactionError};
 use sled::Db;
 use std::sync::Arc;
-use tracing::{debug, info, error};
+use tokio::sync::broadcast;
+use tracing::{debug, error, info};
 use uuid::Uuid;
 
+/// Current `Listing::schema_version`; older records are upgraded when read
+const LISTING_SCHEMA_VERSION: u32 = 1;
+
+/// Prefix of the active listing index, `listing_by_active:<inverted created_at>:<id>`
+const ACTIVE_INDEX_PREFIX: &[u8] = b"listing_by_active:";
+
+/// Prefix of the sold index, `listing_by_sold:<inverted sold_at>:<id>`. Named like
+/// the other `listing_by_*` indexes rather than `sold_index:`.
+const SOLD_INDEX_PREFIX: &str = "listing_by_sold:";
+
+/// Marker set once the active index has been backfilled from existing listings
+const ACTIVE_INDEX_BUILT_KEY: &[u8] = b"meta:active_index_built";
+
+/// Key holding the last allocated listing sequence number
+const LISTING_SEQ_KEY: &[u8] = b"counter:listing_seq";
+
+/// How long a stored idempotent response is replayed for
+const IDEMPOTENCY_TTL_HOURS: i64 = 24;
+
+/// How long an in-flight idempotency claim holds its key; older claims are taken
+/// to belong to a request that died before finishing
+const IDEMPOTENCY_PENDING_TTL_MINS: i64 = 10;
+
+/// How many times a sale is retried after losing a race with another write
+const MAX_SALE_ATTEMPTS: usize = 3;
+
+/// Why a listing write was refused
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListingWriteError {
+    /// The listing is already sold, cancelled or expired
+    NotActive,
+    /// Another write changed the listing after it was read
+    RevisionConflict,
+}
+
+/// Outcome of claiming an idempotency key before acting on a request
+#[derive(Debug, Clone, PartialEq)]
+pub enum IdempotencyClaim {
+    /// The key is now held by the caller, who must store a response or release it
+    Claimed,
+    /// Another request holding the key is still in flight
+    InFlight,
+    /// A request with the key already finished; replay its response
+    Completed(serde_json::Value),
+}
+
+impl std::fmt::Display for ListingWriteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListingWriteError::NotActive => write!(f, "Listing is not active"),
+            ListingWriteError::RevisionConflict => write!(f, "Listing was modified concurrently"),
+        }
+    }
+}
+
+impl std::error::Error for ListingWriteError {}
+
 /// Listings database manager
 pub struct ListingsDb {
     db: Arc<Db>,
+    /// Active listings older than this are treated as expired (unbounded if `None`)
+    max_listing_lifetime: Option<Duration>,
+    /// Where create/cancel/sold events are published (not published if `None`)
+    events: Option<broadcast::Sender<ListingEvent>>,
+    /// Rewrite records older than `LISTING_SCHEMA_VERSION` when they're read
+    migrate_on_read: bool,
+    /// Marketplace fee charged on new listings, in percent
+    marketplace_fee_percent: f64,
 }
 
 impl ListingsDb {
     pub fn new(db: Arc<Db>) -> Self {
-        Self { db }
+        Self {
+            db,
+            max_listing_lifetime: None,
+            events: None,
+            migrate_on_read: false,
+            marketplace_fee_percent: 1.0,
+        }
     }
 
-    /// Create a new listing
-    pub fn create_listing(&self, request: CreateListingRequest) -> Result<Listing> {
-        // Validate tip percent
-        let tip_percent = match request.tip_percent {
-            p if p == 0.0 => 0.0,
-            p if (p - 2.5).abs() < 0.01 => 2.5,
-            p if (p - 5.0).abs() < 0.01 => 5.0,
-            _ => 0.0, // Default to 0 if invalid
+    /// Charge `percent` marketplace fee on listings created from now on
+    pub fn with_marketplace_fee_percent(mut self, percent: f64) -> Self {
+        self.marketplace_fee_percent = percent;
+        self
+    }
+
+    /// Upgrade and rewrite legacy listing records as `get_listing` encounters them
+    pub fn with_migrate_on_read(mut self, enabled: bool) -> Self {
+        self.migrate_on_read = enabled;
+        self
+    }
+
+    /// Publish listing lifecycle events to `sender`
+    pub fn with_event_sender(mut self, sender: broadcast::Sender<ListingEvent>) -> Self {
+        self.events = Some(sender);
+        self
+    }
+
+    /// Send an event to any subscribers; having none is not an error
+    fn publish(&self, event: ListingEvent) {
+        if let Some(ref events) = self.events {
+            let _ = events.send(event);
+        }
+    }
+
+    /// Treat active listings older than `lifetime` as expired when read
+    pub fn with_max_listing_lifetime(mut self, lifetime: Option<std::time::Duration>) -> Self {
+        self.max_listing_lifetime = lifetime.and_then(|l| Duration::from_std(l).ok());
+        self
+    }
+
+    /// Why an active listing should no longer be buyable: past the expiry the
+    /// seller chose, or older than the configured maximum lifetime
+    pub fn expiry_reason(&self, listing: &Listing) -> Option<&'static str> {
+        let now = Utc::now();
+        if listing.expires_at.is_some_and(|t| t <= now) {
+            return Some("listing expiry reached");
+        }
+        match self.max_listing_lifetime {
+            Some(lifetime) if now - listing.created_at > lifetime => Some("max listing lifetime exceeded"),
+            _ => None,
+        }
+    }
+
+    /// Expire every active listing that is past its expiry or the maximum
+    /// lifetime, returning how many were expired. Reads through the active index
+    /// already hide them; this makes the transition without waiting for a read.
+    pub fn expire_due_listings(&self) -> usize {
+        let mut expired = 0;
+
+        for (_, id_bytes) in self.db.scan_prefix(ACTIVE_INDEX_PREFIX).flatten() {
+            let id = String::from_utf8_lossy(&id_bytes);
+            let Ok(Some(listing)) = self.get_listing(&id) else {
+                continue;
+            };
+            if listing.status != ListingStatus::Active {
+                continue;
+            }
+            if let Some(reason) = self.expiry_reason(&listing) {
+                match self.mark_expired(&listing.id, reason) {
+                    Ok(_) => expired += 1,
+                    Err(e) => error!("Failed to expire listing {}: {}", listing.id, e),
+                }
+            }
+        }
+
+        expired
+    }
+
+    /// Move an active listing to `Expired` because it timed out, recording why
+    pub fn mark_expired(&self, id: &str, reason: &str) -> Result<Option<Listing>> {
+        self.close_for_seller(id, ListingStatus::Expired, reason)
+    }
+
+    /// Cancel an active listing on the seller's behalf, e.g. because the ordinal
+    /// left the listed UTXO, recording why
+    pub fn withdraw_listing(&self, id: &str, reason: &str) -> Result<Option<Listing>> {
+        self.close_for_seller(id, ListingStatus::Cancelled, reason)
+    }
+
+    fn close_for_seller(&self, id: &str, status: ListingStatus, reason: &str) -> Result<Option<Listing>> {
+        let mut listing = match self.get_listing(id)? {
+            Some(l) => l,
+            None => return Ok(None),
         };
 
+        if listing.status != ListingStatus::Active {
+            return Err(ListingWriteError::NotActive.into());
+        }
+
+        listing.status = status;
+        listing.expired_reason = Some(reason.to_string());
+        listing.updated_at = Utc::now();
+        self.store_deactivated(&mut listing)?;
+
+        info!("Closed listing {} as {:?}: {}", id, status, reason);
+        self.publish(match status {
+            ListingStatus::Expired => ListingEvent::Expired(listing.clone()),
+            _ => ListingEvent::Cancelled(listing.clone()),
+        });
+        Ok(Some(listing))
+    }
+
+    /// Create a new listing
+    pub fn create_listing(
+        &self,
+        request: CreateListingRequest,
+        attributes: BTreeMap<String, String>,
+        collection_id: Option<String>,
+        fiat_price: Option<FiatPrice>,
+    ) -> Result<Listing> {
+        // The handler enforces the configured maximum; only guard against nonsense here
+        let tip_percent = request.tip_percent;
+        if !tip_percent.is_finite() || tip_percent < 0.0 {
+            anyhow::bail!("Invalid tip percent {}", tip_percent);
+        }
+
         // Calculate fees
-        let fees = ListingFees::calculate(request.seller_wants_satoshis, tip_percent);
+        let fees = ListingFees::calculate(request.seller_wants_satoshis, self.marketplace_fee_percent, tip_percent);
+
+        // An auction's fees hold its starting price until it closes
+        let auction = request
+            .auction_duration_secs
+            .map(|secs| -> Result<Auction> {
+                let duration = i64::try_from(secs)
+                    .ok()
+                    .and_then(Duration::try_seconds)
+                    .context("Auction duration out of range")?;
+                Ok(Auction {
+                    start_price_sats: request.seller_wants_satoshis,
+                    ends_at: Utc::now() + duration,
+                    high_bid: None,
+                    bid_count: 0,
+                    closed_at: None,
+                    settle_by: None,
+                })
+            })
+            .transpose()?;
+
+        let seq = self.next_seq()?;
+
+        let expires_at = request
+            .expires_in_days
+            .map(|days| Utc::now() + Duration::days(i64::from(days)));
+
+        let id = match request.idempotency_key {
+            Some(ref key) => listing_id_for_key(&request.origin, &request.seller_ord_address, key),
+            None => Uuid::new_v4().to_string(),
+        };
 
         let listing = Listing {
-            id: Uuid::new_v4().to_string(),
+            id,
+            schema_version: LISTING_SCHEMA_VERSION,
+            revision: 0,
+            seq,
             origin: request.origin.clone(),
             seller_address: request.seller_address,
             seller_ord_address: request.seller_ord_address,
@@ -39,30 +258,64 @@ impl ListingsDb {
             psbt_hex: None,
             listing_utxo: None,
             ordinal_utxo: request.ordinal_utxo,
+            attributes,
+            collection_id,
             created_at: Utc::now(),
             updated_at: Utc::now(),
             sold_at: None,
             buyer_address: None,
             purchase_txid: None,
+            broadcast_response: None,
+            expired_reason: None,
+            expires_at,
+            fiat_price,
+            accepted_offer: None,
+            cart_hold: None,
+            auction,
         };
 
-        // Store in database
+        // Store the record and every index entry atomically so a crash can't
+        // leave an index pointing at a missing listing
         let key = format!("listing:{}", listing.id);
         let value = serde_json::to_vec(&listing).context("Failed to serialize listing")?;
-        self.db.insert(key.as_bytes(), value).context("Failed to insert listing")?;
+        let mut index_keys = vec![
+            // Quick lookup by origin
+            format!("listing_by_origin:{}", listing.origin),
+            format!("listing_by_seq:{:020}", listing.seq),
+            format!("listing_by_seller:{}:{}", listing.seller_address, listing.id),
+            active_index_key(&listing),
+        ];
+        // MAP attributes for trait filtering
+        index_keys.extend(
+            listing.attributes
+                .iter()
+                .map(|(key, value)| format!("listing_by_attr:{}={}:{}", key, value, listing.id)),
+        );
+        index_keys.extend(collection_index_key(&listing));
 
-        // Also index by origin for quick lookup
-        let origin_key = format!("listing_by_origin:{}", listing.origin);
-        self.db.insert(origin_key.as_bytes(), listing.id.as_bytes())
-            .context("Failed to insert origin index")?;
+        let inserted = self.db
+            .transaction(|tx| {
+                // A deterministic ID may already be taken by a concurrent create
+                if tx.get(key.as_bytes())?.is_some() {
+                    return Ok(false);
+                }
+                tx.insert(key.as_bytes(), value.as_slice())?;
+                for index_key in &index_keys {
+                    tx.insert(index_key.as_bytes(), listing.id.as_bytes())?;
+                }
+                Ok::<_, ConflictableTransactionError>(true)
+            })
+            .context("Failed to insert listing")?;
+        if !inserted {
+            anyhow::bail!("Listing {} already exists", listing.id);
+        }
 
-        // Index by seller
-        let seller_key = format!("listing_by_seller:{}:{}", listing.seller_address, listing.id);
-        self.db.insert(seller_key.as_bytes(), listing.id.as_bytes())
-            .context("Failed to insert seller index")?;
+        info!(
+            "Created listing #{} ({}) for origin {} at {} sats",
+            listing.seq, listing.id, listing.origin, listing.fees.total_price
+        );
+        self.publish(ListingEvent::Created(listing.clone()));
 
-        info!("Created listing {} for origin {} at {} sats", listing.id, listing.origin, listing.fees.total_price);
-        
         Ok(listing)
     }
 
@@ -72,33 +325,142 @@ impl ListingsDb {
         
         match self.db.get(key.as_bytes())? {
             Some(bytes) => {
-                let listing: Listing = serde_json::from_slice(&bytes)
+                let mut listing: Listing = serde_json::from_slice(&bytes)
                     .context("Failed to deserialize listing")?;
+
+                if self.migrate_on_read && upgrade_listing(&mut listing) {
+                    match self.update_listing(&mut listing) {
+                        Ok(()) => info!("Migrated listing {} to schema version {}", listing.id, LISTING_SCHEMA_VERSION),
+                        // Someone else rewrote it first; their write carries the new version
+                        Err(e) if is_revision_conflict(&e) => return self.get_listing(id),
+                        Err(e) => return Err(e),
+                    }
+                }
+
                 Ok(Some(listing))
             }
             None => Ok(None),
         }
     }
 
+    /// Get a listing by its sequence number
+    pub fn get_listing_by_seq(&self, seq: u64) -> Result<Option<Listing>> {
+        let seq_key = format!("listing_by_seq:{:020}", seq);
+
+        match self.db.get(seq_key.as_bytes())? {
+            Some(id_bytes) => {
+                let id = String::from_utf8_lossy(&id_bytes);
+                self.get_listing(&id)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Atomically allocate the next listing sequence number (starts at 1)
+    fn next_seq(&self) -> Result<u64> {
+        let updated = self.db
+            .update_and_fetch(LISTING_SEQ_KEY, |old| {
+                let current = old
+                    .and_then(|bytes| <[u8; 8]>::try_from(bytes).ok())
+                    .map(u64::from_be_bytes)
+                    .unwrap_or(0);
+                Some((current + 1).to_be_bytes().to_vec())
+            })
+            .context("Failed to allocate listing sequence number")?
+            .context("Listing sequence counter missing after update")?;
+
+        let bytes = <[u8; 8]>::try_from(updated.as_ref()).context("Corrupt listing sequence counter")?;
+        Ok(u64::from_be_bytes(bytes))
+    }
+
     /// Get a listing by origin
-    pub fn get_listing_by_origin(&self, origin: &str) -> Result<Option<Listing>> {
+    ///
+    /// The origin index keeps pointing at the latest listing of an ordinal after
+    /// it sells, is cancelled or expires; closed listings are only returned with `include_closed`.
+    pub fn get_listing_by_origin(&self, origin: &str, include_closed: bool) -> Result<Option<Listing>> {
         let origin_key = format!("listing_by_origin:{}", origin);
         
         match self.db.get(origin_key.as_bytes())? {
             Some(id_bytes) => {
                 let id = String::from_utf8_lossy(&id_bytes);
-                self.get_listing(&id)
+                let listing = self.get_listing(&id)?;
+                Ok(listing.filter(|l| include_closed || l.status == ListingStatus::Active))
             }
             None => Ok(None),
         }
     }
 
-    /// Update a listing
-    pub fn update_listing(&self, listing: &Listing) -> Result<()> {
+    /// Write back a listing read earlier, failing with `RevisionConflict` if the
+    /// stored record has changed since. Bumps `listing.revision` on success.
+    pub fn update_listing(&self, listing: &mut Listing) -> Result<()> {
         let key = format!("listing:{}", listing.id);
-        let value = serde_json::to_vec(listing).context("Failed to serialize listing")?;
-        self.db.insert(key.as_bytes(), value).context("Failed to update listing")?;
-        
+        let current = self.db.get(key.as_bytes())?;
+
+        let stored_revision = current
+            .as_ref()
+            .and_then(|bytes| serde_json::from_slice::<Listing>(bytes).ok())
+            .map(|l| l.revision);
+        if stored_revision != Some(listing.revision) {
+            return Err(ListingWriteError::RevisionConflict.into());
+        }
+
+        let mut next = listing.clone();
+        next.revision += 1;
+        let value = serde_json::to_vec(&next).context("Failed to serialize listing")?;
+
+        // Swap against the exact bytes checked above so a write in between loses
+        self.db
+            .compare_and_swap(key.as_bytes(), current, Some(value))
+            .context("Failed to update listing")?
+            .map_err(|_| ListingWriteError::RevisionConflict)?;
+
+        listing.revision = next.revision;
+        debug!("Updated listing {}", listing.id);
+        Ok(())
+    }
+
+    /// Persist a listing that has just left Active. In the same transaction it is
+    /// dropped from the active index and, if sold, added to the sold index. The
+    /// origin index is kept so the ordinal's sale history stays reachable. Fails with `RevisionConflict` if the stored record has changed since
+    /// `listing` was read.
+    fn store_deactivated(&self, listing: &mut Listing) -> Result<()> {
+        let expected_revision = listing.revision;
+        listing.revision += 1;
+
+        let key = format!("listing:{}", listing.id);
+        let value = serde_json::to_vec(&*listing).context("Failed to serialize listing")?;
+        let active_key = active_index_key(listing);
+        let sold_key = (listing.status == ListingStatus::Sold).then(|| sold_index_key(listing));
+
+        let result = self.db.transaction(|tx| {
+            let stored_revision = tx
+                .get(key.as_bytes())?
+                .and_then(|bytes| serde_json::from_slice::<Listing>(&bytes).ok())
+                .map(|l| l.revision);
+            if stored_revision != Some(expected_revision) {
+                return Err(ConflictableTransactionError::Abort(ListingWriteError::RevisionConflict));
+            }
+
+            tx.insert(key.as_bytes(), value.as_slice())?;
+            tx.remove(active_key.as_bytes())?;
+            if let Some(ref sold_key) = sold_key {
+                tx.insert(sold_key.as_bytes(), listing.id.as_bytes())?;
+            }
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {}
+            Err(TransactionError::Abort(e)) => {
+                listing.revision = expected_revision;
+                return Err(e.into());
+            }
+            Err(TransactionError::Storage(e)) => {
+                listing.revision = expected_revision;
+                return Err(anyhow::Error::new(e).context("Failed to update listing"));
+            }
+        }
+
         debug!("Updated listing {}", listing.id);
         Ok(())
     }
@@ -117,76 +479,461 @@ impl ListingsDb {
 
         // Verify status
         if listing.status != ListingStatus::Active {
-            anyhow::bail!("Listing is not active");
+            return Err(ListingWriteError::NotActive.into());
         }
 
         // Update status
         listing.status = ListingStatus::Cancelled;
         listing.updated_at = Utc::now();
         
-        self.update_listing(&listing)?;
-
-        // Remove from origin index
-        let origin_key = format!("listing_by_origin:{}", listing.origin);
-        self.db.remove(origin_key.as_bytes())?;
+        self.store_deactivated(&mut listing)?;
 
         info!("Cancelled listing {}", id);
+        self.publish(ListingEvent::Cancelled(listing.clone()));
         Ok(Some(listing))
     }
 
-    /// Mark a listing as sold
-    pub fn mark_listing_sold(
-        &self, 
-        id: &str, 
-        buyer_address: &str,
-        purchase_txid: &str
+    /// Change an active listing's price, keeping its ID, sequence number and
+    /// `created_at`. `tip_percent` of `None` keeps the current tip. A fiat price is
+    /// dropped, since the new price is in satoshis.
+    pub fn reprice_listing(
+        &self,
+        id: &str,
+        seller_ord_address: &str,
+        seller_wants_satoshis: u64,
+        tip_percent: Option<f64>,
     ) -> Result<Option<Listing>> {
         let mut listing = match self.get_listing(id)? {
             Some(l) => l,
             None => return Ok(None),
         };
 
+        if listing.seller_ord_address != seller_ord_address {
+            anyhow::bail!("Not authorized to reprice this listing");
+        }
+
         if listing.status != ListingStatus::Active {
-            anyhow::bail!("Listing is not active");
+            return Err(ListingWriteError::NotActive.into());
         }
 
-        listing.status = ListingStatus::Sold;
-        listing.sold_at = Some(Utc::now());
-        listing.buyer_address = Some(buyer_address.to_string());
-        listing.purchase_txid = Some(purchase_txid.to_string());
-        listing.updated_at = Utc::now();
+        // The ordinal-lock script pays out the price it was built with
+        if listing.listing_utxo.is_some() {
+            anyhow::bail!("Listing is locked on-chain at its current price; cancel and relist to change it");
+        }
+
+        if listing.auction.is_some() {
+            anyhow::bail!("Auction listings are priced by their bids");
+        }
+
+        if listing.accepted_offer.as_ref().is_some_and(|a| a.expires_at > Utc::now()) {
+            anyhow::bail!("Listing is reserved for an accepted offer until it expires");
+        }
+        listing.accepted_offer = None;
 
-        self.update_listing(&listing)?;
+        let tip_percent = tip_percent.unwrap_or(listing.fees.tip_percent);
+        listing.fees = ListingFees::calculate(seller_wants_satoshis, self.marketplace_fee_percent, tip_percent);
+        listing.fiat_price = None;
+        listing.updated_at = Utc::now();
 
-        // Remove from origin index
-        let origin_key = format!("listing_by_origin:{}", listing.origin);
-        self.db.remove(origin_key.as_bytes())?;
+        self.update_listing(&mut listing)?;
 
-        info!("Listing {} sold to {} in tx {}", id, buyer_address, purchase_txid);
+        info!("Repriced listing {} to {} sats", id, listing.fees.total_price);
+        self.publish(ListingEvent::Repriced(listing.clone()));
         Ok(Some(listing))
     }
 
-    /// Get all active listings
-    pub fn get_active_listings(&self, page: usize, per_page: usize) -> Result<(Vec<Listing>, usize)> {
-        let mut listings = Vec::new();
-        
-        for item in self.db.scan_prefix(b"listing:") {
-            if let Ok((_, value)) = item {
-                if let Ok(listing) = serde_json::from_slice::<Listing>(&value) {
-                    if listing.status == ListingStatus::Active {
-                        listings.push(listing);
+    /// Record a new pending offer on `listing`
+    pub fn create_offer(
+        &self,
+        listing: &Listing,
+        buyer_ord_address: &str,
+        amount_sats: u64,
+        expires_at: DateTime<Utc>,
+    ) -> Result<Offer> {
+        let now = Utc::now();
+        let offer = Offer {
+            id: Uuid::new_v4().to_string(),
+            listing_id: listing.id.clone(),
+            buyer_ord_address: buyer_ord_address.to_string(),
+            amount_sats,
+            status: OfferStatus::Pending,
+            expires_at,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let key = format!("offer:{}", offer.id);
+        let value = serde_json::to_vec(&offer).context("Failed to serialize offer")?;
+        let index_key = format!("offer_by_listing:{}:{}", offer.listing_id, offer.id);
+
+        self.db
+            .transaction(|tx| {
+                tx.insert(key.as_bytes(), value.as_slice())?;
+                tx.insert(index_key.as_bytes(), offer.id.as_bytes())?;
+                Ok::<_, ConflictableTransactionError>(())
+            })
+            .context("Failed to insert offer")?;
+
+        info!("Offer {} of {} sats on listing {}", offer.id, amount_sats, listing.id);
+        Ok(offer)
+    }
+
+    /// Get an offer by ID
+    pub fn get_offer(&self, id: &str) -> Result<Option<Offer>> {
+        match self.db.get(format!("offer:{}", id).as_bytes())? {
+            Some(bytes) => Ok(Some(serde_json::from_slice(&bytes).context("Failed to deserialize offer")?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Offers on a listing, newest first
+    pub fn get_offers_for_listing(&self, listing_id: &str) -> Result<Vec<Offer>> {
+        let prefix = format!("offer_by_listing:{}:", listing_id);
+        let mut offers = Vec::new();
+
+        for (_, id_bytes) in self.db.scan_prefix(prefix.as_bytes()).flatten() {
+            if let Some(offer) = self.get_offer(&String::from_utf8_lossy(&id_bytes))? {
+                offers.push(offer);
+            }
+        }
+
+        offers.sort_by_key(|o| std::cmp::Reverse(o.created_at));
+        Ok(offers)
+    }
+
+    fn store_offer(&self, offer: &Offer) -> Result<()> {
+        let value = serde_json::to_vec(offer).context("Failed to serialize offer")?;
+        self.db
+            .insert(format!("offer:{}", offer.id).as_bytes(), value)
+            .context("Failed to update offer")?;
+        Ok(())
+    }
+
+    /// Load an offer for the seller to respond to, checking it is still pending
+    /// and that `seller_ord_address` owns its listing
+    fn offer_for_response(&self, offer_id: &str, seller_ord_address: &str) -> Result<Option<(Offer, Listing)>> {
+        let Some(offer) = self.get_offer(offer_id)? else {
+            return Ok(None);
+        };
+
+        match offer.status_at(Utc::now()) {
+            OfferStatus::Pending => {}
+            OfferStatus::Expired => anyhow::bail!("Offer has expired"),
+            _ => anyhow::bail!("Offer has already been answered"),
+        }
+
+        let listing = self
+            .get_listing(&offer.listing_id)?
+            .context("The offer's listing no longer exists")?;
+        if listing.seller_ord_address != seller_ord_address {
+            anyhow::bail!("Not authorized to respond to this offer");
+        }
+
+        Ok(Some((offer, listing)))
+    }
+
+    /// Accept a pending offer: the listing is repriced to the offer amount and
+    /// reserved for the offer's buyer until the offer expires, and the listing's
+    /// other pending offers are rejected. Settlement is an ordinary purchase.
+    pub fn accept_offer(&self, offer_id: &str, seller_ord_address: &str) -> Result<Option<(Offer, Listing)>> {
+        let Some((mut offer, mut listing)) = self.offer_for_response(offer_id, seller_ord_address)? else {
+            return Ok(None);
+        };
+
+        if listing.status != ListingStatus::Active {
+            return Err(ListingWriteError::NotActive.into());
+        }
+
+        // The ordinal-lock script pays out the asking price it was built with
+        if listing.listing_utxo.is_some() {
+            anyhow::bail!("Listing is locked on-chain at its asking price; offers can't be accepted");
+        }
+
+        let now = Utc::now();
+        let ask_fees = match listing.accepted_offer.take() {
+            Some(reserved) if reserved.expires_at > now => {
+                anyhow::bail!("Listing is already reserved for another accepted offer");
+            }
+            // A lapsed reservation still holds the real asking price
+            Some(lapsed) => lapsed.ask_fees,
+            None => listing.fees.clone(),
+        };
+
+        listing.fees = ListingFees::calculate(offer.amount_sats, self.marketplace_fee_percent, ask_fees.tip_percent);
+        listing.accepted_offer = Some(AcceptedOffer {
+            offer_id: offer.id.clone(),
+            buyer_ord_address: offer.buyer_ord_address.clone(),
+            expires_at: offer.expires_at,
+            ask_fees,
+        });
+        listing.updated_at = now;
+        self.update_listing(&mut listing)?;
+
+        offer.status = OfferStatus::Accepted;
+        offer.updated_at = now;
+        self.store_offer(&offer)?;
+
+        // Nothing else can be accepted while the listing is reserved
+        for mut other in self.get_offers_for_listing(&listing.id)? {
+            if other.id != offer.id && other.status == OfferStatus::Pending {
+                other.status = OfferStatus::Rejected;
+                other.updated_at = now;
+                self.store_offer(&other)?;
+            }
+        }
+
+        info!("Accepted offer {} on listing {} at {} sats", offer.id, listing.id, offer.amount_sats);
+        Ok(Some((offer, listing)))
+    }
+
+    /// Reject a pending offer
+    pub fn reject_offer(&self, offer_id: &str, seller_ord_address: &str) -> Result<Option<Offer>> {
+        let Some((mut offer, _)) = self.offer_for_response(offer_id, seller_ord_address)? else {
+            return Ok(None);
+        };
+
+        offer.status = OfferStatus::Rejected;
+        offer.updated_at = Utc::now();
+        self.store_offer(&offer)?;
+
+        info!("Rejected offer {} on listing {}", offer.id, offer.listing_id);
+        Ok(Some(offer))
+    }
+
+    /// If `listing`'s accepted offer has expired, restore its asking price and
+    /// mark the offer expired. Returns whether a reservation was released.
+    pub fn release_lapsed_offer(&self, listing: &mut Listing) -> Result<bool> {
+        let now = Utc::now();
+        let Some(lapsed) = listing.accepted_offer.take_if(|a| a.expires_at <= now) else {
+            return Ok(false);
+        };
+
+        let reserved_fees = std::mem::replace(&mut listing.fees, lapsed.ask_fees.clone());
+        let previous_updated_at = std::mem::replace(&mut listing.updated_at, now);
+        if let Err(e) = self.update_listing(listing) {
+            listing.fees = reserved_fees;
+            listing.updated_at = previous_updated_at;
+            listing.accepted_offer = Some(lapsed);
+            return Err(e);
+        }
+
+        if let Some(mut offer) = self.get_offer(&lapsed.offer_id)? {
+            offer.status = OfferStatus::Expired;
+            offer.updated_at = now;
+            self.store_offer(&offer)?;
+        }
+
+        info!("Reservation for offer {} on listing {} lapsed", lapsed.offer_id, listing.id);
+        Ok(true)
+    }
+
+    /// Hold every listing in a cart for `buyer_ord_address` until `until`, in one
+    /// transaction: if any listing is no longer active or has changed since it was
+    /// read, none of them are held. Bumps each listing's revision on success.
+    pub fn reserve_for_cart(&self, listings: &mut [Listing], buyer_ord_address: &str, until: DateTime<Utc>) -> Result<()> {
+        let now = Utc::now();
+        let mut writes = Vec::with_capacity(listings.len());
+        for listing in listings.iter() {
+            let mut next = listing.clone();
+            next.revision += 1;
+            next.updated_at = now;
+            next.cart_hold = Some(CartHold {
+                buyer_ord_address: buyer_ord_address.to_string(),
+                expires_at: until,
+            });
+            let value = serde_json::to_vec(&next).context("Failed to serialize listing")?;
+            writes.push((format!("listing:{}", listing.id), listing.revision, value));
+        }
+
+        let result = self.db.transaction(|tx| {
+            for (key, expected_revision, value) in &writes {
+                let stored = tx
+                    .get(key.as_bytes())?
+                    .and_then(|bytes| serde_json::from_slice::<Listing>(&bytes).ok());
+                match stored {
+                    Some(l) if l.revision != *expected_revision => {
+                        return Err(ConflictableTransactionError::Abort(ListingWriteError::RevisionConflict));
                     }
+                    Some(l) if l.status == ListingStatus::Active => {}
+                    Some(_) => return Err(ConflictableTransactionError::Abort(ListingWriteError::NotActive)),
+                    None => return Err(ConflictableTransactionError::Abort(ListingWriteError::RevisionConflict)),
+                }
+                tx.insert(key.as_bytes(), value.as_slice())?;
+            }
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {}
+            Err(TransactionError::Abort(e)) => return Err(e.into()),
+            Err(TransactionError::Storage(e)) => return Err(anyhow::Error::new(e).context("Failed to hold listings")),
+        }
+
+        for listing in listings.iter_mut() {
+            listing.revision += 1;
+            listing.updated_at = now;
+            listing.cart_hold = Some(CartHold {
+                buyer_ord_address: buyer_ord_address.to_string(),
+                expires_at: until,
+            });
+        }
+        info!("Held {} listings for cart of {} until {}", listings.len(), buyer_ord_address, until);
+        Ok(())
+    }
+
+    /// Bid on an open auction. The first bid must reach the starting price and
+    /// each later one must beat the current high bid.
+    pub fn place_bid(&self, id: &str, bidder_ord_address: &str, amount_sats: u64) -> Result<Option<Listing>> {
+        let mut listing = match self.get_listing(id)? {
+            Some(l) => l,
+            None => return Ok(None),
+        };
+
+        if listing.status != ListingStatus::Active {
+            return Err(ListingWriteError::NotActive.into());
+        }
+
+        let now = Utc::now();
+        let Some(auction) = listing.auction.as_mut() else {
+            anyhow::bail!("Listing is not an auction");
+        };
+        if auction.closed_at.is_some() || auction.ends_at <= now {
+            anyhow::bail!("Auction has ended");
+        }
+        if listing.seller_ord_address == bidder_ord_address {
+            anyhow::bail!("Sellers can't bid on their own auction");
+        }
+
+        let minimum = match auction.high_bid {
+            Some(ref high) => high.amount_sats.saturating_add(1),
+            None => auction.start_price_sats,
+        };
+        if amount_sats < minimum {
+            anyhow::bail!("Bid must be at least {} sats", minimum);
+        }
+
+        let bid = Bid {
+            bidder_ord_address: bidder_ord_address.to_string(),
+            amount_sats,
+            placed_at: now,
+        };
+        auction.high_bid = Some(bid.clone());
+        auction.bid_count += 1;
+        listing.updated_at = now;
+        self.update_listing(&mut listing)?;
+
+        // Bid history, in order of placement
+        let key = format!("auction_bid:{}:{:020}:{}", id, now.timestamp_millis(), Uuid::new_v4());
+        let value = serde_json::to_vec(&bid).context("Failed to serialize bid")?;
+        self.db.insert(key.as_bytes(), value).context("Failed to store bid")?;
+
+        info!("Bid of {} sats on auction {}", amount_sats, id);
+        self.publish(ListingEvent::BidPlaced(listing.clone()));
+        Ok(Some(listing))
+    }
+
+    /// Bids placed on an auction listing, highest (latest) first
+    pub fn get_bids(&self, listing_id: &str) -> Result<Vec<Bid>> {
+        let prefix = format!("auction_bid:{}:", listing_id);
+        let mut bids = self.db
+            .scan_prefix(prefix.as_bytes())
+            .flatten()
+            .map(|(_, value)| serde_json::from_slice::<Bid>(&value).context("Failed to deserialize bid"))
+            .collect::<Result<Vec<_>>>()?;
+        bids.reverse();
+        Ok(bids)
+    }
+
+    /// Close an auction whose end time has passed. With bids, the listing is
+    /// repriced to the winning bid and reserved for the winner until
+    /// `settlement_window` from now; without, it expires.
+    pub fn close_auction(&self, id: &str, settlement_window: std::time::Duration) -> Result<Option<Listing>> {
+        let mut listing = match self.get_listing(id)? {
+            Some(l) => l,
+            None => return Ok(None),
+        };
+
+        if listing.status != ListingStatus::Active {
+            return Err(ListingWriteError::NotActive.into());
+        }
+
+        let now = Utc::now();
+        let Some(auction) = listing.auction.as_mut() else {
+            anyhow::bail!("Listing is not an auction");
+        };
+        if auction.closed_at.is_some() || auction.ends_at > now {
+            anyhow::bail!("Auction is not ready to close");
+        }
+        let Some(winning_amount) = auction.high_bid.as_ref().map(|b| b.amount_sats) else {
+            return self.mark_expired(id, "auction ended without bids");
+        };
+
+        auction.closed_at = Some(now);
+        auction.settle_by = Some(now + Duration::from_std(settlement_window).context("Settlement window out of range")?);
+        listing.fees = ListingFees::calculate(winning_amount, self.marketplace_fee_percent, listing.fees.tip_percent);
+        listing.updated_at = now;
+        self.update_listing(&mut listing)?;
+
+        info!("Auction {} closed at {} sats", id, winning_amount);
+        self.publish(ListingEvent::AuctionClosed(listing.clone()));
+        Ok(Some(listing))
+    }
+
+    /// Mark a listing as sold. If another write lands between reading and
+    /// writing the listing, the sale is retried against the fresh record, so a
+    /// concurrent sale surfaces as `ListingWriteError::NotActive`.
+    pub fn mark_listing_sold(
+        &self, 
+        id: &str, 
+        buyer_address: Option<&str>,
+        purchase_txid: &str,
+        broadcast_response: Option<&serde_json::Value>,
+    ) -> Result<Option<Listing>> {
+        for _ in 0..MAX_SALE_ATTEMPTS {
+            let mut listing = match self.get_listing(id)? {
+                Some(l) => l,
+                None => return Ok(None),
+            };
+
+            if listing.status != ListingStatus::Active {
+                return Err(ListingWriteError::NotActive.into());
+            }
+
+            listing.status = ListingStatus::Sold;
+            listing.sold_at = Some(Utc::now());
+            listing.buyer_address = buyer_address.map(|a| a.to_string());
+            listing.purchase_txid = Some(purchase_txid.to_string());
+            listing.broadcast_response = broadcast_response.cloned();
+            listing.updated_at = Utc::now();
+
+            match self.store_deactivated(&mut listing) {
+                Ok(()) => {}
+                Err(e) if is_revision_conflict(&e) => {
+                    debug!("Revision conflict marking listing {} sold, retrying", id);
+                    continue;
                 }
+                Err(e) => return Err(e),
             }
+
+            info!(
+                "Listing {} sold to {} in tx {}",
+                id, buyer_address.unwrap_or("unknown buyer"), purchase_txid
+            );
+            self.publish(ListingEvent::Sold(listing.clone()));
+            return Ok(Some(listing));
         }
 
-        // Sort by created_at descending
-        listings.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Err(ListingWriteError::RevisionConflict.into())
+    }
+
+    /// Get all active listings
+    pub fn get_active_listings(&self, page: usize, per_page: usize) -> Result<(Vec<Listing>, usize)> {
+        let listings = self.scan_active_index();
         
         let total = listings.len();
         
         // Paginate
-        let start = (page - 1) * per_page;
+        let start = page_start(page, per_page);
         let paginated: Vec<Listing> = listings
             .into_iter()
             .skip(start)
@@ -196,49 +943,792 @@ impl ListingsDb {
         Ok((paginated, total))
     }
 
+    /// Get active listings matching every `(key, value)` attribute filter
+    pub fn get_listings_by_attributes(
+        &self,
+        filters: &[(String, String)],
+        page: usize,
+        per_page: usize,
+    ) -> Result<(Vec<Listing>, usize)> {
+        let mut matching: Option<HashSet<String>> = None;
+
+        for (key, value) in filters {
+            let prefix = format!("listing_by_attr:{}={}:", key.to_lowercase(), value.to_lowercase());
+            let ids: HashSet<String> = self.db
+                .scan_prefix(prefix.as_bytes())
+                .flatten()
+                .map(|(_, id)| String::from_utf8_lossy(&id).into_owned())
+                .collect();
+
+            matching = Some(match matching {
+                Some(current) => current.intersection(&ids).cloned().collect(),
+                None => ids,
+            });
+        }
+
+        let mut listings = Vec::new();
+        for id in matching.unwrap_or_default() {
+            if let Some(listing) = self.get_listing(&id)? {
+                if listing.status == ListingStatus::Active {
+                    listings.push(listing);
+                }
+            }
+        }
+
+        listings.sort_by_key(|l| std::cmp::Reverse(l.created_at));
+
+        let total = listings.len();
+        let start = page_start(page, per_page);
+        let paginated = listings.into_iter().skip(start).take(per_page).collect();
+
+        Ok((paginated, total))
+    }
+
+    /// Get active listings in a collection, newest first
+    pub fn get_listings_by_collection(
+        &self,
+        collection_id: &str,
+        page: usize,
+        per_page: usize,
+    ) -> Result<(Vec<Listing>, usize)> {
+        let prefix = format!("listing_by_collection:{}:", collection_id);
+        let mut listings = Vec::new();
+
+        for (_, id_bytes) in self.db.scan_prefix(prefix.as_bytes()).flatten() {
+            let id = String::from_utf8_lossy(&id_bytes);
+            if let Some(listing) = self.get_listing(&id)? {
+                if listing.status == ListingStatus::Active {
+                    listings.push(listing);
+                }
+            }
+        }
+
+        listings.sort_by_key(|l| std::cmp::Reverse(l.created_at));
+
+        let total = listings.len();
+        let start = page_start(page, per_page);
+        let paginated = listings.into_iter().skip(start).take(per_page).collect();
+
+        Ok((paginated, total))
+    }
+
+    /// Collections with at least one active listing, most listings first
+    pub fn list_collections(&self, page: usize, per_page: usize) -> Result<(Vec<CollectionSummary>, usize)> {
+        let active_ids: HashSet<String> = self.db
+            .scan_prefix(ACTIVE_INDEX_PREFIX)
+            .flatten()
+            .map(|(_, id)| String::from_utf8_lossy(&id).into_owned())
+            .collect();
+
+        // collection_id -> (active listing count, a sample listing id)
+        let mut counts: HashMap<String, (usize, String)> = HashMap::new();
+        for (key, id_bytes) in self.db.scan_prefix(b"listing_by_collection:").flatten() {
+            let id = String::from_utf8_lossy(&id_bytes).into_owned();
+            if !active_ids.contains(&id) {
+                continue;
+            }
+            let key = String::from_utf8_lossy(&key);
+            let Some((collection_id, _)) = key["listing_by_collection:".len()..].rsplit_once(':') else {
+                continue;
+            };
+            counts
+                .entry(collection_id.to_string())
+                .or_insert_with(|| (0, id))
+                .0 += 1;
+        }
+
+        let mut ranked: Vec<(String, usize, String)> = counts
+            .into_iter()
+            .map(|(collection_id, (count, sample_id))| (collection_id, count, sample_id))
+            .collect();
+        ranked.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let total = ranked.len();
+        let mut collections = Vec::new();
+        for (collection_id, listing_count, sample_id) in ranked.into_iter().skip(page_start(page, per_page)).take(per_page) {
+            let Some(sample) = self.get_listing(&sample_id)? else {
+                continue;
+            };
+            collections.push(CollectionSummary {
+                collection_id,
+                listing_count,
+                sample_preview_url: format!("/ordinal/{}/preview", sample.origin),
+                sample_origin: sample.origin,
+            });
+        }
+
+        Ok((collections, total))
+    }
+
+    /// Most recently sold listings, newest first
+    pub fn get_recent_sales(&self, limit: usize) -> Result<Vec<Listing>> {
+        let mut sales = Vec::new();
+
+        for (_, id_bytes) in self.db.scan_prefix(SOLD_INDEX_PREFIX).flatten() {
+            if sales.len() >= limit {
+                break;
+            }
+            let id = String::from_utf8_lossy(&id_bytes);
+            if let Some(listing) = self.get_listing(&id)? {
+                sales.push(listing);
+            }
+        }
+
+        Ok(sales)
+    }
+
     /// Get listings by seller
     pub fn get_listings_by_seller(&self, seller_address: &str) -> Result<Vec<Listing>> {
         let prefix = format!("listing_by_seller:{}:", seller_address);
         let mut listings = Vec::new();
         
-        for item in self.db.scan_prefix(prefix.as_bytes()) {
-            if let Ok((_, id_bytes)) = item {
-                let id = String::from_utf8_lossy(&id_bytes);
-                if let Ok(Some(listing)) = self.get_listing(&id) {
-                    listings.push(listing);
-                }
+        for (_, id_bytes) in self.db.scan_prefix(prefix.as_bytes()).flatten() {
+            let id = String::from_utf8_lossy(&id_bytes);
+            if let Ok(Some(listing)) = self.get_listing(&id) {
+                listings.push(listing);
             }
         }
 
-        listings.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        listings.sort_by_key(|l| std::cmp::Reverse(l.created_at));
         Ok(listings)
     }
 
+    /// Get sold listings bought by `buyer_address`, newest sale first
+    pub fn get_purchases_by_buyer(&self, buyer_address: &str) -> Result<Vec<Listing>> {
+        let mut purchases = Vec::new();
+
+        for (_, id_bytes) in self.db.scan_prefix(SOLD_INDEX_PREFIX).flatten() {
+            let id = String::from_utf8_lossy(&id_bytes);
+            if let Some(listing) = self.get_listing(&id)? {
+                if listing.buyer_address.as_deref() == Some(buyer_address) {
+                    purchases.push(listing);
+                }
+            }
+        }
+
+        Ok(purchases)
+    }
+
+    /// Add a listing to `address`'s watchlist. Returns false if it was already watched,
+    /// keeping the original `watched_at`.
+    pub fn watch_listing(&self, address: &str, listing_id: &str) -> Result<bool> {
+        let now = Utc::now().to_rfc3339();
+        let swapped = self.db
+            .compare_and_swap(watch_key(address, listing_id).as_bytes(), None as Option<&[u8]>, Some(now.as_bytes()))
+            .context("Failed to update watchlist")?;
+        Ok(swapped.is_ok())
+    }
+
+    /// Remove a listing from `address`'s watchlist. Returns false if it wasn't watched.
+    pub fn unwatch_listing(&self, address: &str, listing_id: &str) -> Result<bool> {
+        let removed = self.db
+            .remove(watch_key(address, listing_id).as_bytes())
+            .context("Failed to update watchlist")?;
+        Ok(removed.is_some())
+    }
+
+    /// Number of listings `address` watches
+    pub fn watchlist_len(&self, address: &str) -> usize {
+        self.db.scan_prefix(format!("watch:{}:", address).as_bytes()).count()
+    }
+
+    /// Listings `address` watches, most recently watched first, each joined to its
+    /// current state (`None` once the listing has been pruned)
+    pub fn get_watchlist(&self, address: &str) -> Result<Vec<WatchedListing>> {
+        let prefix = format!("watch:{}:", address);
+        let mut watched = Vec::new();
+
+        for (key, value) in self.db.scan_prefix(prefix.as_bytes()).flatten() {
+            let listing_id = String::from_utf8_lossy(&key[prefix.len()..]).into_owned();
+            let watched_at = std::str::from_utf8(&value)
+                .ok()
+                .and_then(|s| chrono::DateTime::parse_from_rfc3339(s).ok())
+                .map(|t| t.with_timezone(&Utc));
+            let listing = self.get_listing(&listing_id)?;
+            watched.push(WatchedListing { listing_id, watched_at, listing });
+        }
+
+        watched.sort_by_key(|w| std::cmp::Reverse(w.watched_at));
+        Ok(watched)
+    }
+
+    /// Delete sold, cancelled and expired listings last updated before `older_than` ago,
+    /// along with their index entries, offers and bids. With `archive_sold`, each pruned sale is
+    /// first summarized under `sold_archive:<id>`. Returns how many were pruned.
+    pub fn prune_closed(&self, older_than: std::time::Duration, archive_sold: bool) -> Result<usize> {
+        let cutoff = Utc::now() - Duration::from_std(older_than).context("Prune age out of range")?;
+
+        let closed: Vec<Listing> = self.db
+            .scan_prefix(b"listing:")
+            .flatten()
+            .filter_map(|(_, value)| serde_json::from_slice::<Listing>(&value).ok())
+            .filter(|l| l.status != ListingStatus::Active && l.updated_at < cutoff)
+            .collect();
+
+        for listing in &closed {
+            let key = format!("listing:{}", listing.id);
+            let mut index_keys = vec![
+                format!("listing_by_seq:{:020}", listing.seq),
+                format!("listing_by_seller:{}:{}", listing.seller_address, listing.id),
+            ];
+            index_keys.extend(
+                listing.attributes
+                    .iter()
+                    .map(|(key, value)| format!("listing_by_attr:{}={}:{}", key, value, listing.id)),
+            );
+            index_keys.extend(collection_index_key(listing));
+            // Offers and bids go with their listing
+            for (index_key, offer_id) in self.db
+                .scan_prefix(format!("offer_by_listing:{}:", listing.id).as_bytes())
+                .flatten()
+            {
+                index_keys.push(String::from_utf8_lossy(&index_key).into_owned());
+                index_keys.push(format!("offer:{}", String::from_utf8_lossy(&offer_id)));
+            }
+            index_keys.extend(
+                self.db
+                    .scan_prefix(format!("auction_bid:{}:", listing.id).as_bytes())
+                    .keys()
+                    .flatten()
+                    .map(|key| String::from_utf8_lossy(&key).into_owned()),
+            );
+
+            let archive = if listing.status == ListingStatus::Sold {
+                index_keys.push(sold_index_key(listing));
+                let entry = SoldArchiveEntry {
+                    listing_id: listing.id.clone(),
+                    origin: listing.origin.clone(),
+                    price_sats: listing.fees.total_price,
+                    purchase_txid: listing.purchase_txid.clone(),
+                    sold_at: listing.sold_at,
+                };
+                archive_sold
+                    .then(|| serde_json::to_vec(&entry))
+                    .transpose()
+                    .context("Failed to serialize sale archive")?
+            } else {
+                None
+            };
+            let archive_key = format!("sold_archive:{}", listing.id);
+            let origin_key = format!("listing_by_origin:{}", listing.origin);
+
+            self.db
+                .transaction(|tx| {
+                    if let Some(ref archive) = archive {
+                        tx.insert(archive_key.as_bytes(), archive.as_slice())?;
+                    }
+                    // Leave the origin index alone if the ordinal has been relisted since
+                    if tx.get(origin_key.as_bytes())?.is_some_and(|id| id.as_ref() == listing.id.as_bytes()) {
+                        tx.remove(origin_key.as_bytes())?;
+                    }
+                    tx.remove(key.as_bytes())?;
+                    for index_key in &index_keys {
+                        tx.remove(index_key.as_bytes())?;
+                    }
+                    Ok::<_, ConflictableTransactionError>(())
+                })
+                .context("Failed to prune listing")?;
+        }
+
+        info!("Pruned {} closed listings last updated before {}", closed.len(), cutoff);
+        Ok(closed.len())
+    }
+
+    /// Claim an idempotency key on `listing_id` by writing a pending marker, unless
+    /// another request holds it or has already finished with it. Expired records
+    /// and abandoned claims are taken over.
+    pub fn claim_idempotency_key(&self, listing_id: &str, key: &str) -> Result<IdempotencyClaim> {
+        let db_key = format!("idempotency:{}:{}", listing_id, key);
+        let pending = serde_json::to_vec(&serde_json::json!({
+            "pending": true,
+            "stored_at": Utc::now().to_rfc3339(),
+        }))?;
+
+        let mut current = self.db.get(db_key.as_bytes())?;
+        loop {
+            if let Some(ref bytes) = current {
+                let record: serde_json::Value = serde_json::from_slice(bytes)
+                    .context("Failed to deserialize idempotency record")?;
+                let age = record["stored_at"]
+                    .as_str()
+                    .and_then(|t| chrono::DateTime::parse_from_rfc3339(t).ok())
+                    .map(|t| Utc::now() - t.with_timezone(&Utc));
+                let in_flight = record["pending"].as_bool() == Some(true);
+                let ttl = if in_flight {
+                    Duration::minutes(IDEMPOTENCY_PENDING_TTL_MINS)
+                } else {
+                    Duration::hours(IDEMPOTENCY_TTL_HOURS)
+                };
+
+                match age {
+                    Some(age) if age < ttl && in_flight => return Ok(IdempotencyClaim::InFlight),
+                    Some(age) if age < ttl => return Ok(IdempotencyClaim::Completed(record["response"].clone())),
+                    _ => {}
+                }
+            }
+
+            match self.db.compare_and_swap(db_key.as_bytes(), current.clone(), Some(pending.as_slice()))? {
+                Ok(()) => return Ok(IdempotencyClaim::Claimed),
+                // Someone else wrote the key first; decide against what they wrote
+                Err(cas) => current = cas.current,
+            }
+        }
+    }
+
+    /// Give up a claimed idempotency key without storing a response, so the
+    /// request can be retried with it
+    pub fn release_idempotency_key(&self, listing_id: &str, key: &str) -> Result<()> {
+        let db_key = format!("idempotency:{}:{}", listing_id, key);
+        let Some(bytes) = self.db.get(db_key.as_bytes())? else {
+            return Ok(());
+        };
+        let record: serde_json::Value = serde_json::from_slice(&bytes)
+            .context("Failed to deserialize idempotency record")?;
+        if record["pending"].as_bool() == Some(true) {
+            // Only drop the marker we read, never a response stored since
+            let _ = self.db.compare_and_swap(db_key.as_bytes(), Some(bytes), None::<&[u8]>)?;
+        }
+        Ok(())
+    }
+
+    /// Remember the response sent for an idempotency key on `listing_id`,
+    /// replacing its pending claim
+    pub fn store_idempotent_response(&self, listing_id: &str, key: &str, response: &serde_json::Value) -> Result<()> {
+        let db_key = format!("idempotency:{}:{}", listing_id, key);
+        let record = serde_json::json!({
+            "stored_at": Utc::now().to_rfc3339(),
+            "response": response,
+        });
+        self.db
+            .insert(db_key.as_bytes(), serde_json::to_vec(&record)?)
+            .context("Failed to store idempotency record")?;
+        Ok(())
+    }
+
+    /// Compute marketplace-wide statistics in a single pass over all listings
+    pub fn aggregate_stats(&self) -> MarketplaceStats {
+        let mut stats = MarketplaceStats::default();
+        let mut sellers = HashSet::new();
+
+        for (_, value) in self.db.scan_prefix(b"listing:").flatten() {
+            if let Ok(listing) = serde_json::from_slice::<Listing>(&value) {
+                match listing.status {
+                    ListingStatus::Active => stats.active_listings += 1,
+                    ListingStatus::Sold => {
+                        stats.sold_listings += 1;
+                        stats.total_volume_sats += listing.fees.total_price;
+                    }
+                    ListingStatus::Cancelled => stats.cancelled_listings += 1,
+                    ListingStatus::Expired => stats.expired_listings += 1,
+                }
+                sellers.insert(listing.seller_address);
+            }
+        }
+
+        if stats.sold_listings > 0 {
+            stats.average_sale_price_sats = stats.total_volume_sats / stats.sold_listings as u64;
+        }
+        stats.distinct_sellers = sellers.len();
+        stats.computed_at = Utc::now();
+        stats
+    }
+
     /// Count active listings
     pub fn count_active_listings(&self) -> usize {
-        let mut count = 0;
-        for item in self.db.scan_prefix(b"listing:") {
-            if let Ok((_, value)) = item {
-                if let Ok(listing) = serde_json::from_slice::<Listing>(&value) {
-                    if listing.status == ListingStatus::Active {
-                        count += 1;
-                    }
+        self.scan_active_index().len()
+    }
+
+    /// Active listings newest first, read through the active index. Listings past
+    /// their expiry or the maximum lifetime are expired and skipped.
+    fn scan_active_index(&self) -> Vec<Listing> {
+        let mut listings = Vec::new();
+
+        for (_, id_bytes) in self.db.scan_prefix(ACTIVE_INDEX_PREFIX).flatten() {
+            let id = String::from_utf8_lossy(&id_bytes);
+            let listing = match self.get_listing(&id) {
+                Ok(Some(l)) if l.status == ListingStatus::Active => l,
+                Ok(_) => continue,
+                Err(e) => {
+                    error!("Failed to read active listing {}: {}", id, e);
+                    continue;
+                }
+            };
+            if let Some(reason) = self.expiry_reason(&listing) {
+                if let Err(e) = self.mark_expired(&listing.id, reason) {
+                    error!("Failed to expire listing {}: {}", listing.id, e);
+                }
+                continue;
+            }
+            listings.push(listing);
+        }
+
+        listings
+    }
+
+    /// Populate the active index from the main records if this database predates
+    /// it. Returns how many listings were indexed.
+    pub fn ensure_active_index(&self) -> Result<usize> {
+        if self.db.contains_key(ACTIVE_INDEX_BUILT_KEY)? {
+            return Ok(0);
+        }
+
+        let mut indexed = 0;
+        for (_, value) in self.db.scan_prefix(b"listing:").flatten() {
+            if let Ok(listing) = serde_json::from_slice::<Listing>(&value) {
+                if listing.status == ListingStatus::Active {
+                    self.db.insert(active_index_key(&listing).as_bytes(), listing.id.as_bytes())
+                        .context("Failed to insert active index")?;
+                    indexed += 1;
+                }
+            }
+        }
+
+        self.db.insert(ACTIVE_INDEX_BUILT_KEY, &[1u8])?;
+        info!("Built active listing index ({} listings)", indexed);
+        Ok(indexed)
+    }
+
+    /// Verify the origin and seller indexes agree with the main listing records
+    pub fn check_index_consistency(&self) -> IndexConsistency {
+        let mut report = IndexConsistency::default();
+
+        for (key, id_bytes) in self.db.scan_prefix(b"listing_by_origin:").flatten() {
+            report.origin_index_entries += 1;
+            let key = String::from_utf8_lossy(&key);
+            let origin = key.trim_start_matches("listing_by_origin:");
+            let id = String::from_utf8_lossy(&id_bytes);
+
+            match self.get_listing(&id) {
+                Ok(Some(listing)) if listing.origin != origin => {
+                    report.problems.push(format!("{} points to listing {} for origin {}", key, id, listing.origin));
                 }
+                Ok(Some(_)) => {}
+                Ok(None) => report.problems.push(format!("{} points to missing listing {}", key, id)),
+                Err(e) => report.problems.push(format!("{}: {}", key, e)),
             }
         }
-        count
+
+        for (key, id_bytes) in self.db.scan_prefix(b"listing_by_seller:").flatten() {
+            report.seller_index_entries += 1;
+            let id = String::from_utf8_lossy(&id_bytes);
+
+            if !matches!(self.get_listing(&id), Ok(Some(_))) {
+                let key = String::from_utf8_lossy(&key);
+                report.problems.push(format!("{} points to missing listing {}", key, id));
+            }
+        }
+
+        report.consistent = report.problems.is_empty();
+        report
+    }
+
+    /// Cheap read proving the database is usable
+    pub fn probe(&self) -> Result<()> {
+        self.db.get(LISTING_SEQ_KEY).context("Database read failed")?;
+        Ok(())
+    }
+
+    /// Size of the database on disk in bytes
+    pub fn size_on_disk(&self) -> Result<u64> {
+        self.db.size_on_disk().context("Failed to read database size")
     }
 
     /// Check if an origin is already listed
     pub fn is_origin_listed(&self, origin: &str) -> Result<bool> {
-        Ok(self.get_listing_by_origin(origin)?.is_some())
+        Ok(self.get_listing_by_origin(origin, false)?.is_some())
     }
 }
 
+/// Whether `e` is a lost race with another listing write
+pub fn is_revision_conflict(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<ListingWriteError>() == Some(&ListingWriteError::RevisionConflict)
+}
+
+/// Offset of the first item on 1-based `page`. Page 0 is treated as page 1
+/// rather than underflowing.
+pub fn page_start(page: usize, per_page: usize) -> usize {
+    page.saturating_sub(1).saturating_mul(per_page)
+}
+
+/// Bring a listing record up to `LISTING_SCHEMA_VERSION`, returning whether it
+/// changed. Fields added since v0 are filled by their serde defaults on load, so
+/// for now this only stamps the version.
+fn upgrade_listing(listing: &mut Listing) -> bool {
+    if listing.schema_version >= LISTING_SCHEMA_VERSION {
+        return false;
+    }
+    listing.schema_version = LISTING_SCHEMA_VERSION;
+    true
+}
+
+/// Listing ID for a create carrying an idempotency key: a UUID derived from the
+/// origin, seller and key, so the same create always maps to the same listing
+pub fn listing_id_for_key(origin: &str, seller_ord_address: &str, key: &str) -> String {
+    let digest = sha256::Hash::hash(format!("{}:{}:{}", origin, seller_ord_address, key).as_bytes());
+    let mut bytes = [0u8; 16];
+    bytes.copy_from_slice(&digest.to_byte_array()[..16]);
+    uuid::Builder::from_custom_bytes(bytes).into_uuid().to_string()
+}
+
+/// Active index key; the timestamp is inverted so a forward scan yields newest first
+fn active_index_key(listing: &Listing) -> String {
+    let created_ms = listing.created_at.timestamp_millis().max(0) as u64;
+    format!("listing_by_active:{:020}:{}", u64::MAX - created_ms, listing.id)
+}
+
+/// Watchlist entry key, `watch:<address>:<listing id>`
+fn watch_key(address: &str, listing_id: &str) -> String {
+    format!("watch:{}:{}", address, listing_id)
+}
+
+/// Sold index key; the timestamp is inverted so a forward scan yields newest first
+fn sold_index_key(listing: &Listing) -> String {
+    let sold_ms = listing.sold_at.map(|t| t.timestamp_millis().max(0) as u64).unwrap_or(0);
+    format!("{}{:020}:{}", SOLD_INDEX_PREFIX, u64::MAX - sold_ms, listing.id)
+}
+
+/// Collection index key, if the listed ordinal belongs to a collection
+fn collection_index_key(listing: &Listing) -> Option<String> {
+    listing
+        .collection_id
+        .as_ref()
+        .map(|collection_id| format!("listing_by_collection:{}:{}", collection_id, listing.id))
+}
+
 impl Clone for ListingsDb {
     fn clone(&self) -> Self {
         Self {
             db: Arc::clone(&self.db),
+            max_listing_lifetime: self.max_listing_lifetime,
+            events: self.events.clone(),
+            migrate_on_read: self.migrate_on_read,
+            marketplace_fee_percent: self.marketplace_fee_percent,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::attributes_from_metadata;
+    use crate::test_support::{address, create_listing, listing_request, listings_db};
+
+    /// Create listing `n` with traits from MAP `metadata`
+    fn create_with_traits(db: &ListingsDb, n: u8, metadata: serde_json::Value) -> Listing {
+        db.create_listing(listing_request(n, 10_000), attributes_from_metadata(&metadata), None, None)
+            .unwrap()
+    }
+
+    fn attr_filters(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn attribute_filters_combine_with_and() {
+        let db = listings_db();
+        let blue_red = create_with_traits(&db, 1, serde_json::json!({
+            "app": "ord",
+            "subTypeData": { "traits": [
+                { "name": "Background", "value": "Blue" },
+                { "name": "Eyes", "value": "Red" },
+            ]},
+        }));
+        let blue_green = create_with_traits(&db, 2, serde_json::json!({
+            "subTypeData": { "traits": [
+                { "trait_type": "background", "value": "blue" },
+                { "trait_type": "eyes", "value": "green" },
+            ]},
+        }));
+        create_with_traits(&db, 3, serde_json::json!({ "background": "red" }));
+
+        let ids = |filters: &[(&str, &str)]| {
+            let (listings, total) = db.get_listings_by_attributes(&attr_filters(filters), 1, 50).unwrap();
+            assert_eq!(total, listings.len());
+            let mut ids: Vec<String> = listings.into_iter().map(|l| l.id).collect();
+            ids.sort();
+            ids
+        };
+        let mut both_blue = vec![blue_red.id.clone(), blue_green.id.clone()];
+        both_blue.sort();
+
+        assert_eq!(ids(&[("background", "blue")]), both_blue);
+        assert_eq!(ids(&[("Background", "BLUE")]), both_blue);
+        assert_eq!(ids(&[("background", "blue"), ("eyes", "green")]), [blue_green.id]);
+        assert!(ids(&[("background", "red"), ("eyes", "green")]).is_empty());
+    }
+
+    #[test]
+    fn concurrent_creates_get_distinct_sequence_numbers() {
+        let db = listings_db();
+
+        let handles: Vec<_> = (1..=16u8)
+            .map(|n| {
+                let db = db.clone();
+                std::thread::spawn(move || create_listing(&db, n, 10_000))
+            })
+            .collect();
+        let listings: Vec<Listing> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        let mut seqs: Vec<u64> = listings.iter().map(|l| l.seq).collect();
+        seqs.sort_unstable();
+        assert_eq!(seqs, (1..=16).collect::<Vec<u64>>());
+
+        for listing in &listings {
+            let found = db.get_listing_by_seq(listing.seq).unwrap().unwrap();
+            assert_eq!(found.id, listing.id);
+        }
+        assert!(db.get_listing_by_seq(17).unwrap().is_none());
+
+        // Later creations keep counting up
+        assert_eq!(create_listing(&db, 17, 10_000).seq, 17);
+    }
+
+    /// Push `listing`'s creation back by `age`
+    fn backdate(db: &ListingsDb, listing: &Listing, age: Duration) {
+        let mut stored = db.get_listing(&listing.id).unwrap().unwrap();
+        stored.created_at -= age;
+        db.update_listing(&mut stored).unwrap();
+    }
+
+    #[test]
+    fn over_age_listings_are_hidden_and_expired_when_read() {
+        let db = listings_db().with_max_listing_lifetime(Some(std::time::Duration::from_secs(3600)));
+        let old = create_listing(&db, 1, 10_000);
+        let fresh = create_listing(&db, 2, 10_000);
+        backdate(&db, &old, Duration::hours(2));
+
+        let (active, total) = db.get_active_listings(1, 50).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(active.iter().map(|l| l.id.as_str()).collect::<Vec<_>>(), [fresh.id.as_str()]);
+
+        let expired = db.get_listing(&old.id).unwrap().unwrap();
+        assert_eq!(expired.status, ListingStatus::Expired);
+        assert_eq!(expired.expired_reason.as_deref(), Some("max listing lifetime exceeded"));
+    }
+
+    #[test]
+    fn listings_within_their_lifetime_stay_active() {
+        let db = listings_db().with_max_listing_lifetime(Some(std::time::Duration::from_secs(3600)));
+        let listing = create_listing(&db, 1, 10_000);
+        backdate(&db, &listing, Duration::minutes(30));
+
+        assert!(db.expiry_reason(&db.get_listing(&listing.id).unwrap().unwrap()).is_none());
+        assert_eq!(db.count_active_listings(), 1);
+    }
+
+    #[test]
+    fn reserve_for_cart_holds_every_listing() {
+        let db = listings_db();
+        let mut listings = vec![create_listing(&db, 1, 10_000), create_listing(&db, 2, 20_000)];
+        let until = Utc::now() + Duration::minutes(10);
+
+        db.reserve_for_cart(&mut listings, &address(9), until).unwrap();
+
+        for listing in &listings {
+            let stored = db.get_listing(&listing.id).unwrap().unwrap();
+            let hold = stored.cart_hold.expect("listing should be held");
+            assert_eq!(hold.buyer_ord_address, address(9));
+            assert_eq!(hold.expires_at, until);
+            assert_eq!(stored.revision, listing.revision);
+        }
+    }
+
+    #[test]
+    fn reserve_for_cart_holds_nothing_if_one_listing_changed() {
+        let db = listings_db();
+        let mut listings = vec![create_listing(&db, 1, 10_000), create_listing(&db, 2, 20_000)];
+
+        // Someone else writes the second listing after the cart read it
+        let mut changed = db.get_listing(&listings[1].id).unwrap().unwrap();
+        db.update_listing(&mut changed).unwrap();
+
+        let err = db
+            .reserve_for_cart(&mut listings, &address(9), Utc::now() + Duration::minutes(10))
+            .unwrap_err();
+        assert!(is_revision_conflict(&err));
+
+        for listing in &listings {
+            assert!(db.get_listing(&listing.id).unwrap().unwrap().cart_hold.is_none());
         }
     }
+
+    #[test]
+    fn indexes_stay_consistent_through_the_listing_lifecycle() {
+        let db = listings_db();
+        let listings: Vec<Listing> = (1..=4).map(|n| create_listing(&db, n, 10_000)).collect();
+
+        db.mark_listing_sold(&listings[0].id, Some(&address(9)), &listings[0].origin, None).unwrap();
+        db.cancel_listing(&listings[1].id, &listings[1].seller_ord_address).unwrap();
+        db.reprice_listing(&listings[2].id, &listings[2].seller_ord_address, 15_000, None).unwrap();
+
+        let report = db.check_index_consistency();
+        assert!(report.consistent, "{:?}", report.problems);
+        assert_eq!(report.seller_index_entries, listings.len());
+
+        // The check does catch an index entry left pointing at a missing record
+        db.db.remove(format!("listing:{}", listings[3].id).as_bytes()).unwrap();
+        let report = db.check_index_consistency();
+        assert!(!report.consistent);
+        assert!(report.problems.iter().any(|p| p.contains(&listings[3].id)));
+    }
+
+    #[test]
+    fn page_zero_reads_the_first_page_instead_of_underflowing() {
+        assert_eq!(page_start(0, 20), 0);
+        assert_eq!(page_start(1, 20), 0);
+        assert_eq!(page_start(3, 20), 40);
+        assert_eq!(page_start(usize::MAX, usize::MAX), usize::MAX);
+
+        let db = listings_db();
+        for n in 1..=3 {
+            create_listing(&db, n, 10_000);
+        }
+        let (page, total) = db.get_active_listings(0, 2).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(page.len(), 2);
+    }
+
+    #[test]
+    fn v0_records_are_upgraded_when_read() {
+        let db = listings_db();
+        let listing = create_listing(&db, 1, 10_000);
+
+        // Rewrite the record as it was stored before versioning
+        let key = format!("listing:{}", listing.id);
+        let mut v0 = serde_json::to_value(&listing).unwrap();
+        v0.as_object_mut().unwrap().remove("schema_version");
+        db.db.insert(key.as_bytes(), serde_json::to_vec(&v0).unwrap()).unwrap();
+        let stored_version = || {
+            let bytes = db.db.get(key.as_bytes()).unwrap().unwrap();
+            serde_json::from_slice::<Listing>(&bytes).unwrap().schema_version
+        };
+
+        assert_eq!(db.get_listing(&listing.id).unwrap().unwrap().schema_version, 0);
+        assert_eq!(stored_version(), 0);
+
+        let migrating = db.clone().with_migrate_on_read(true);
+        let upgraded = migrating.get_listing(&listing.id).unwrap().unwrap();
+        assert_eq!(upgraded.schema_version, LISTING_SCHEMA_VERSION);
+        assert_eq!(upgraded.fees.total_price, listing.fees.total_price);
+        assert_eq!(stored_version(), LISTING_SCHEMA_VERSION);
+    }
+
+    #[test]
+    fn recent_sales_are_newest_first_and_limited() {
+        let db = listings_db();
+        let listings: Vec<Listing> = (1..=4).map(|n| create_listing(&db, n, 10_000)).collect();
+        let unsold = create_listing(&db, 5, 10_000);
+
+        for listing in &listings {
+            db.mark_listing_sold(&listing.id, Some(&address(9)), &listing.origin, None).unwrap();
+            // The sold index orders by millisecond
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let ids = |sales: Vec<Listing>| sales.into_iter().map(|l| l.id).collect::<Vec<_>>();
+        let newest_first: Vec<String> = listings.iter().rev().map(|l| l.id.clone()).collect();
+        assert_eq!(ids(db.get_recent_sales(10).unwrap()), newest_first);
+        assert_eq!(ids(db.get_recent_sales(2).unwrap()), newest_first[..2]);
+        assert!(!ids(db.get_recent_sales(10).unwrap()).contains(&unsold.id));
+    }
 }
@@ -1,8 +1,17 @@
 mod gorillapool;
 mod ordinals;
 mod listings_db;
+mod price_oracle;
+mod purchase_callback;
+pub mod listing_monitor;
+pub mod signing;
+pub mod startup;
+mod upstream_health;
 
 pub use gorillapool::GorillaPoolClient;
-pub use ordinals::OrdinalService;
-pub use listings_db::ListingsDb;
+pub use ordinals::{attributes_from_metadata, OrdinalService};
+pub use listings_db::{is_revision_conflict, listing_id_for_key, page_start, IdempotencyClaim, ListingWriteError, ListingsDb};
+pub use price_oracle::PriceOracle;
+pub use purchase_callback::PurchaseCallbacks;
+pub use upstream_health::{spawn_upstream_probe, UpstreamHealth};
 pub mod tx_builder;
\ No newline at end of file
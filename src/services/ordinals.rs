@@ -1,8 +1,11 @@
 use crate::cache::CacheManager;
 use crate::config::Config;
-use crate::models::{OrdinalDetails, WalletOrdinals};
+use crate::models::{CollectionHolders, HolderCount, OrdinalDetails, WalletOrdinals};
+use std::collections::{BTreeMap, HashMap};
 use crate::services::GorillaPoolClient;
 use anyhow::{Context, Result};
+use bitcoin::hashes::Hash;
+use bitcoin::{Address, Network, PubkeyHash, ScriptBuf};
 use chrono::Utc;
 use std::sync::Arc;
 use std::time::Instant;
@@ -22,14 +25,31 @@ impl OrdinalService {
 
     /// Get all ordinals for a wallet address
     pub async fn get_wallet_ordinals(&self, address: &str) -> Result<WalletOrdinals> {
-        let start = Instant::now();
         info!("Fetching ordinals for address: {}", address);
 
         if let Some(cached) = self.cache.get_wallet_ordinals(address).await {
-            debug!("Cache hit for wallet: {}", address);
-            return Ok(cached);
+            if !self.wallet_data_too_old(&cached) {
+                debug!("Cache hit for wallet: {}", address);
+                return Ok(cached);
+            }
+            warn!(
+                "Cached wallet data for {} fetched at {} exceeds max age, refetching",
+                address, cached.fetched_at
+            );
+            self.cache.invalidate_wallet(address).await;
         }
 
+        // Clients often load a wallet together; let them share one GorillaPool fetch
+        self.cache
+            .get_or_fetch_wallet_ordinals(address, self.fetch_wallet_ordinals(address))
+            .await
+            .map_err(|e| anyhow::anyhow!("{:#}", e))
+    }
+
+    /// Fetch a wallet's ordinals from GorillaPool, bypassing the wallet cache
+    async fn fetch_wallet_ordinals(&self, address: &str) -> Result<WalletOrdinals> {
+        let start = Instant::now();
+
         // Fetch from GorillaPool using the correct endpoint
         let raw_inscriptions = self.gorillapool
             .get_address_inscriptions(address)
@@ -93,11 +113,7 @@ impl OrdinalService {
                     .cloned();
 
                 // Extract collection ID if present
-                let collection_id = metadata.as_ref()
-                    .and_then(|m| m.get("subTypeData"))
-                    .and_then(|s| s.get("collectionId"))
-                    .and_then(|v| v.as_str())
-                    .map(|s| s.to_string());
+                let collection_id = collection_id_from_metadata(metadata.as_ref());
 
                 // Get the origin outpoint for content URL
                 let origin_outpoint = origin_data.get("outpoint")
@@ -114,8 +130,16 @@ impl OrdinalService {
                             .and_then(|n| n.parse::<u64>().ok())
                     });
 
+                let (origin_txid, origin_vout) = split_origin(&origin_outpoint)
+                    .unwrap_or_else(|| {
+                        warn!("Unparseable origin {}, using current outpoint", origin_outpoint);
+                        (txid.clone(), vout)
+                    });
+
                 let details = OrdinalDetails {
                     origin: origin_outpoint.clone(),
+                    origin_txid,
+                    origin_vout,
                     txid,
                     vout,
                     owner_address: address.to_string(),
@@ -147,8 +171,6 @@ impl OrdinalService {
             fetch_time_ms,
         };
 
-        self.cache.set_wallet_ordinals(address, &wallet_data).await;
-
         info!(
             "Fetched {} ordinals for {} in {}ms",
             wallet_data.total_count, address, fetch_time_ms
@@ -157,6 +179,15 @@ impl OrdinalService {
         Ok(wallet_data)
     }
 
+    /// Whether cached wallet data is past `max_wallet_cache_age`, whatever the cache TTL says
+    fn wallet_data_too_old(&self, data: &WalletOrdinals) -> bool {
+        let Some(max_age) = self.config.max_wallet_cache_age else {
+            return false;
+        };
+        let age = Utc::now().signed_duration_since(data.fetched_at);
+        age.to_std().map(|a| a > max_age).unwrap_or(false)
+    }
+
     /// Get details for a specific ordinal by origin
     pub async fn get_ordinal_details(&self, origin: &str) -> Result<Option<OrdinalDetails>> {
         if let Some(cached) = self.cache.get_ordinal_details(origin).await {
@@ -164,10 +195,63 @@ impl OrdinalService {
             return Ok(Some(cached));
         }
 
-        // For now, return None if not in cache
-        // Full implementation would query by origin
-        warn!("Ordinal not in cache: {}", origin);
-        Ok(None)
+        if self.cache.is_ordinal_not_found(origin).await {
+            debug!("Negative cache hit for ordinal: {}", origin);
+            return Ok(None);
+        }
+
+        debug!("Ordinal not in cache, fetching from GorillaPool: {}", origin);
+
+        let inscription = match self.gorillapool
+            .get_inscription_by_origin(origin)
+            .await
+            .context("Failed to fetch inscription by origin")?
+        {
+            Some(i) => i,
+            None => {
+                self.cache.set_ordinal_not_found(origin).await;
+                return Ok(None);
+            }
+        };
+
+        let owner_address = inscription.lock
+            .as_deref()
+            .and_then(|lock| address_from_lock(lock, self.config.network))
+            .unwrap_or_else(|| {
+                warn!("Could not resolve owner address for ordinal: {}", origin);
+                String::new()
+            });
+
+        let collection_id = collection_id_from_metadata(inscription.map.as_ref());
+
+        let (origin_txid, origin_vout) = split_origin(&inscription.origin)
+            .unwrap_or_else(|| {
+                warn!("Unparseable origin {}, using current outpoint", inscription.origin);
+                (inscription.txid.clone(), inscription.vout)
+            });
+
+        let details = OrdinalDetails {
+            origin: inscription.origin.clone(),
+            origin_txid,
+            origin_vout,
+            txid: inscription.txid,
+            vout: inscription.vout,
+            owner_address,
+            satoshis: 1,
+            content_type: inscription.file.as_ref().map(|f| f.content_type.clone()),
+            content_size: inscription.file.as_ref().map(|f| f.size),
+            content_hash: inscription.file.as_ref().map(|f| f.hash.clone()),
+            block_height: inscription.height,
+            inscription_number: inscription.id,
+            metadata: inscription.map,
+            collection_id,
+            content_url: self.gorillapool.content_url(&inscription.origin),
+            preview_url: self.gorillapool.preview_url(&inscription.origin),
+            fetched_at: Utc::now(),
+        };
+
+        self.cache.set_ordinal_details(origin, &details).await;
+        Ok(Some(details))
     }
 
     /// Get inscription content
@@ -182,21 +266,213 @@ impl OrdinalService {
             .await
             .context("Failed to fetch inscription content")?;
 
-        self.cache.set_content(origin, &content, &content_type).await;
+        if self.should_cache_content(content.len(), &content_type) {
+            self.cache.set_content(origin, &content, &content_type).await;
+        } else {
+            debug!(
+                "Serving content for {} uncached ({} bytes, {})",
+                origin, content.len(), content_type
+            );
+        }
         Ok((content, content_type))
     }
 
+    /// JPEG preview of an image inscription scaled down to at most `max_width`
+    /// pixels wide (never upscaled). Returns `None` for content that isn't a
+    /// decodable image.
+    pub async fn get_ordinal_preview(&self, origin: &str, max_width: u32) -> Result<Option<Vec<u8>>> {
+        if let Some(cached) = self.cache.get_preview(origin, max_width).await {
+            debug!("Cache hit for preview: {} @{}", origin, max_width);
+            return Ok(Some(cached));
+        }
+
+        let (content, content_type) = self.get_ordinal_content(origin).await?;
+        if !content_type.to_ascii_lowercase().starts_with("image/") {
+            return Ok(None);
+        }
+
+        // Decoding and resizing are CPU-bound; keep them off the async workers
+        let preview = tokio::task::spawn_blocking(move || render_preview(&content, max_width))
+            .await
+            .context("Preview task failed")?;
+
+        let Some(jpeg) = preview else {
+            debug!("Could not decode {} content for {} as an image", content_type, origin);
+            return Ok(None);
+        };
+
+        self.cache.set_preview(origin, max_width, &jpeg).await;
+        Ok(Some(jpeg))
+    }
+
+    /// Whether fetched content may go in the content cache: within `max_content_bytes`
+    /// and, if an allowlist is configured, of an allowed type (`image/*` style
+    /// wildcards match any subtype)
+    fn should_cache_content(&self, len: usize, content_type: &str) -> bool {
+        if len as u64 > self.config.max_content_bytes {
+            return false;
+        }
+        if self.config.content_type_allowlist.is_empty() {
+            return true;
+        }
+
+        let essence = content_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+        self.config.content_type_allowlist.iter().any(|allowed| match allowed.strip_suffix("/*") {
+            Some(top_level) => essence.split('/').next() == Some(top_level),
+            None => essence == *allowed,
+        })
+    }
+
+    /// Distinct owners of a collection, computed from the ordinals we've indexed
+    /// (every ordinal seen through wallet or origin lookups is cached with its owner)
+    pub async fn get_collection_holders(&self, collection_id: &str) -> CollectionHolders {
+        if let Some(cached) = self.cache.get_collection_holders(collection_id).await {
+            debug!("Cache hit for collection holders: {}", collection_id);
+            return cached;
+        }
+
+        let ordinals = self.cache.ordinals_in_collection(collection_id);
+
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for ordinal in &ordinals {
+            if !ordinal.owner_address.is_empty() {
+                *counts.entry(ordinal.owner_address.clone()).or_default() += 1;
+            }
+        }
+
+        let mut top_holders: Vec<HolderCount> = counts
+            .into_iter()
+            .map(|(address, count)| HolderCount { address, count })
+            .collect();
+        top_holders.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.address.cmp(&b.address)));
+
+        let holders = CollectionHolders {
+            collection_id: collection_id.to_string(),
+            distinct_holders: top_holders.len(),
+            total_ordinals: ordinals.len(),
+            top_holders,
+            computed_at: Utc::now(),
+        };
+
+        self.cache.set_collection_holders(collection_id, &holders).await;
+        holders
+    }
+
     /// Force refresh a wallet's ordinals
     pub async fn refresh_wallet(&self, address: &str) -> Result<WalletOrdinals> {
         self.cache.invalidate_wallet(address).await;
         self.get_wallet_ordinals(address).await
     }
 
+    /// Whether `address` currently holds the ordinal at `origin`. Always asks
+    /// GorillaPool rather than the cache, since ownership is what's being checked.
+    /// An ordinal GorillaPool doesn't know is treated as not owned.
+    pub async fn verify_ownership(&self, origin: &str, address: &str) -> Result<bool> {
+        let inscription = self.gorillapool
+            .get_inscription_by_origin(origin)
+            .await
+            .context("Failed to fetch inscription by origin")?;
+
+        let owner = inscription
+            .and_then(|i| i.lock)
+            .as_deref()
+            .and_then(|lock| address_from_lock(lock, self.config.network));
+
+        debug!("Ownership check for {}: owner {:?}, claimed {}", origin, owner, address);
+        Ok(owner.as_deref() == Some(address))
+    }
+
+    /// Current chain tip height, cached briefly; `None` if GorillaPool can't say
+    pub async fn chain_tip(&self) -> Option<u64> {
+        if let Some(height) = self.cache.get_chain_tip().await {
+            return Some(height);
+        }
+
+        match self.gorillapool.get_chain_tip().await {
+            Ok(height) => {
+                self.cache.set_chain_tip(height).await;
+                Some(height)
+            }
+            Err(e) => {
+                warn!("Failed to fetch chain tip: {}", e);
+                None
+            }
+        }
+    }
+
     pub fn gorillapool(&self) -> &GorillaPoolClient {
         &self.gorillapool
     }
 }
 
+/// Extract filterable traits from MAP metadata: top-level string fields plus
+/// `subTypeData.traits` name/value pairs. Keys and values are lowercased.
+pub fn attributes_from_metadata(metadata: &serde_json::Value) -> BTreeMap<String, String> {
+    let mut attributes = BTreeMap::new();
+
+    if let Some(map) = metadata.as_object() {
+        for (key, value) in map {
+            if let Some(v) = value.as_str() {
+                attributes.insert(key.to_lowercase(), v.to_lowercase());
+            }
+        }
+    }
+
+    let traits = metadata
+        .get("subTypeData")
+        .and_then(|s| s.get("traits"))
+        .and_then(|t| t.as_array());
+
+    for item in traits.into_iter().flatten() {
+        let name = item.get("name").or_else(|| item.get("trait_type")).and_then(|v| v.as_str());
+        let value = item.get("value").map(|v| match v.as_str() {
+            Some(s) => s.to_string(),
+            None => v.to_string(),
+        });
+
+        if let (Some(name), Some(value)) = (name, value) {
+            attributes.insert(name.to_lowercase(), value.to_lowercase());
+        }
+    }
+
+    attributes
+}
+
+/// Extract the collection ID from MAP metadata (`subTypeData.collectionId`)
+fn collection_id_from_metadata(metadata: Option<&serde_json::Value>) -> Option<String> {
+    metadata
+        .and_then(|m| m.get("subTypeData"))
+        .and_then(|s| s.get("collectionId"))
+        .and_then(|v| v.as_str())
+        .map(|s| s.to_string())
+}
+
+/// Split an origin outpoint into txid and vout. GorillaPool uses `txid_vout`
+/// but `txid:vout` is accepted too.
+fn split_origin(origin: &str) -> Option<(String, u32)> {
+    let (txid, vout) = origin.rsplit_once(['_', ':'])?;
+    if txid.len() != 64 || !txid.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return None;
+    }
+    Some((txid.to_string(), vout.parse().ok()?))
+}
+
+/// Resolve a P2PKH address on `network` from GorillaPool's `lock` field, which is
+/// either a full P2PKH locking script or a bare 20-byte pubkey hash (both hex)
+fn address_from_lock(lock: &str, network: Network) -> Option<String> {
+    let bytes = hex::decode(lock).ok()?;
+
+    let pkh = match bytes.len() {
+        20 => bytes.as_slice(),
+        25 if bytes[..3] == [0x76, 0xa9, 0x14] && bytes[23..] == [0x88, 0xac] => &bytes[3..23],
+        _ => return None,
+    };
+
+    let hash = PubkeyHash::from_slice(pkh).ok()?;
+    let script = ScriptBuf::new_p2pkh(&hash);
+    Address::from_script(&script, network).ok().map(|a| a.to_string())
+}
+
 impl Clone for OrdinalService {
     fn clone(&self) -> Self {
         Self {
@@ -206,3 +482,168 @@ impl Clone for OrdinalService {
         }
     }
 }
+
+/// JPEG quality used for previews
+const PREVIEW_JPEG_QUALITY: u8 = 80;
+
+/// Decode `content`, shrink it to `max_width` preserving aspect ratio and encode
+/// as JPEG; `None` if it can't be decoded
+fn render_preview(content: &[u8], max_width: u32) -> Option<Vec<u8>> {
+    let image = image::load_from_memory(content).ok()?;
+
+    let image = if image.width() > max_width {
+        let height = ((image.height() as u64 * max_width as u64) / image.width() as u64).max(1) as u32;
+        image.resize_exact(max_width, height, image::imageops::FilterType::Triangle)
+    } else {
+        image
+    };
+
+    // JPEG has no alpha channel
+    let rgb = image.to_rgb8();
+    let mut jpeg = Vec::new();
+    image::codecs::jpeg::JpegEncoder::new_with_quality(&mut jpeg, PREVIEW_JPEG_QUALITY)
+        .encode_image(&rgb)
+        .ok()?;
+    Some(jpeg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{address, ordinal, serve};
+    use axum::{routing::get, Json, Router};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Service against a mock GorillaPool holding no inscriptions for any address,
+    /// returning the counter of wallet fetches it received. Responses are slowed
+    /// so concurrent callers overlap.
+    async fn service_with_empty_wallets() -> (OrdinalService, Arc<AtomicUsize>) {
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+        let gorillapool = Router::new().route(
+            "/txos/address/:address/unspent",
+            get(move || async move {
+                counter.fetch_add(1, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+                Json(Vec::<serde_json::Value>::new())
+            }),
+        );
+        let config = Config {
+            gorillapool_base_url: serve(gorillapool).await,
+            gorillapool_retry_attempts: 1,
+            ..Default::default()
+        };
+        let cache = Arc::new(CacheManager::new(&config));
+        let service = OrdinalService::new(GorillaPoolClient::new(&config).unwrap(), cache, config);
+        (service, hits)
+    }
+
+    /// Cache wallet data for `address` as if it had been fetched `age` ago
+    async fn cache_wallet(service: &OrdinalService, address: &str, age: chrono::Duration) {
+        let data = WalletOrdinals {
+            address: address.to_string(),
+            total_count: 0,
+            ordinals: Vec::new(),
+            fetched_at: Utc::now() - age,
+            fetch_time_ms: 0,
+        };
+        service.cache.get_or_fetch_wallet_ordinals(address, async { Ok(data) }).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn collection_holders_counts_distinct_owners() {
+        let config = Config::default();
+        let cache = Arc::new(CacheManager::new(&config));
+        let service = OrdinalService::new(GorillaPoolClient::new(&config).unwrap(), Arc::clone(&cache), config);
+
+        let owners = [address(1), address(1), address(1), address(2), address(2), address(3)];
+        for (n, owner) in owners.iter().enumerate() {
+            let details = ordinal(n as u8, owner, Some("punks"));
+            cache.set_ordinal_details(&details.origin, &details).await;
+        }
+        let other = ordinal(50, &address(4), Some("apes"));
+        cache.set_ordinal_details(&other.origin, &other).await;
+
+        let holders = service.get_collection_holders("punks").await;
+
+        assert_eq!(holders.distinct_holders, 3);
+        assert_eq!(holders.total_ordinals, 6);
+        let top: Vec<(&str, usize)> = holders.top_holders.iter().map(|h| (h.address.as_str(), h.count)).collect();
+        assert_eq!(top, [(address(1).as_str(), 3), (address(2).as_str(), 2), (address(3).as_str(), 1)]);
+    }
+
+    #[tokio::test]
+    async fn wallet_data_past_the_max_age_is_refetched() {
+        let (service, hits) = service_with_empty_wallets().await;
+        let max_age = service.config.max_wallet_cache_age.unwrap();
+
+        let fresh = address(1);
+        cache_wallet(&service, &fresh, chrono::Duration::seconds(1)).await;
+        service.get_wallet_ordinals(&fresh).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 0);
+
+        let stale = address(2);
+        let too_old = chrono::Duration::from_std(max_age).unwrap() + chrono::Duration::seconds(1);
+        cache_wallet(&service, &stale, too_old).await;
+        let refetched = service.get_wallet_ordinals(&stale).await.unwrap();
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert!(Utc::now() - refetched.fetched_at < chrono::Duration::seconds(5));
+    }
+
+    #[tokio::test]
+    async fn concurrent_wallet_loads_share_one_upstream_fetch() {
+        let (service, hits) = service_with_empty_wallets().await;
+        let wallet = address(1);
+
+        let loads = (0..8).map(|_| service.get_wallet_ordinals(&wallet));
+        for result in futures_util::future::join_all(loads).await {
+            assert_eq!(result.unwrap().address, wallet);
+        }
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn ownership_is_checked_against_addresses_on_the_configured_network() {
+        let pkh = PubkeyHash::from_slice(&[7; 20]).unwrap();
+        let lock = hex::encode([7u8; 20]);
+        let gorillapool = Router::new().route(
+            "/inscriptions/origin/:origin",
+            get(move |axum::extract::Path(origin): axum::extract::Path<String>| async move {
+                Json(serde_json::json!({
+                    "txid": "ab".repeat(32),
+                    "vout": 0,
+                    "origin": origin,
+                    "lock": lock,
+                }))
+            }),
+        );
+        let config = Config {
+            gorillapool_base_url: serve(gorillapool).await,
+            gorillapool_retry_attempts: 1,
+            network: Network::Testnet,
+            ..Default::default()
+        };
+        let cache = Arc::new(CacheManager::new(&config));
+        let service = OrdinalService::new(GorillaPoolClient::new(&config).unwrap(), cache, config);
+
+        let script = ScriptBuf::new_p2pkh(&pkh);
+        let testnet_owner = Address::from_script(&script, Network::Testnet).unwrap().to_string();
+        let mainnet_owner = Address::from_script(&script, Network::Bitcoin).unwrap().to_string();
+        let origin = format!("{}_0", "ab".repeat(32));
+
+        assert!(service.verify_ownership(&origin, &testnet_owner).await.unwrap());
+        assert!(!service.verify_ownership(&origin, &mainnet_owner).await.unwrap());
+    }
+
+    #[test]
+    fn split_origin_accepts_underscore_and_colon_separators() {
+        let txid = "ab".repeat(32);
+        assert_eq!(split_origin(&format!("{}_0", txid)), Some((txid.clone(), 0)));
+        assert_eq!(split_origin(&format!("{}:7", txid)), Some((txid.clone(), 7)));
+
+        assert_eq!(split_origin(&txid), None);
+        assert_eq!(split_origin(&format!("{}_x", txid)), None);
+        assert_eq!(split_origin("abcd_0"), None);
+        assert_eq!(split_origin(&format!("{}_0", "zz".repeat(32))), None);
+    }
+}
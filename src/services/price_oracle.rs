@@ -0,0 +1,59 @@
+use crate::config::Config;
+use anyhow::{Context, Result};
+use reqwest::Client;
+use serde_json::Value;
+use std::time::Duration;
+use tracing::debug;
+
+/// Fetches the BSV/USD exchange rate used to convert fiat prices into satoshis
+#[derive(Clone)]
+pub struct PriceOracle {
+    client: Client,
+    url: String,
+}
+
+impl PriceOracle {
+    pub fn new(config: &Config) -> Result<Self> {
+        let client = Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()
+            .context("Failed to create price oracle HTTP client")?;
+
+        Ok(Self {
+            client,
+            url: config.price_oracle_url.clone(),
+        })
+    }
+
+    /// Current price of one BSV in USD
+    ///
+    /// Expects a JSON body with a `rate` field (WhatsOnChain's exchangerate format),
+    /// given either as a number or a numeric string
+    pub async fn bsv_usd_rate(&self) -> Result<f64> {
+        let response = self
+            .client
+            .get(&self.url)
+            .send()
+            .await
+            .context("Price oracle unreachable")?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("Price oracle returned {}", response.status());
+        }
+
+        let body: Value = response.json().await.context("Invalid price oracle response")?;
+        let rate = match body.get("rate") {
+            Some(Value::Number(n)) => n.as_f64(),
+            Some(Value::String(s)) => s.parse::<f64>().ok(),
+            _ => None,
+        }
+        .context("Price oracle response has no rate")?;
+
+        if !rate.is_finite() || rate <= 0.0 {
+            anyhow::bail!("Price oracle returned an invalid rate {}", rate);
+        }
+
+        debug!("BSV/USD rate: {}", rate);
+        Ok(rate)
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use reqwest::{Client, StatusCode, Url};
+use serde_json::json;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// How long a checkout page gets to accept a completion callback
+const CALLBACK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Whether `ip` is a public address a callback may be sent to: loopback, private,
+/// link-local, unspecified and other non-routable ranges are refused
+fn is_public_ip(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            let shared = a == 100 && (64..128).contains(&b); // 100.64.0.0/10 carrier-grade NAT
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_documentation()
+                || shared)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            let unique_local = first & 0xfe00 == 0xfc00;
+            let link_local = first & 0xffc0 == 0xfe80;
+            !(v6.is_loopback() || v6.is_unspecified() || unique_local || link_local)
+        }
+    }
+}
+
+/// DNS resolver that only hands out public addresses, so a callback host can't be
+/// pointed at internal services, including by re-resolving after validation
+struct PublicOnlyResolver;
+
+impl Resolve for PublicOnlyResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(async move {
+            let addrs: Vec<SocketAddr> = tokio::net::lookup_host((name.as_str(), 0))
+                .await?
+                .filter(|addr| is_public_ip(addr.ip()))
+                .collect();
+            if addrs.is_empty() {
+                return Err(format!("{} has no public address", name.as_str()).into());
+            }
+            Ok(Box::new(addrs.into_iter()) as Addrs)
+        })
+    }
+}
+
+/// Sends purchase completion callbacks to checkout pages' `return_url`s
+#[derive(Clone)]
+pub struct PurchaseCallbacks {
+    client: Client,
+    /// Refuse callbacks to non-public hosts (only tests turn this off)
+    public_only: bool,
+}
+
+impl PurchaseCallbacks {
+    pub fn new() -> Result<Self> {
+        let client = Client::builder()
+            .timeout(CALLBACK_TIMEOUT)
+            .redirect(reqwest::redirect::Policy::none())
+            .dns_resolver(Arc::new(PublicOnlyResolver))
+            .build()
+            .context("Failed to create purchase callback HTTP client")?;
+
+        Ok(Self { client, public_only: true })
+    }
+
+    /// Callbacks that may reach any host, for tests whose receiver is on loopback
+    #[cfg(test)]
+    pub fn allowing_any_host() -> Self {
+        Self { client: Client::new(), public_only: false }
+    }
+
+    /// Check a callback URL's host is public: IP literals are checked directly and
+    /// names must resolve only to public addresses. The client re-checks on connect.
+    pub async fn check_url(&self, url: &Url) -> Result<(), String> {
+        if !self.public_only {
+            return Ok(());
+        }
+        let host = url.host_str().ok_or("Invalid return_url: missing host")?;
+        let port = url.port_or_known_default().unwrap_or(80);
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+
+        let addrs: Vec<SocketAddr> = match host.parse::<IpAddr>() {
+            Ok(ip) => vec![SocketAddr::new(ip, port)],
+            Err(_) => tokio::net::lookup_host((host, port))
+                .await
+                .map_err(|e| format!("Invalid return_url: can't resolve {}: {}", host, e))?
+                .collect(),
+        };
+
+        if addrs.is_empty() || addrs.iter().any(|addr| !is_public_ip(addr.ip())) {
+            return Err(format!("Invalid return_url: {} is not a public address", host));
+        }
+        Ok(())
+    }
+
+    /// POST the completion of `listing_id`'s purchase in `txid` to `url`
+    async fn send(&self, url: &Url, listing_id: &str, txid: &str) -> reqwest::Result<StatusCode> {
+        let resp = self
+            .client
+            .post(url.clone())
+            .json(&json!({
+                "listing_id": listing_id,
+                "txid": txid,
+                "status": "sold",
+            }))
+            .send()
+            .await?;
+        Ok(resp.status())
+    }
+
+    /// Notify a checkout page that a purchase completed (fire-and-forget)
+    pub fn notify(&self, url: Url, listing_id: String, txid: String) {
+        let callbacks = self.clone();
+        tokio::spawn(async move {
+            match callbacks.send(&url, &listing_id, &txid).await {
+                Ok(status) => info!("Purchase callback to {} returned {}", url, status),
+                Err(e) => warn!("Purchase callback to {} failed: {}", url, e),
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{routing::post, Json, Router};
+    use tokio::sync::mpsc;
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[tokio::test]
+    async fn check_url_refuses_internal_addresses() {
+        let callbacks = PurchaseCallbacks::new().unwrap();
+        for internal in [
+            "http://127.0.0.1/done",
+            "http://localhost:8080/done",
+            "http://10.0.0.5/done",
+            "http://192.168.1.1/done",
+            "http://169.254.169.254/latest/meta-data",
+            "http://0.0.0.0/done",
+            "http://[::1]/done",
+            "http://[fe80::1]/done",
+            "http://[::ffff:127.0.0.1]/done",
+        ] {
+            assert!(callbacks.check_url(&url(internal)).await.is_err(), "{}", internal);
+        }
+
+        assert!(callbacks.check_url(&url("https://93.184.216.34/done")).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn client_refuses_names_resolving_to_internal_addresses() {
+        let callbacks = PurchaseCallbacks::new().unwrap();
+        let result = callbacks.send(&url("http://localhost:9/done"), "listing", "txid").await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn notify_posts_the_completed_purchase() {
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let app = Router::new().route(
+            "/done",
+            post(move |Json(body): Json<serde_json::Value>| async move {
+                tx.send(body).unwrap();
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        // The server under test is on loopback, which the real client refuses
+        let callbacks = PurchaseCallbacks::allowing_any_host();
+        callbacks.notify(url(&format!("http://{}/done", addr)), "listing-1".into(), "ab".repeat(32));
+
+        let body = tokio::time::timeout(Duration::from_secs(5), rx.recv()).await.unwrap().unwrap();
+        assert_eq!(body["listing_id"], "listing-1");
+        assert_eq!(body["txid"], "ab".repeat(32));
+        assert_eq!(body["status"], "sold");
+    }
+}
@@ -0,0 +1,49 @@
+use anyhow::{Context, Result};
+use base64::Engine;
+use bitcoin::secp256k1::{Message, Secp256k1};
+use bitcoin::sign_message::{signed_msg_hash, MessageSignature};
+use bitcoin::hashes::Hash;
+use bitcoin::{Address, Network, PrivateKey};
+
+/// A Bitcoin Signed Message signature and the address that produced it
+#[derive(Debug, Clone)]
+pub struct SignedMessage {
+    /// Base64-encoded 65-byte compact recoverable signature
+    pub signature: String,
+    /// P2PKH address of the signing key
+    pub signer_address: String,
+}
+
+/// Sign `message` with a WIF private key using the standard Bitcoin Signed Message format
+pub fn sign_message(wif: &str, message: &str, network: Network) -> Result<SignedMessage> {
+    let secp = Secp256k1::new();
+    let key = PrivateKey::from_wif(wif).context("Invalid WIF private key")?;
+
+    let msg_hash = signed_msg_hash(message);
+    let msg = Message::from_digest(msg_hash.to_byte_array());
+    let signature = secp.sign_ecdsa_recoverable(&msg, &key.inner);
+    let signature = MessageSignature::new(signature, key.compressed);
+
+    let signer_address = Address::p2pkh(&key.public_key(&secp), network);
+
+    Ok(SignedMessage {
+        signature: base64::engine::general_purpose::STANDARD.encode(signature.serialize()),
+        signer_address: signer_address.to_string(),
+    })
+}
+
+/// Check that `signature` (base64 Bitcoin Signed Message) over `message` was made
+/// by the key behind the P2PKH `address` on `network`
+pub fn verify_message(address: &str, message: &str, signature: &str, network: Network) -> Result<bool> {
+    let secp = Secp256k1::verification_only();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(signature)
+        .context("Signature is not valid base64")?;
+    let signature = MessageSignature::from_slice(&bytes).context("Malformed message signature")?;
+
+    let public_key = signature
+        .recover_pubkey(&secp, signed_msg_hash(message))
+        .context("Could not recover a public key from the signature")?;
+
+    Ok(Address::p2pkh(&public_key, network).to_string() == address)
+}
@@ -0,0 +1,92 @@
+use crate::config::Config;
+use crate::models::StartupReport;
+use crate::services::{GorillaPoolClient, ListingsDb};
+use chrono::Utc;
+use tracing::{info, warn};
+
+/// Build the startup validation report: config in effect, listing counts,
+/// index consistency, upstream reachability and database size
+pub async fn build_startup_report(
+    config: &Config,
+    listings_db: &ListingsDb,
+    gorillapool: &GorillaPoolClient,
+) -> StartupReport {
+    let (gorillapool_reachable, gorillapool_error) = match gorillapool.ping().await {
+        Ok(()) => (true, None),
+        Err(e) => (false, Some(e.to_string())),
+    };
+
+    let db_size_bytes = listings_db.size_on_disk().unwrap_or_else(|e| {
+        warn!("Could not determine database size: {}", e);
+        0
+    });
+
+    StartupReport {
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        generated_at: Utc::now(),
+        config: config.redacted_summary(),
+        active_listings: listings_db.count_active_listings(),
+        index_consistency: listings_db.check_index_consistency(),
+        gorillapool_reachable,
+        gorillapool_error,
+        db_path: config.db_path.clone(),
+        db_size_bytes,
+    }
+}
+
+/// Log the report, warning on anything an operator should look at
+pub fn log_startup_report(report: &StartupReport) {
+    info!("📋 Startup report:");
+    info!("   Config: {}", report.config);
+    info!("   Active listings: {}", report.active_listings);
+    info!("   Database: {} ({} bytes)", report.db_path, report.db_size_bytes);
+
+    if report.index_consistency.consistent {
+        info!(
+            "   Indexes consistent ({} origin, {} seller entries)",
+            report.index_consistency.origin_index_entries,
+            report.index_consistency.seller_index_entries
+        );
+    } else {
+        warn!("   Index problems found: {}", report.index_consistency.problems.len());
+        for problem in &report.index_consistency.problems {
+            warn!("     - {}", problem);
+        }
+    }
+
+    match &report.gorillapool_error {
+        None => info!("   GorillaPool reachable"),
+        Some(e) => warn!("   GorillaPool unreachable: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{create_listing, listings_db};
+
+    #[tokio::test]
+    async fn report_counts_active_listings_and_redacts_secrets() {
+        let config = Config {
+            handcash_app_secret: "super-secret-value".to_string(),
+            // Nothing listens on the discard port, so the ping fails fast
+            gorillapool_base_url: "http://127.0.0.1:9".to_string(),
+            gorillapool_retry_attempts: 1,
+            ..Default::default()
+        };
+        let db = listings_db();
+        create_listing(&db, 1, 10_000);
+        create_listing(&db, 2, 20_000);
+        let gorillapool = GorillaPoolClient::new(&config).unwrap();
+
+        let report = build_startup_report(&config, &db, &gorillapool).await;
+
+        assert_eq!(report.active_listings, 2);
+        assert!(report.index_consistency.consistent);
+        assert!(!report.gorillapool_reachable);
+
+        let json = serde_json::to_string(&report).unwrap();
+        assert!(!json.contains("super-secret-value"));
+        assert!(report.config.get("handcash_app_secret").is_some_and(|v| v != "super-secret-value"));
+    }
+}
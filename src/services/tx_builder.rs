@@ -1,12 +1,397 @@
 // src/services/tx_builder.rs
 
-use crate::models::{Listing, BuyerUtxo};
+use crate::models::{Listing, BuyerUtxo, InputSigner, PurchaseTxTemplate, TemplateOutput};
 use bitcoin::{
     Address, Amount, Network, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
     consensus::serialize,
 };
 use bitcoin::hex::DisplayHex;
+use bitcoin::address::Payload;
+use bitcoin::hashes::Hash;
+use bitcoin::script::{Builder, PushBytesBuf};
+use std::fmt;
 use std::str::FromStr;
+use thiserror::Error;
+use tracing::warn;
+
+/// Most a purchase may overpay the miner fee when its change is too small to keep.
+/// Larger leftovers are refused rather than silently donated to miners.
+const MAX_DUST_DONATION: u64 = 1000;
+
+/// Serialized size of one P2PKH change output
+const CHANGE_OUTPUT_BYTES: u64 = 34;
+
+/// Allowance for a P2PKH unlocking script (signature + pubkey), since inputs are
+/// estimated before they're signed
+const P2PKH_UNLOCK_BYTES: u64 = 107;
+
+/// Serialized size of one unsigned input: outpoint, empty script length and sequence
+const UNSIGNED_INPUT_BYTES: u64 = 41;
+
+/// Which party an address in a transaction belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AddressRole {
+    BuyerOrdinal,
+    BuyerPayment,
+    Seller,
+    SellerOrdinal,
+    MarketplaceFee,
+}
+
+impl AddressRole {
+    /// Whether the address came from the buyer's request rather than stored listing or config data
+    pub fn is_buyer(self) -> bool {
+        matches!(self, AddressRole::BuyerOrdinal | AddressRole::BuyerPayment)
+    }
+}
+
+impl fmt::Display for AddressRole {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            AddressRole::BuyerOrdinal => "buyer ordinal",
+            AddressRole::BuyerPayment => "buyer payment",
+            AddressRole::Seller => "seller",
+            AddressRole::SellerOrdinal => "seller ordinal",
+            AddressRole::MarketplaceFee => "marketplace fee",
+        })
+    }
+}
+
+/// Why a transaction couldn't be built
+#[derive(Debug, Error)]
+pub enum TxBuildError {
+    #[error("listing {listing_id} has an invalid ordinal txid {txid}")]
+    InvalidOrdinalTxid { listing_id: String, txid: String },
+    #[error("invalid funding UTXO txid {0}")]
+    InvalidUtxoTxid(String),
+    #[error("invalid {role} address {address}: {reason}")]
+    InvalidAddress { role: AddressRole, address: String, reason: String },
+    #[error("{role} address {address} must be P2PKH")]
+    NotP2pkh { role: AddressRole, address: String },
+    #[error("listing {listing_id} has an invalid script: {reason}")]
+    InvalidScript { listing_id: String, reason: String },
+    #[error("Change of {leftover} sats is too small for its own output after fees and would be lost to miners (limit {limit} sats); consolidate your UTXOs or add one large enough to leave change")]
+    DustChange { leftover: u64, limit: u64 },
+    #[error("Insufficient funds: inputs total {available} sats but outputs need {outputs} sats plus {fee} sats miner fee")]
+    InsufficientFunds { available: u64, outputs: u64, fee: u64 },
+}
+
+/// Parse `address`, requiring it to be on `network`
+fn parse_address(address: &str, role: AddressRole, network: Network) -> Result<Address, TxBuildError> {
+    let invalid = |reason: String| TxBuildError::InvalidAddress {
+        role,
+        address: address.to_string(),
+        reason,
+    };
+    Address::from_str(address)
+        .map_err(|e| invalid(e.to_string()))?
+        .require_network(network)
+        .map_err(|e| invalid(e.to_string()))
+}
+
+/// Parse the listed ordinal's txid
+fn ordinal_txid(listing: &Listing) -> Result<Txid, TxBuildError> {
+    Txid::from_str(&listing.ordinal_utxo.txid).map_err(|_| TxBuildError::InvalidOrdinalTxid {
+        listing_id: listing.id.clone(),
+        txid: listing.ordinal_utxo.txid.clone(),
+    })
+}
+
+/// Parse a funding UTXO's txid
+fn utxo_txid(utxo: &BuyerUtxo) -> Result<Txid, TxBuildError> {
+    Txid::from_str(&utxo.txid).map_err(|_| TxBuildError::InvalidUtxoTxid(utxo.txid.clone()))
+}
+
+/// Sig request for a listing's ordinal input, which only the seller can sign
+fn ordinal_sig_request(listing: &Listing, input_index: u32) -> Result<crate::models::SigRequest, TxBuildError> {
+    let ordinal_utxo = &listing.ordinal_utxo;
+    Ok(crate::models::SigRequest {
+        input_index,
+        signer: InputSigner::Seller,
+        address: Some(listing.seller_ord_address.clone()),
+        prev_txid: ordinal_utxo.txid.clone(),
+        prev_vout: ordinal_utxo.vout,
+        satoshis: ordinal_utxo.satoshis,
+        script_hex: ordinal_utxo.script_hex().map_err(|e| TxBuildError::InvalidScript {
+            listing_id: listing.id.clone(),
+            reason: e.to_string(),
+        })?,
+    })
+}
+
+/// Chain settings shared by the transaction builders
+#[derive(Debug, Clone, Copy)]
+pub struct TxParams<'a> {
+    /// Network every address must belong to
+    pub network: Network,
+    /// Miner fee rate used to size fees and change
+    pub fee_rate_sat_per_byte: f64,
+    /// Smallest change output worth creating
+    pub dust_threshold_sats: u64,
+    /// Prefix of the OP_RETURN attribution output on purchases; `None` leaves it out
+    pub op_return_prefix: Option<&'a str>,
+}
+
+impl<'a> TxParams<'a> {
+    pub fn from_config(config: &'a crate::config::Config) -> Self {
+        Self {
+            network: config.network,
+            fee_rate_sat_per_byte: config.fee_rate_sat_per_byte,
+            dust_threshold_sats: config.dust_threshold_sats,
+            op_return_prefix: config.op_return_prefix.as_deref(),
+        }
+    }
+
+    /// These params with the OP_RETURN attribution output dropped if `omit` is set
+    pub fn omit_op_return(self, omit: bool) -> Self {
+        Self {
+            op_return_prefix: if omit { None } else { self.op_return_prefix },
+            ..self
+        }
+    }
+}
+
+/// Zero-value `OP_FALSE OP_RETURN <prefix> <listing id>...` output attributing a sale
+/// to this marketplace
+fn attribution_output(prefix: &str, listings: &[Listing]) -> Result<TxOut, TxBuildError> {
+    let push = |data: &str, listing: &Listing| {
+        PushBytesBuf::try_from(data.as_bytes().to_vec()).map_err(|e| TxBuildError::InvalidScript {
+            listing_id: listing.id.clone(),
+            reason: e.to_string(),
+        })
+    };
+
+    let mut builder = Builder::new()
+        .push_opcode(bitcoin::opcodes::OP_FALSE)
+        .push_opcode(bitcoin::opcodes::all::OP_RETURN);
+    let pushes = listings
+        .first()
+        .map(|first| (prefix, first))
+        .into_iter()
+        .chain(listings.iter().map(|listing| (listing.id.as_str(), listing)));
+    for (data, listing) in pushes {
+        builder = builder.push_slice(push(data, listing)?);
+    }
+
+    Ok(TxOut {
+        value: Amount::ZERO,
+        script_pubkey: builder.into_script(),
+    })
+}
+
+/// Estimate the miner fee for `tx` once signed and with `change_outputs` P2PKH
+/// change outputs appended
+fn estimate_fee(tx: &Transaction, change_outputs: u64, fee_rate_sat_per_byte: f64) -> u64 {
+    let size = serialize(tx).len() as u64
+        + tx.input.len() as u64 * P2PKH_UNLOCK_BYTES
+        + change_outputs * CHANGE_OUTPUT_BYTES;
+    (size as f64 * fee_rate_sat_per_byte).ceil() as u64
+}
+
+/// Estimated miner fee for a typical single-listing purchase: the ordinal input
+/// and one payment input, buyer ordinal, seller and (if charged) marketplace
+/// outputs, plus one change output
+pub fn estimate_purchase_network_fee(has_marketplace_output: bool, fee_rate_sat_per_byte: f64) -> u64 {
+    let input = TxIn {
+        previous_output: OutPoint::null(),
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::new(),
+    };
+    let output = TxOut {
+        value: Amount::from_sat(1),
+        script_pubkey: ScriptBuf::new_p2pkh(&bitcoin::PubkeyHash::all_zeros()),
+    };
+    let fixed_outputs = if has_marketplace_output { 3 } else { 2 };
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version(1),
+        lock_time: bitcoin::absolute::LockTime::ZERO,
+        input: vec![input; 2],
+        output: vec![output; fixed_outputs],
+    };
+    estimate_fee(&tx, 1, fee_rate_sat_per_byte)
+}
+
+/// Miner fee the unfunded part of a purchase of `listings` costs once paid for by
+/// one funding input with one change output: `estimate_purchase_network_fee` for the
+/// first listing, plus another ordinal input and its outputs for each further one,
+/// plus the attribution output when `params` includes it
+pub fn purchase_base_fee(listings: &[Listing], params: TxParams<'_>) -> u64 {
+    let has_marketplace_output = |listing: &Listing| listing.fees.marketplace_fee + listing.fees.tip_amount > 0;
+    let Some((first, rest)) = listings.split_first() else {
+        return 0;
+    };
+
+    let mut extra_bytes = 0u64;
+    for listing in rest {
+        let outputs = if has_marketplace_output(listing) { 3 } else { 2 };
+        extra_bytes += UNSIGNED_INPUT_BYTES + P2PKH_UNLOCK_BYTES + outputs * CHANGE_OUTPUT_BYTES;
+    }
+    if let Some(output) = params
+        .op_return_prefix
+        .and_then(|prefix| attribution_output(prefix, listings).ok())
+    {
+        extra_bytes += serialize(&output).len() as u64;
+    }
+
+    estimate_purchase_network_fee(has_marketplace_output(first), params.fee_rate_sat_per_byte)
+        + (extra_bytes as f64 * params.fee_rate_sat_per_byte).ceil() as u64
+}
+
+/// Miner fee the unfunded part of a lock transaction for `listing` costs once paid
+/// for by one funding input with one change output
+pub fn lock_base_fee(
+    listing: &Listing,
+    ordlock_prefix: &[u8],
+    ordlock_suffix: &[u8],
+    params: TxParams<'_>,
+) -> Result<u64, TxBuildError> {
+    let input = TxIn {
+        previous_output: OutPoint::null(),
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::new(),
+    };
+    let tx = Transaction {
+        version: bitcoin::transaction::Version(1),
+        lock_time: bitcoin::absolute::LockTime::ZERO,
+        input: vec![input; 2],
+        output: vec![TxOut {
+            value: Amount::from_sat(1),
+            script_pubkey: ordlock_script(listing, ordlock_prefix, ordlock_suffix, params.network)?,
+        }],
+    };
+    Ok(estimate_fee(&tx, 1, params.fee_rate_sat_per_byte))
+}
+
+/// Sats `funding_inputs` must total for a transaction whose fixed outputs need
+/// `outputs_sats` beyond its ordinal inputs and whose unfunded part costs `base_fee`
+/// (see `purchase_base_fee` and `lock_base_fee`): every funding input past the first
+/// adds its own signed size at the configured fee rate
+pub fn funding_target(outputs_sats: u64, base_fee: u64, funding_inputs: usize, fee_rate_sat_per_byte: f64) -> u64 {
+    let input_fee = ((UNSIGNED_INPUT_BYTES + P2PKH_UNLOCK_BYTES) as f64 * fee_rate_sat_per_byte).ceil() as u64;
+    outputs_sats + base_fee + funding_inputs.saturating_sub(1) as u64 * input_fee
+}
+
+/// Choose funding UTXOs meeting `funding_target`. The target is re-sized until the
+/// selection covers the fee of the inputs it picked; returns the unmet target on failure.
+pub fn select_funding_utxos(
+    candidates: &[BuyerUtxo],
+    outputs_sats: u64,
+    base_fee: u64,
+    params: TxParams<'_>,
+) -> Result<Vec<BuyerUtxo>, u64> {
+    // Each pass either settles or strictly grows the input count, which is bounded
+    // by the number of candidates
+    let mut inputs = 1;
+    loop {
+        let target = funding_target(outputs_sats, base_fee, inputs, params.fee_rate_sat_per_byte);
+        let selected = select_utxos(candidates, target, params.dust_threshold_sats).ok_or(target)?;
+        if selected.len() <= inputs {
+            return Ok(selected);
+        }
+        inputs = selected.len();
+    }
+}
+
+/// Sort candidate UTXOs largest-first, breaking ties on equal value by (txid, vout)
+/// so coin selection is deterministic
+pub fn sort_candidates(utxos: &mut [BuyerUtxo]) {
+    utxos.sort_by(|a, b| {
+        b.satoshis
+            .cmp(&a.satoshis)
+            .then_with(|| a.txid.cmp(&b.txid))
+            .then_with(|| a.vout.cmp(&b.vout))
+    });
+}
+
+/// Sum above the target that still counts as an exact match (no change output)
+const EXACT_MATCH_WINDOW: u64 = 100;
+
+/// Upper bound on branch-and-bound search steps before falling back to largest-first
+const BNB_MAX_TRIES: usize = 100_000;
+
+/// Choose which candidate UTXOs fund a payment of `target` sats.
+///
+/// First tries branch-and-bound for a subset landing within `EXACT_MATCH_WINDOW` of
+/// the target (no change needed). Otherwise falls back to largest-first, continuing
+/// past the target until the change would be at least `dust_threshold` when possible.
+/// Returns `None` if the candidates can't cover the target.
+pub fn select_utxos(candidates: &[BuyerUtxo], target: u64, dust_threshold: u64) -> Option<Vec<BuyerUtxo>> {
+    let mut sorted = candidates.to_vec();
+    sort_candidates(&mut sorted);
+
+    let available: u64 = sorted.iter().map(|u| u.satoshis).sum();
+    if available < target {
+        return None;
+    }
+
+    if let Some(indices) = branch_and_bound(&sorted, target) {
+        return Some(indices.into_iter().map(|i| sorted[i].clone()).collect());
+    }
+
+    // Largest-first: stop once change clears dust, or accept sub-dust change if we must
+    let mut selected = Vec::new();
+    let mut total = 0u64;
+    for utxo in &sorted {
+        if total >= target + dust_threshold {
+            break;
+        }
+        total += utxo.satoshis;
+        selected.push(utxo.clone());
+    }
+
+    Some(selected)
+}
+
+/// Depth-first search over include/exclude decisions (largest UTXOs first) for a
+/// subset summing to within `EXACT_MATCH_WINDOW` above `target`
+fn branch_and_bound(sorted: &[BuyerUtxo], target: u64) -> Option<Vec<usize>> {
+    // remaining[i] = sum of sorted[i..], used to prune branches that can't reach the target
+    let mut remaining = vec![0u64; sorted.len() + 1];
+    for i in (0..sorted.len()).rev() {
+        remaining[i] = remaining[i + 1] + sorted[i].satoshis;
+    }
+
+    let mut tries = 0usize;
+    let mut selected = Vec::new();
+
+    fn search(
+        sorted: &[BuyerUtxo],
+        remaining: &[u64],
+        target: u64,
+        index: usize,
+        total: u64,
+        selected: &mut Vec<usize>,
+        tries: &mut usize,
+    ) -> bool {
+        *tries += 1;
+        if *tries > BNB_MAX_TRIES || total > target + EXACT_MATCH_WINDOW {
+            return false;
+        }
+        if total >= target {
+            return true;
+        }
+        if index >= sorted.len() || total + remaining[index] < target {
+            return false;
+        }
+
+        selected.push(index);
+        if search(sorted, remaining, target, index + 1, total + sorted[index].satoshis, selected, tries) {
+            return true;
+        }
+        selected.pop();
+
+        search(sorted, remaining, target, index + 1, total, selected, tries)
+    }
+
+    if search(sorted, &remaining, target, 0, 0, &mut selected, &mut tries) {
+        Some(selected)
+    } else {
+        None
+    }
+}
 
 pub fn build_purchase_tx(
     listing: &Listing,
@@ -14,7 +399,122 @@ pub fn build_purchase_tx(
     buyer_payment_address: &str,
     buyer_utxos: Vec<BuyerUtxo>,
     marketplace_fee_address: &str,
-) -> Result<crate::models::PreparePurchaseResponse, Box<dyn std::error::Error>> {
+    params: TxParams<'_>,
+    split_change: Option<u32>,
+) -> Result<crate::models::PreparePurchaseResponse, TxBuildError> {
+    build_cart_purchase_tx(
+        std::slice::from_ref(listing),
+        buyer_ord_address,
+        buyer_payment_address,
+        buyer_utxos,
+        marketplace_fee_address,
+        params,
+        split_change,
+    )
+}
+
+/// Size, fee and change of the purchase `build_purchase_tx` would build, without
+/// the transaction itself
+pub fn estimate_purchase_tx(
+    listing: &Listing,
+    buyer_ord_address: &str,
+    buyer_payment_address: &str,
+    buyer_utxos: Vec<BuyerUtxo>,
+    marketplace_fee_address: &str,
+    params: TxParams<'_>,
+    split_change: Option<u32>,
+) -> Result<crate::models::PurchaseEstimate, TxBuildError> {
+    let purchase = assemble_cart_purchase_tx(
+        std::slice::from_ref(listing),
+        buyer_ord_address,
+        buyer_payment_address,
+        &buyer_utxos,
+        marketplace_fee_address,
+        params,
+        split_change,
+    )?;
+
+    let total_in: u64 = listing.ordinal_utxo.satoshis + buyer_utxos.iter().map(|u| u.satoshis).sum::<u64>();
+    let total_out: u64 = purchase.tx.output.iter().map(|o| o.value.to_sat()).sum();
+
+    Ok(crate::models::PurchaseEstimate {
+        estimated_size_bytes: serialize(&purchase.tx).len() as u64
+            + purchase.tx.input.len() as u64 * P2PKH_UNLOCK_BYTES,
+        miner_fee: total_in.saturating_sub(total_out),
+        input_count: purchase.tx.input.len(),
+        change_sats: purchase.change_sats,
+    })
+}
+
+/// An unsigned purchase transaction and what it pays back to the buyer
+struct AssembledPurchase {
+    tx: Transaction,
+    change_sats: u64,
+}
+
+/// Build one unsigned transaction buying every listing in `listings`.
+///
+/// Inputs are the ordinals (in listing order) followed by the buyer's payment
+/// UTXOs. The 1-sat buyer ordinal outputs come first, one per ordinal input, so
+/// each inscription lands at the same index it was spent from; the seller and
+/// marketplace outputs for each listing follow, then change, then the optional
+/// OP_RETURN attribution output.
+pub fn build_cart_purchase_tx(
+    listings: &[Listing],
+    buyer_ord_address: &str,
+    buyer_payment_address: &str,
+    buyer_utxos: Vec<BuyerUtxo>,
+    marketplace_fee_address: &str,
+    params: TxParams<'_>,
+    split_change: Option<u32>,
+) -> Result<crate::models::PreparePurchaseResponse, TxBuildError> {
+    let purchase = assemble_cart_purchase_tx(
+        listings,
+        buyer_ord_address,
+        buyer_payment_address,
+        &buyer_utxos,
+        marketplace_fee_address,
+        params,
+        split_change,
+    )?;
+
+    // Sig requests: the seller signs each ordinal input, the buyer each payment input
+    let mut sig_requests = Vec::with_capacity(purchase.tx.input.len());
+    for (i, listing) in listings.iter().enumerate() {
+        sig_requests.push(ordinal_sig_request(listing, i as u32)?);
+    }
+    for (i, utxo) in buyer_utxos.iter().enumerate() {
+        let input_index = i + listings.len();
+        sig_requests.push(crate::models::SigRequest {
+            input_index: input_index as u32,
+            signer: InputSigner::Buyer,
+            address: utxo.address.clone(),
+            prev_txid: utxo.txid.clone(),
+            prev_vout: utxo.vout,
+            satoshis: utxo.satoshis,
+            script_hex: utxo.script_hex.clone(),
+        });
+    }
+
+    let raw_bytes = serialize(&purchase.tx);
+    let raw_tx_hex = raw_bytes.as_hex().to_string();
+
+    Ok(crate::models::PreparePurchaseResponse {
+        raw_tx_hex,
+        sig_requests,
+    })
+}
+
+/// Inputs, outputs and change for `build_cart_purchase_tx`
+fn assemble_cart_purchase_tx(
+    listings: &[Listing],
+    buyer_ord_address: &str,
+    buyer_payment_address: &str,
+    buyer_utxos: &[BuyerUtxo],
+    marketplace_fee_address: &str,
+    params: TxParams<'_>,
+    split_change: Option<u32>,
+) -> Result<AssembledPurchase, TxBuildError> {
     let mut tx = Transaction {
         version: bitcoin::transaction::Version(1),
         lock_time: bitcoin::absolute::LockTime::ZERO,
@@ -22,20 +522,20 @@ pub fn build_purchase_tx(
         output: vec![],
     };
 
-    // Input 0: Ordinal UTXO
-    let ordinal_utxo = &listing.ordinal_utxo;
-    let ordinal_txid = Txid::from_str(&ordinal_utxo.txid)?;
-    tx.input.push(TxIn {
-        previous_output: OutPoint { txid: ordinal_txid, vout: ordinal_utxo.vout },
-        script_sig: ScriptBuf::new(),
-        sequence: Sequence::MAX,
-        witness: Witness::new(),
-    });
+    // Inputs 0..n: Ordinal UTXOs
+    for listing in listings {
+        tx.input.push(TxIn {
+            previous_output: OutPoint { txid: ordinal_txid(listing)?, vout: listing.ordinal_utxo.vout },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        });
+    }
 
     // Buyer payment inputs
-    let mut total_input_sats: u64 = 1; // from ordinal
-    for utxo in &buyer_utxos {
-        let txid = Txid::from_str(&utxo.txid)?;
+    let mut total_input_sats: u64 = listings.len() as u64; // 1 sat per ordinal
+    for utxo in buyer_utxos {
+        let txid = utxo_txid(utxo)?;
         tx.input.push(TxIn {
             previous_output: OutPoint { txid, vout: utxo.vout },
             script_sig: ScriptBuf::new(),
@@ -45,61 +545,543 @@ pub fn build_purchase_tx(
         total_input_sats += utxo.satoshis;
     }
 
+    // Fixed outputs: ordinals to buyer, then seller payment and marketplace fee + tip per listing
+    let mut templates = Vec::with_capacity(listings.len());
+    for listing in listings {
+        templates.push(build_purchase_template(listing, Some(buyer_ord_address), marketplace_fee_address, params.network)?);
+    }
+    let (ordinal_outputs, payment_outputs): (Vec<&TemplateOutput>, Vec<&TemplateOutput>) = templates
+        .iter()
+        .flat_map(|t| t.outputs.iter())
+        .partition(|o| o.role == "buyer_ordinal");
+    for output in ordinal_outputs.into_iter().chain(payment_outputs) {
+        let address = output.address.as_deref().unwrap_or(buyer_ord_address);
+        let role = match output.role.as_str() {
+            "buyer_ordinal" => AddressRole::BuyerOrdinal,
+            "seller" => AddressRole::Seller,
+            _ => AddressRole::MarketplaceFee,
+        };
+        let addr = parse_address(address, role, params.network)?;
+        tx.output.push(TxOut {
+            value: Amount::from_sat(output.satoshis),
+            script_pubkey: addr.script_pubkey(),
+        });
+    }
+
+    // The attribution output goes last, but is added now so the fee estimates include it
+    let attribution = match params.op_return_prefix {
+        Some(prefix) => Some(attribution_output(prefix, listings)?),
+        None => None,
+    };
+    if let Some(output) = &attribution {
+        tx.output.push(output.clone());
+    }
+
+    // Miner fee is size-based; make sure the inputs cover outputs + fee before adding change
+    let total_fixed_outputs: u64 = templates.iter().map(|t| t.total_fixed_sats).sum();
+    let fee_without_change = estimate_fee(&tx, 0, params.fee_rate_sat_per_byte);
+    if total_input_sats < total_fixed_outputs + fee_without_change {
+        return Err(TxBuildError::InsufficientFunds {
+            available: total_input_sats,
+            outputs: total_fixed_outputs,
+            fee: fee_without_change,
+        });
+    }
+
+    // Change output(s) to buyer. Only split as far as every part stays above dust
+    // after paying for the extra outputs; zero splits means no change output.
+    let mut splits = split_change.unwrap_or(1).max(1) as u64;
+    let mut change = 0;
+    while splits > 0 {
+        let fee = estimate_fee(&tx, splits, params.fee_rate_sat_per_byte);
+        change = total_input_sats.saturating_sub(total_fixed_outputs + fee);
+        if change / splits >= params.dust_threshold_sats {
+            break;
+        }
+        splits -= 1;
+    }
+
+    if splits == 0 {
+        // No change output: whatever the fee estimate didn't claim goes to the miner
+        let leftover = total_input_sats - total_fixed_outputs - fee_without_change;
+        if leftover > MAX_DUST_DONATION {
+            return Err(TxBuildError::DustChange { leftover, limit: MAX_DUST_DONATION });
+        }
+        if leftover > 0 {
+            warn!("Purchase change of {} sats is dust; adding it to the miner fee", leftover);
+        }
+    }
+
+    if attribution.is_some() {
+        tx.output.pop();
+    }
+
+    if let Some(part) = change.checked_div(splits) {
+        let change_addr = parse_address(buyer_payment_address, AddressRole::BuyerPayment, params.network)?;
+        for i in 0..splits {
+            // The last output absorbs the rounding remainder
+            let value = if i == splits - 1 { change - part * (splits - 1) } else { part };
+            tx.output.push(TxOut {
+                value: Amount::from_sat(value),
+                script_pubkey: change_addr.script_pubkey(),
+            });
+        }
+    }
+
+    if let Some(output) = attribution {
+        tx.output.push(output);
+    }
+
+    Ok(AssembledPurchase {
+        tx,
+        change_sats: if splits > 0 { change } else { 0 },
+    })
+}
+
+/// Build the non-funding part of a purchase: the ordinal input the buyer's wallet
+/// must spend and the fixed outputs (buyer ordinal, seller, marketplace fee + tip).
+///
+/// When `buyer_ord_address` is `None` the buyer ordinal output is returned without an
+/// address or script, for wallets that fill it in themselves.
+pub fn build_purchase_template(
+    listing: &Listing,
+    buyer_ord_address: Option<&str>,
+    marketplace_fee_address: &str,
+    network: Network,
+) -> Result<PurchaseTxTemplate, TxBuildError> {
+    let mut outputs = Vec::new();
+
     // Output 0: Ordinal to buyer (1 sat)
-    let buyer_ord_addr = Address::from_str(buyer_ord_address)?.require_network(Network::Bitcoin)?;
-    tx.output.push(TxOut {
-        value: Amount::from_sat(1),
-        script_pubkey: buyer_ord_addr.script_pubkey(),
+    let buyer_script = match buyer_ord_address {
+        Some(address) => {
+            let addr = parse_address(address, AddressRole::BuyerOrdinal, network)?;
+            Some(addr.script_pubkey().as_bytes().as_hex().to_string())
+        }
+        None => None,
+    };
+    outputs.push(TemplateOutput {
+        role: "buyer_ordinal".to_string(),
+        address: buyer_ord_address.map(|a| a.to_string()),
+        satoshis: 1,
+        script_hex: buyer_script,
     });
 
     // Output 1: Seller receives their full requested amount
-    let seller_addr = Address::from_str(&listing.seller_address)?.require_network(Network::Bitcoin)?;
-    tx.output.push(TxOut {
-        value: Amount::from_sat(listing.fees.seller_receives),
-        script_pubkey: seller_addr.script_pubkey(),
+    let seller_addr = parse_address(&listing.seller_address, AddressRole::Seller, network)?;
+    outputs.push(TemplateOutput {
+        role: "seller".to_string(),
+        address: Some(listing.seller_address.clone()),
+        satoshis: listing.fees.seller_receives,
+        script_hex: Some(seller_addr.script_pubkey().as_bytes().as_hex().to_string()),
     });
 
-    // Output 2: Marketplace receives 1% fee + tip (donation)
+    // Output 2: Marketplace receives its fee + tip (donation)
     let total_marketplace_sats = listing.fees.marketplace_fee + listing.fees.tip_amount;
     if total_marketplace_sats > 0 {
-        let marketplace_addr = Address::from_str(marketplace_fee_address)?.require_network(Network::Bitcoin)?;
-        tx.output.push(TxOut {
-            value: Amount::from_sat(total_marketplace_sats),
-            script_pubkey: marketplace_addr.script_pubkey(),
+        let marketplace_addr = parse_address(marketplace_fee_address, AddressRole::MarketplaceFee, network)?;
+        outputs.push(TemplateOutput {
+            role: "marketplace_fee".to_string(),
+            address: Some(marketplace_fee_address.to_string()),
+            satoshis: total_marketplace_sats,
+            script_hex: Some(marketplace_addr.script_pubkey().as_bytes().as_hex().to_string()),
         });
     }
 
-    // Change output to buyer
-    let total_fixed_outputs = 1 + listing.fees.seller_receives + total_marketplace_sats;
-    let estimated_fee = 300u64; // conservative miner fee
-    let change = total_input_sats.saturating_sub(total_fixed_outputs + estimated_fee);
+    let total_fixed_sats = outputs.iter().map(|o| o.satoshis).sum();
 
-    if change >= 546 { // dust threshold
-        let change_addr = Address::from_str(buyer_payment_address)?.require_network(Network::Bitcoin)?;
-        tx.output.push(TxOut {
-            value: Amount::from_sat(change),
-            script_pubkey: change_addr.script_pubkey(),
-        });
+    Ok(PurchaseTxTemplate {
+        listing_id: listing.id.clone(),
+        ordinal_input: listing.ordinal_utxo.clone(),
+        outputs,
+        total_fixed_sats,
+    })
+}
+
+/// Check a signed purchase transaction actually buys `listing` on the agreed
+/// terms: it spends the listed ordinal, pays the seller `seller_receives` and
+/// pays the marketplace fee plus tip to one of `fee_addresses`. Returns every
+/// failed check.
+pub fn verify_purchase_tx(
+    tx: &Transaction,
+    listing: &Listing,
+    fee_addresses: &[&str],
+    network: Network,
+) -> Result<(), Vec<String>> {
+    let mut problems = Vec::new();
+
+    let spends_ordinal = tx.input.iter().any(|input| {
+        input.previous_output.txid.to_string() == listing.ordinal_utxo.txid
+            && input.previous_output.vout == listing.ordinal_utxo.vout
+    });
+    if !spends_ordinal {
+        problems.push(format!(
+            "does not spend the listed ordinal {}:{}",
+            listing.ordinal_utxo.txid, listing.ordinal_utxo.vout
+        ));
     }
 
-    // Sig requests for buyer inputs only (skip ordinal input)
-    let mut sig_requests = Vec::new();
-    for (i, utxo) in buyer_utxos.iter().enumerate() {
-        let input_index = i + 1; // input 0 is ordinal
+    let pays = |address: &str, satoshis: u64| {
+        let Some(script) = Address::from_str(address)
+            .ok()
+            .and_then(|a| a.require_network(network).ok())
+            .map(|a| a.script_pubkey())
+        else {
+            return false;
+        };
+        tx.output
+            .iter()
+            .any(|out| out.script_pubkey == script && out.value.to_sat() >= satoshis)
+    };
+
+    if !pays(&listing.seller_address, listing.fees.seller_receives) {
+        problems.push(format!(
+            "does not pay the seller {} sats to {}",
+            listing.fees.seller_receives, listing.seller_address
+        ));
+    }
+
+    let marketplace_sats = listing.fees.marketplace_fee + listing.fees.tip_amount;
+    if marketplace_sats > 0 && !fee_addresses.iter().any(|a| pays(a, marketplace_sats)) {
+        problems.push(format!(
+            "does not pay the marketplace fee of {} sats to {}",
+            marketplace_sats,
+            fee_addresses.join(" or ")
+        ));
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(problems)
+    }
+}
+
+/// Build the ordinal-lock script for `listing`: the contract `prefix`, a push of the
+/// seller's cancel pubkey hash, a push of the serialized payout output, then `suffix`
+pub fn ordlock_script(
+    listing: &Listing,
+    prefix: &[u8],
+    suffix: &[u8],
+    network: Network,
+) -> Result<ScriptBuf, TxBuildError> {
+    let cancel_addr = parse_address(&listing.seller_ord_address, AddressRole::SellerOrdinal, network)?;
+    let Payload::PubkeyHash(cancel_pkh) = cancel_addr.payload() else {
+        return Err(TxBuildError::NotP2pkh {
+            role: AddressRole::SellerOrdinal,
+            address: listing.seller_ord_address.clone(),
+        });
+    };
+
+    let payout_addr = parse_address(&listing.seller_address, AddressRole::Seller, network)?;
+    let payout = serialize(&TxOut {
+        value: Amount::from_sat(listing.fees.seller_receives),
+        script_pubkey: payout_addr.script_pubkey(),
+    });
+
+    let payload = Builder::new()
+        .push_slice(cancel_pkh.to_byte_array())
+        .push_slice(PushBytesBuf::try_from(payout).map_err(|e| TxBuildError::InvalidScript {
+            listing_id: listing.id.clone(),
+            reason: e.to_string(),
+        })?)
+        .into_script();
+
+    let mut bytes = prefix.to_vec();
+    bytes.extend_from_slice(payload.as_bytes());
+    bytes.extend_from_slice(suffix);
+    Ok(ScriptBuf::from_bytes(bytes))
+}
+
+/// Build an unsigned transaction moving the listed ordinal into an ordinal-lock output.
+///
+/// Input 0 is the seller's ordinal and output 0 the 1-sat lock; `seller_utxos` pay the
+/// miner fee with change back to the seller's payment address. Sig requests cover
+/// every input, since the seller signs them all.
+pub fn build_lock_tx(
+    listing: &Listing,
+    seller_utxos: Vec<BuyerUtxo>,
+    ordlock_prefix: &[u8],
+    ordlock_suffix: &[u8],
+    params: TxParams<'_>,
+) -> Result<crate::models::PreparePurchaseResponse, TxBuildError> {
+    let mut tx = Transaction {
+        version: bitcoin::transaction::Version(1),
+        lock_time: bitcoin::absolute::LockTime::ZERO,
+        input: vec![],
+        output: vec![],
+    };
+
+    // Input 0: the ordinal being listed
+    let ordinal_utxo = &listing.ordinal_utxo;
+    tx.input.push(TxIn {
+        previous_output: OutPoint { txid: ordinal_txid(listing)?, vout: ordinal_utxo.vout },
+        script_sig: ScriptBuf::new(),
+        sequence: Sequence::MAX,
+        witness: Witness::new(),
+    });
+    let mut sig_requests = vec![ordinal_sig_request(listing, 0)?];
+
+    // Seller funding inputs
+    let mut total_input_sats = ordinal_utxo.satoshis;
+    for utxo in &seller_utxos {
+        tx.input.push(TxIn {
+            previous_output: OutPoint { txid: utxo_txid(utxo)?, vout: utxo.vout },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::new(),
+        });
         sig_requests.push(crate::models::SigRequest {
-            input_index: input_index as u32,
+            input_index: (tx.input.len() - 1) as u32,
+            signer: InputSigner::Seller,
+            address: utxo.address.clone(),
             prev_txid: utxo.txid.clone(),
             prev_vout: utxo.vout,
             satoshis: utxo.satoshis,
             script_hex: utxo.script_hex.clone(),
         });
+        total_input_sats += utxo.satoshis;
     }
 
-    let raw_bytes = serialize(&tx);
-    let raw_tx_hex = raw_bytes.as_hex().to_string();
+    // Output 0: the ordinal, now held by the lock contract
+    tx.output.push(TxOut {
+        value: Amount::from_sat(1),
+        script_pubkey: ordlock_script(listing, ordlock_prefix, ordlock_suffix, params.network)?,
+    });
+
+    let fee_without_change = estimate_fee(&tx, 0, params.fee_rate_sat_per_byte);
+    if total_input_sats < 1 + fee_without_change {
+        return Err(TxBuildError::InsufficientFunds {
+            available: total_input_sats,
+            outputs: 1,
+            fee: fee_without_change,
+        });
+    }
+
+    let change = total_input_sats.saturating_sub(1 + estimate_fee(&tx, 1, params.fee_rate_sat_per_byte));
+    if change >= params.dust_threshold_sats {
+        let change_addr = parse_address(&listing.seller_address, AddressRole::Seller, params.network)?;
+        tx.output.push(TxOut {
+            value: Amount::from_sat(change),
+            script_pubkey: change_addr.script_pubkey(),
+        });
+    }
 
     Ok(crate::models::PreparePurchaseResponse {
-        raw_tx_hex,
+        raw_tx_hex: serialize(&tx).as_hex().to_string(),
         sig_requests,
     })
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{address, create_listing, listings_db};
+
+    fn utxo(n: u8, satoshis: u64) -> BuyerUtxo {
+        BuyerUtxo {
+            txid: format!("{:02x}", n).repeat(32),
+            vout: 0,
+            satoshis,
+            script_hex: String::new(),
+            address: None,
+        }
+    }
+
+    fn sats(utxos: &[BuyerUtxo]) -> Vec<u64> {
+        utxos.iter().map(|u| u.satoshis).collect()
+    }
+
+    fn params(fee_rate_sat_per_byte: f64) -> TxParams<'static> {
+        TxParams {
+            network: Network::Bitcoin,
+            fee_rate_sat_per_byte,
+            dust_threshold_sats: 546,
+            op_return_prefix: None,
+        }
+    }
+
+    #[test]
+    fn select_utxos_prefers_an_exact_match() {
+        // Largest-first would take 6000 + 5000; 5000 + 2000 needs no change
+        let candidates = [utxo(1, 6000), utxo(2, 5000), utxo(3, 2000)];
+        let selected = select_utxos(&candidates, 7000, 546).unwrap();
+        assert_eq!(sats(&selected), [5000, 2000]);
+    }
+
+    #[test]
+    fn select_utxos_leaves_change_above_dust_without_an_exact_match() {
+        let candidates = [utxo(1, 4000), utxo(2, 10_000)];
+        let selected = select_utxos(&candidates, 5000, 546).unwrap();
+        assert_eq!(sats(&selected), [10_000]);
+    }
+
+    #[test]
+    fn select_utxos_refuses_insufficient_funds() {
+        let candidates = [utxo(1, 1000), utxo(2, 2000)];
+        assert!(select_utxos(&candidates, 5000, 546).is_none());
+        assert!(select_utxos(&[], 1, 546).is_none());
+    }
+
+    #[test]
+    fn select_funding_utxos_covers_the_fee_of_every_input_it_picks() {
+        let candidates: Vec<BuyerUtxo> = (1..=20).map(|n| utxo(n, 400)).collect();
+        let params = params(1.0);
+
+        let selected = select_funding_utxos(&candidates, 1000, 500, params).unwrap();
+        let total: u64 = selected.iter().map(|u| u.satoshis).sum();
+        assert!(selected.len() > 1);
+        assert!(total >= funding_target(1000, 500, selected.len(), params.fee_rate_sat_per_byte));
+
+        // 20 inputs can't pay for themselves plus a 10k output
+        let needed = select_funding_utxos(&candidates, 10_000, 500, params).unwrap_err();
+        assert!(needed > 20 * 400);
+    }
+
+    #[test]
+    fn purchase_template_matches_the_listing_fees() {
+        let listing = create_listing(&listings_db(), 1, 100_000);
+        let fee_address = address(3);
+
+        let template = build_purchase_template(&listing, Some(&address(8)), &fee_address, Network::Bitcoin).unwrap();
+
+        assert_eq!(template.listing_id, listing.id);
+        assert_eq!(template.ordinal_input.txid, listing.ordinal_utxo.txid);
+        assert_eq!(template.ordinal_input.vout, listing.ordinal_utxo.vout);
+
+        let outputs: Vec<(&str, Option<&str>, u64)> = template
+            .outputs
+            .iter()
+            .map(|o| (o.role.as_str(), o.address.as_deref(), o.satoshis))
+            .collect();
+        assert_eq!(
+            outputs,
+            [
+                ("buyer_ordinal", Some(address(8).as_str()), 1),
+                ("seller", Some(listing.seller_address.as_str()), listing.fees.seller_receives),
+                ("marketplace_fee", Some(fee_address.as_str()), listing.fees.marketplace_fee + listing.fees.tip_amount),
+            ]
+        );
+        assert_eq!(template.total_fixed_sats, 1 + listing.fees.total_price);
+    }
+
+    #[test]
+    fn purchase_template_leaves_the_buyer_output_open_without_an_address() {
+        let listing = create_listing(&listings_db(), 1, 100_000);
+
+        let template = build_purchase_template(&listing, None, &address(3), Network::Bitcoin).unwrap();
+
+        assert_eq!(template.outputs[0].role, "buyer_ordinal");
+        assert!(template.outputs[0].address.is_none());
+        assert!(template.outputs[0].script_hex.is_none());
+    }
+
+    /// Values of `tx`'s outputs paying `address`
+    fn paid_to(tx_hex: &str, address: &str) -> Vec<u64> {
+        let tx: Transaction = bitcoin::consensus::deserialize(&hex::decode(tx_hex).unwrap()).unwrap();
+        let script = parse_address(address, AddressRole::BuyerPayment, Network::Bitcoin).unwrap().script_pubkey();
+        tx.output
+            .iter()
+            .filter(|o| o.script_pubkey == script)
+            .map(|o| o.value.to_sat())
+            .collect()
+    }
+
+    #[test]
+    fn split_change_makes_that_many_change_outputs() {
+        let listing = create_listing(&listings_db(), 1, 10_000);
+
+        let purchase = build_purchase_tx(
+            &listing,
+            &address(8),
+            &address(9),
+            vec![utxo(0xee, 200_000)],
+            &address(3),
+            params(0.5),
+            Some(3),
+        )
+        .unwrap();
+
+        let change = paid_to(&purchase.raw_tx_hex, &address(9));
+        assert_eq!(change.len(), 3);
+        assert!(change.iter().all(|&sats| sats >= 546));
+        assert!(change.iter().max().unwrap() - change.iter().min().unwrap() < 3);
+    }
+
+    #[test]
+    fn split_change_stops_where_parts_would_be_dust() {
+        let listing = create_listing(&listings_db(), 1, 10_000);
+        // Roughly 1500 sats of change: enough for two parts above dust, not three
+        let funding = listing.fees.total_price + 1_650;
+
+        let purchase = build_purchase_tx(
+            &listing,
+            &address(8),
+            &address(9),
+            vec![utxo(0xee, funding)],
+            &address(3),
+            params(0.5),
+            Some(3),
+        )
+        .unwrap();
+
+        assert_eq!(paid_to(&purchase.raw_tx_hex, &address(9)).len(), 2);
+    }
+
+    #[test]
+    fn equal_value_utxos_build_the_same_tx_in_any_order() {
+        let listing = create_listing(&listings_db(), 1, 10_000);
+        let mut candidates: Vec<BuyerUtxo> = (0xe0..0xe6).map(|n| utxo(n, 4_000)).collect();
+
+        let mut built = Vec::new();
+        for _ in 0..3 {
+            candidates.rotate_left(2);
+            candidates.swap(0, 3);
+            let base_fee = purchase_base_fee(std::slice::from_ref(&listing), params(0.5));
+            let selected =
+                select_funding_utxos(&candidates, listing.fees.total_price, base_fee, params(0.5)).unwrap();
+            let purchase =
+                build_purchase_tx(&listing, &address(8), &address(9), selected, &address(3), params(0.5), None).unwrap();
+            built.push(purchase.raw_tx_hex);
+        }
+
+        assert!(built.windows(2).all(|pair| pair[0] == pair[1]));
+    }
+
+    #[test]
+    fn sort_candidates_breaks_ties_by_outpoint() {
+        let mut utxos = vec![utxo(2, 500), utxo(1, 500), utxo(3, 900)];
+        utxos[1].vout = 1;
+        utxos.push(BuyerUtxo { vout: 0, ..utxos[1].clone() });
+
+        sort_candidates(&mut utxos);
+
+        let order: Vec<(u64, &str, u32)> = utxos.iter().map(|u| (u.satoshis, &u.txid[..2], u.vout)).collect();
+        assert_eq!(order, [(900, "03", 0), (500, "01", 0), (500, "01", 1), (500, "02", 0)]);
+    }
+
+    #[test]
+    fn lock_tx_spends_the_ordinal_into_the_lock_output() {
+        let listing = create_listing(&listings_db(), 1, 10_000);
+        let (prefix, suffix) = ([0x51u8, 0x52], [0x53u8]);
+        let params = params(0.5);
+        let base_fee = lock_base_fee(&listing, &prefix, &suffix, params).unwrap();
+        let funding = select_funding_utxos(&[utxo(0xee, 5_000)], 0, base_fee, params).unwrap();
+
+        let lock = build_lock_tx(&listing, funding, &prefix, &suffix, params).unwrap();
+        let tx: Transaction = bitcoin::consensus::deserialize(&hex::decode(&lock.raw_tx_hex).unwrap()).unwrap();
+
+        assert_eq!(tx.input[0].previous_output.txid.to_string(), listing.ordinal_utxo.txid);
+        assert_eq!(tx.input[0].previous_output.vout, listing.ordinal_utxo.vout);
+        assert_eq!(tx.input[1].previous_output.txid.to_string(), "ee".repeat(32));
+
+        let script = tx.output[0].script_pubkey.as_bytes();
+        assert_eq!(tx.output[0].value.to_sat(), 1);
+        assert_eq!(tx.output[0].script_pubkey, ordlock_script(&listing, &prefix, &suffix, Network::Bitcoin).unwrap());
+        assert!(script.starts_with(&prefix) && script.ends_with(&suffix));
+
+        // Change goes back to the seller's payment address, and the seller signs everything
+        assert_eq!(paid_to(&lock.raw_tx_hex, &listing.seller_address).len(), 1);
+        assert_eq!(lock.sig_requests.len(), 2);
+        assert!(lock.sig_requests.iter().all(|r| r.signer == InputSigner::Seller));
+    }
+}
@@ -0,0 +1,61 @@
+use crate::services::GorillaPoolClient;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{info, warn};
+
+/// Whether GorillaPool answered the last background probe. While it didn't, the
+/// API is degraded: cached reads are served, upstream-dependent writes refused.
+#[derive(Clone)]
+pub struct UpstreamHealth {
+    degraded: Arc<AtomicBool>,
+}
+
+impl UpstreamHealth {
+    pub fn new(gorillapool_reachable: bool) -> Self {
+        Self {
+            degraded: Arc::new(AtomicBool::new(!gorillapool_reachable)),
+        }
+    }
+
+    pub fn is_degraded(&self) -> bool {
+        self.degraded.load(Ordering::Relaxed)
+    }
+
+    /// Record a probe result, logging when the state changes
+    fn record(&self, reachable: bool) {
+        let was_degraded = self.degraded.swap(!reachable, Ordering::Relaxed);
+        match (was_degraded, reachable) {
+            (false, false) => warn!("GorillaPool unreachable; entering degraded mode"),
+            (true, true) => info!("GorillaPool reachable again; leaving degraded mode"),
+            _ => {}
+        }
+    }
+}
+
+/// Ping GorillaPool every `interval`, updating `health`
+pub fn spawn_upstream_probe(gorillapool: GorillaPoolClient, health: UpstreamHealth, interval: Duration) {
+    info!("Upstream probe running every {}s", interval.as_secs());
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The startup report has just pinged GorillaPool
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+            let reachable = match tokio::time::timeout(interval, gorillapool.ping()).await {
+                Ok(Ok(())) => true,
+                Ok(Err(e)) => {
+                    warn!("Upstream probe failed: {}", e);
+                    false
+                }
+                Err(_) => {
+                    warn!("Upstream probe timed out");
+                    false
+                }
+            };
+            health.record(reachable);
+        }
+    });
+}
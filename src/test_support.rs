@@ -0,0 +1,134 @@
+// Fixtures shared by the unit tests
+
+use crate::api::handlers::AppState;
+use crate::cache::CacheManager;
+use crate::config::Config;
+use crate::metrics::Metrics;
+use crate::models::{CreateListingRequest, Listing, OrdinalDetails, OrdinalUtxoRef, StartupReport};
+use crate::services::{GorillaPoolClient, ListingsDb, OrdinalService, PriceOracle, PurchaseCallbacks, UpstreamHealth};
+use base64::Engine;
+use bitcoin::hashes::Hash;
+use bitcoin::{Address, Network, PubkeyHash, ScriptBuf};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::sync::{broadcast, watch};
+
+/// Mainnet P2PKH address with every pubkey hash byte set to `n`
+pub fn address(n: u8) -> String {
+    p2pkh(n).to_string()
+}
+
+fn p2pkh(n: u8) -> Address {
+    let script = ScriptBuf::new_p2pkh(&PubkeyHash::from_byte_array([n; 20]));
+    Address::from_script(&script, Network::Bitcoin).unwrap()
+}
+
+/// Hex txid with every byte set to `n`
+pub fn txid(n: u8) -> String {
+    format!("{:02x}", n).repeat(32)
+}
+
+/// Details of ordinal `n` (origin `<txid(n)>_0`) held by `owner_address`
+pub fn ordinal(n: u8, owner_address: &str, collection_id: Option<&str>) -> OrdinalDetails {
+    let origin = format!("{}_0", txid(n));
+    OrdinalDetails {
+        origin_txid: txid(n),
+        origin_vout: 0,
+        txid: txid(n),
+        vout: 0,
+        owner_address: owner_address.to_string(),
+        satoshis: 1,
+        content_type: None,
+        content_size: None,
+        content_hash: None,
+        block_height: None,
+        inscription_number: None,
+        metadata: None,
+        collection_id: collection_id.map(str::to_string),
+        content_url: format!("/ordinal/{}/content", origin),
+        preview_url: format!("/ordinal/{}/preview", origin),
+        fetched_at: chrono::Utc::now(),
+        origin,
+    }
+}
+
+/// Listings database in a throwaway sled instance
+pub fn listings_db() -> ListingsDb {
+    let db = sled::Config::new().temporary(true).open().unwrap();
+    ListingsDb::new(Arc::new(db))
+}
+
+/// Listing request for ordinal `n`, sold by address `n` for `seller_wants_satoshis`
+pub fn listing_request(n: u8, seller_wants_satoshis: u64) -> CreateListingRequest {
+    let script = p2pkh(n).script_pubkey();
+    CreateListingRequest {
+        origin: format!("{}_0", txid(n)),
+        ordinal_utxo: OrdinalUtxoRef {
+            txid: txid(n),
+            vout: 0,
+            satoshis: 1,
+            script: base64::engine::general_purpose::STANDARD.encode(script.as_bytes()),
+        },
+        seller_wants_satoshis,
+        price_usd: None,
+        tip_percent: 0.0,
+        seller_address: address(n),
+        seller_ord_address: address(n),
+        signature: String::new(),
+        signed_at: 0,
+        auction_duration_secs: None,
+        idempotency_key: None,
+        expires_in_days: None,
+    }
+}
+
+/// Create an active listing from `listing_request(n, seller_wants_satoshis)`
+pub fn create_listing(db: &ListingsDb, n: u8, seller_wants_satoshis: u64) -> Listing {
+    db.create_listing(listing_request(n, seller_wants_satoshis), BTreeMap::new(), None, None)
+        .unwrap()
+}
+
+/// Serve `router` on a loopback port, with peer addresses available as in
+/// production, returning its base URL
+pub async fn serve(router: axum::Router) -> String {
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let app = router.into_make_service_with_connect_info::<std::net::SocketAddr>();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+    format!("http://{}", addr)
+}
+
+/// Handler state over `listings_db`, talking to whatever GorillaPool `config` names
+pub fn app_state(config: Config, listings_db: ListingsDb) -> AppState {
+    let metrics = Arc::new(Metrics::new());
+    let cache = Arc::new(CacheManager::new(&config));
+    let gorillapool = GorillaPoolClient::new(&config).unwrap().with_metrics(Arc::clone(&metrics));
+    let (listing_events, _) = broadcast::channel(16);
+    let (_, shutdown) = watch::channel(false);
+
+    AppState {
+        ordinal_service: OrdinalService::new(gorillapool, Arc::clone(&cache), config.clone()),
+        cache,
+        listings_db,
+        start_time: Instant::now(),
+        startup_report: Arc::new(StartupReport {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            generated_at: chrono::Utc::now(),
+            config: config.redacted_summary(),
+            active_listings: 0,
+            index_consistency: Default::default(),
+            gorillapool_reachable: true,
+            gorillapool_error: None,
+            db_path: config.db_path.clone(),
+            db_size_bytes: 0,
+        }),
+        price_oracle: PriceOracle::new(&config).unwrap(),
+        purchase_callbacks: PurchaseCallbacks::new().unwrap(),
+        upstream_health: UpstreamHealth::new(true),
+        config,
+        listing_events,
+        shutdown,
+        metrics,
+    }
+}
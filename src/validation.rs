@@ -0,0 +1,52 @@
+use bitcoin::{base58, Network};
+use thiserror::Error;
+
+/// Version byte for mainnet P2PKH addresses
+const MAINNET_P2PKH_VERSION: u8 = 0x00;
+
+/// Version byte for testnet and regtest P2PKH addresses
+const TESTNET_P2PKH_VERSION: u8 = 0x6f;
+
+/// Why an address failed validation
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum AddressError {
+    #[error("address is empty")]
+    Empty,
+    #[error("invalid base58 character {0:?}")]
+    InvalidCharacter(char),
+    #[error("bad checksum")]
+    BadChecksum,
+    #[error("invalid payload length {0} (expected 21 bytes)")]
+    InvalidLength(usize),
+    #[error("wrong network or address type (version byte {found:#04x}, expected P2PKH {expected:#04x})")]
+    WrongNetwork { found: u8, expected: u8 },
+}
+
+/// Validate a BSV P2PKH address for `network` by base58check-decoding it and
+/// checking the payload length and version byte
+pub fn validate_bsv_address(address: &str, network: Network) -> Result<(), AddressError> {
+    if address.is_empty() {
+        return Err(AddressError::Empty);
+    }
+
+    let payload = base58::decode_check(address).map_err(|e| match e {
+        base58::Error::BadByte(b) => AddressError::InvalidCharacter(b as char),
+        base58::Error::BadChecksum(_, _) => AddressError::BadChecksum,
+        base58::Error::TooShort(len) | base58::Error::InvalidLength(len) => AddressError::InvalidLength(len),
+        _ => AddressError::BadChecksum,
+    })?;
+
+    if payload.len() != 21 {
+        return Err(AddressError::InvalidLength(payload.len()));
+    }
+
+    let expected = match network {
+        Network::Bitcoin => MAINNET_P2PKH_VERSION,
+        _ => TESTNET_P2PKH_VERSION,
+    };
+    if payload[0] != expected {
+        return Err(AddressError::WrongNetwork { found: payload[0], expected });
+    }
+
+    Ok(())
+}